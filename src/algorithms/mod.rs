@@ -0,0 +1 @@
+pub mod shortest_path;
@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+use rand::SeedableRng;
+
+use crate::config::ComputeConfig;
+use crate::normal_graph::NormalUndiGraph;
+
+// eccentricities, double sweeps and the exact diameter live together
+pub use crate::eccentricity;
+
+impl NormalUndiGraph {
+    /// Hop distance from `s` to `t`, stopping the BFS once `t` is reached.
+    pub fn distance(&self, s: usize, t: usize) -> Option<usize> {
+        let mut dist = vec![usize::MAX; self.n];
+        let mut queue = VecDeque::from([s]);
+        dist[s] = 0;
+        while let Some(u) = queue.pop_front() {
+            if u == t {
+                return Some(dist[u]);
+            }
+            for &v in &self.adjs[u] {
+                if dist[v] == usize::MAX {
+                    dist[v] = dist[u] + 1;
+                    queue.push_back(v);
+                }
+            }
+        }
+        None
+    }
+    // mean distance over the ordered pairs reachable from `sources`
+    fn mean_distance_from(&self, sources: &[usize], config: &ComputeConfig) -> f64 {
+        let partials = config.par_map(sources.len(), |i| {
+            self.distances_from(sources[i])
+                .into_iter()
+                .filter(|&d| d != 0 && d != usize::MAX)
+                .fold((0usize, 0usize), |(sum, cnt), d| (sum + d, cnt + 1))
+        });
+        let (sum, cnt) = partials
+            .into_iter()
+            .fold((0, 0), |(s, c), (ps, pc)| (s + ps, c + pc));
+        if cnt == 0 {
+            0.0
+        } else {
+            sum as f64 / cnt as f64
+        }
+    }
+    /// Mean hop distance over all pairs of distinct nodes joined by a path,
+    /// one BFS per node.
+    pub fn average_path_length(&self, config: &ComputeConfig) -> f64 {
+        let sources: Vec<usize> = (0..self.n).collect();
+        self.mean_distance_from(&sources, config)
+    }
+    /// `average_path_length` estimated from BFS out of `samples` distinct
+    /// random sources, exact once `samples` reaches the node count.
+    pub fn average_path_length_sampled(
+        &self,
+        samples: usize,
+        seed: u64,
+        config: &ComputeConfig,
+    ) -> f64 {
+        if samples >= self.n {
+            return self.average_path_length(config);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let sources = sample(&mut rng, self.n, samples).into_vec();
+        self.mean_distance_from(&sources, config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shortest_paths() {
+        let config = ComputeConfig::serial();
+        for g in [
+            NormalUndiGraph::from_koch(3),
+            NormalUndiGraph::from_apollo(3),
+            NormalUndiGraph::from_watts_strogatz(200, 4, 0.1, 7),
+        ] {
+            let dist = g.distances_from(3);
+            assert_eq!(g.distance(3, g.n - 1), Some(dist[g.n - 1]));
+            let total: usize = (0..g.n)
+                .map(|u| g.distances_from(u).iter().sum::<usize>())
+                .sum();
+            let exact = total as f64 / (g.n * (g.n - 1)) as f64;
+            assert!((g.average_path_length(&config) - exact).abs() < 1e-9);
+            assert_eq!(
+                g.average_path_length_sampled(g.n, 1, &config),
+                g.average_path_length(&config)
+            );
+            let estimate = g.average_path_length_sampled(g.n / 2, 1, &config);
+            assert!((estimate - exact).abs() < 0.2 * exact);
+        }
+        let two = NormalUndiGraph::from_edges("two".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert_eq!(two.distance(0, 3), None);
+        assert_eq!(two.average_path_length(&config), 1.0);
+    }
+}
@@ -0,0 +1,184 @@
+use nalgebra::DMatrix;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::normal_graph::NormalUndiGraph;
+
+pub struct AlignOptions {
+    // weight of the topological term against the degree-similarity prior
+    pub alpha: f64,
+    pub iterations: usize,
+}
+
+impl Default for AlignOptions {
+    fn default() -> Self {
+        Self {
+            alpha: 0.6,
+            iterations: 30,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Alignment {
+    // mapping[u] is the image of node u of the first graph, None if unmatched
+    pub mapping: Vec<Option<usize>>,
+    pub conserved_edges: usize,
+    // conserved edges over the edges of the first graph
+    pub edge_correctness: f64,
+}
+
+impl NormalUndiGraph {
+    pub(crate) fn normalized_adjacency(&self) -> DMatrix<f64> {
+        let mut w = DMatrix::zeros(self.n, self.n);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &v in adj {
+                w[(v, u)] = 1.0 / adj.len() as f64;
+            }
+        }
+        w
+    }
+    /// IsoRank similarity `R = alpha W1 R W2^T + (1 - alpha) E` by power
+    /// iteration, where `W` are column-normalized adjacency matrices and `E` is
+    /// a degree-similarity prior. Entry (i, j) scores node i of `self` against
+    /// node j of `other`.
+    pub fn isorank(&self, other: &NormalUndiGraph, options: &AlignOptions) -> DMatrix<f64> {
+        let prior = DMatrix::from_fn(self.n, other.n, |i, j| {
+            let (a, b) = (self.adjs[i].len(), other.adjs[j].len());
+            if a.max(b) == 0 {
+                1.0
+            } else {
+                a.min(b) as f64 / a.max(b) as f64
+            }
+        });
+        let prior = &prior / prior.sum().max(f64::MIN_POSITIVE);
+        let w1 = self.normalized_adjacency();
+        let w2t = other.normalized_adjacency().transpose();
+        let mut r = prior.clone();
+        for _ in 0..options.iterations {
+            r = &w1 * &r * &w2t * options.alpha + &prior * (1.0 - options.alpha);
+            let total = r.sum();
+            if total > 0.0 {
+                r /= total;
+            }
+        }
+        r
+    }
+    /// Seed-and-extend alignment: matches pairs greedily, preferring pairs with
+    /// the most already-matched neighbour pairs and breaking ties by IsoRank
+    /// score, so the matching grows outwards from the best seed.
+    pub fn align(&self, other: &NormalUndiGraph, options: &AlignOptions) -> Alignment {
+        let r = self.isorank(other, options);
+        let mut pairs: Vec<(usize, usize)> = (0..self.n)
+            .flat_map(|i| (0..other.n).map(move |j| (i, j)))
+            .collect();
+        pairs.sort_by(|&(a, b), &(c, d)| r[(c, d)].total_cmp(&r[(a, b)]));
+        let mut rank = DMatrix::<usize>::zeros(self.n, other.n);
+        for (k, &(i, j)) in pairs.iter().enumerate() {
+            rank[(i, j)] = k;
+        }
+        let mut gain = DMatrix::<usize>::zeros(self.n, other.n);
+        let mut heap: BinaryHeap<(usize, Reverse<usize>, usize, usize)> = pairs
+            .iter()
+            .enumerate()
+            .map(|(k, &(i, j))| (0, Reverse(k), i, j))
+            .collect();
+        let mut mapping = vec![None; self.n];
+        let mut used = vec![false; other.n];
+        while let Some((g, _, u, v)) = heap.pop() {
+            if mapping[u].is_some() || used[v] || g != gain[(u, v)] {
+                continue;
+            }
+            mapping[u] = Some(v);
+            used[v] = true;
+            for &x in &self.adjs[u] {
+                if mapping[x].is_some() {
+                    continue;
+                }
+                for &y in other.adjs[v].iter().filter(|&&y| !used[y]) {
+                    gain[(x, y)] += 1;
+                    heap.push((gain[(x, y)], Reverse(rank[(x, y)]), x, y));
+                }
+            }
+        }
+        let conserved_edges = self.conserved_edges(other, &mapping);
+        Alignment {
+            edge_correctness: if self.m == 0 {
+                1.0
+            } else {
+                conserved_edges as f64 / self.m as f64
+            },
+            mapping,
+            conserved_edges,
+        }
+    }
+    /// Number of edges of `self` whose endpoints are both mapped onto an edge
+    /// of `other`.
+    pub fn conserved_edges(&self, other: &NormalUndiGraph, mapping: &[Option<usize>]) -> usize {
+        let mut cnt = 0;
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &v in adj.iter().filter(|&&v| u < v) {
+                if let (Some(a), Some(b)) = (mapping[u], mapping[v]) {
+                    if other.adjs[a].binary_search(&b).is_ok() {
+                        cnt += 1;
+                    }
+                }
+            }
+        }
+        cnt
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::seq::SliceRandom;
+    use rand::SeedableRng;
+
+    fn shuffled(g: &NormalUndiGraph, seed: u64) -> (NormalUndiGraph, Vec<usize>) {
+        let mut perm: Vec<usize> = (0..g.n).collect();
+        perm.shuffle(&mut StdRng::seed_from_u64(seed));
+        let edges: Vec<(usize, usize)> = g
+            .adjs
+            .iter()
+            .enumerate()
+            .flat_map(|(u, adj)| adj.iter().map(move |&v| (u, v)))
+            .map(|(u, v)| (perm[u], perm[v]))
+            .collect();
+        (
+            NormalUndiGraph::from_edges(g.name.clone(), g.n, &edges),
+            perm,
+        )
+    }
+
+    #[test]
+    fn test_align_permuted_copy() {
+        let path =
+            NormalUndiGraph::from_edges("P5".to_string(), 5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let (other, _) = shuffled(&path, 3);
+        let res = path.align(&other, &AlignOptions::default());
+        assert_eq!(res.conserved_edges, 4);
+        assert_eq!(res.edge_correctness, 1.0);
+
+        let g = NormalUndiGraph::from_koch(2);
+        let (other, _) = shuffled(&g, 7);
+        let res = g.align(&other, &AlignOptions::default());
+        let mut images: Vec<usize> = res.mapping.iter().map(|m| m.unwrap()).collect();
+        images.sort_unstable();
+        assert_eq!(images, (0..g.n).collect::<Vec<_>>());
+        assert!(res.edge_correctness > 0.9);
+    }
+
+    #[test]
+    fn test_align_different_sizes() {
+        let small = NormalUndiGraph::from_apollo(0);
+        let big = NormalUndiGraph::from_apollo(1);
+        let res = small.align(&big, &AlignOptions::default());
+        // K4 embeds in every Apollonian network
+        assert_eq!(res.conserved_edges, 6);
+        let res = big.align(&small, &AlignOptions::default());
+        assert_eq!(res.mapping.iter().filter(|m| m.is_some()).count(), 4);
+        assert_eq!(res.conserved_edges, 6);
+    }
+}
@@ -0,0 +1,305 @@
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+
+use bzip2::read::BzDecoder;
+use flate2::read::GzDecoder;
+use tar::Archive;
+use zip::ZipArchive;
+
+use super::error::GraphError;
+
+/// Container of a downloaded dataset, told apart by magic bytes rather than
+/// by the URL.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    // bzip2 stream, either a tarball or a single file
+    Bzip2,
+    // gzip stream, either a tarball or a single file
+    Gzip,
+    Tar,
+    Plain,
+}
+
+impl ArchiveKind {
+    pub fn detect(bytes: &[u8]) -> Self {
+        if bytes.starts_with(b"PK\x03\x04") {
+            ArchiveKind::Zip
+        } else if bytes.starts_with(b"BZh") {
+            ArchiveKind::Bzip2
+        } else if bytes.starts_with(&[0x1f, 0x8b]) {
+            ArchiveKind::Gzip
+        } else if is_tar(bytes) {
+            ArchiveKind::Tar
+        } else {
+            ArchiveKind::Plain
+        }
+    }
+}
+
+// POSIX tar headers carry "ustar" at offset 257
+fn is_tar(bytes: &[u8]) -> bool {
+    bytes.get(257..262) == Some(b"ustar")
+}
+
+// every file below `dir`, sorted so the pick is reproducible
+fn files_below(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Files produced by `unpack`.
+#[derive(Debug)]
+pub enum Unpacked {
+    // a compressed or plain single file, the dataset itself
+    Single(PathBuf),
+    // every file of an archive, sorted
+    Files(Vec<PathBuf>),
+}
+
+impl Unpacked {
+    /// The single file, or the archive files that `wanted` accepts.
+    pub fn candidates<F: Fn(&Path) -> bool>(self, wanted: F) -> Vec<PathBuf> {
+        match self {
+            Unpacked::Single(path) => vec![path],
+            Unpacked::Files(files) => files.into_iter().filter(|path| wanted(path)).collect(),
+        }
+    }
+}
+
+/// Unpacks `bytes` of `label` into `dir`; a single file is written to
+/// `dir/file_name`.
+pub fn unpack(
+    bytes: &[u8],
+    label: &str,
+    file_name: &str,
+    dir: &Path,
+) -> Result<Unpacked, GraphError> {
+    let unpack_err = |e: io::Error| GraphError::io(format!("Failed to unpack '{}'", label), e);
+    let single = |contents: &[u8]| -> Result<Unpacked, GraphError> {
+        let path = dir.join(file_name);
+        fs::write(&path, contents).map_err(unpack_err)?;
+        Ok(Unpacked::Single(path))
+    };
+    match ArchiveKind::detect(bytes) {
+        ArchiveKind::Zip => {
+            let mut archive = ZipArchive::new(Cursor::new(bytes)).map_err(|e| {
+                GraphError::Other(format!("Failed to open zip of '{}': {}", label, e))
+            })?;
+            archive.extract(dir).map_err(|e| {
+                GraphError::Other(format!("Failed to unpack zip of '{}': {}", label, e))
+            })?;
+        }
+        ArchiveKind::Tar => Archive::new(bytes).unpack(dir).map_err(unpack_err)?,
+        kind @ (ArchiveKind::Bzip2 | ArchiveKind::Gzip) => {
+            let inner = if kind == ArchiveKind::Bzip2 {
+                read_all(BzDecoder::new(bytes))
+            } else {
+                read_all(GzDecoder::new(bytes))
+            }
+            .map_err(unpack_err)?;
+            if !is_tar(&inner) {
+                return single(&inner);
+            }
+            Archive::new(inner.as_slice())
+                .unpack(dir)
+                .map_err(unpack_err)?;
+        }
+        ArchiveKind::Plain => return single(bytes),
+    }
+    Ok(Unpacked::Files(files_below(dir).map_err(unpack_err)?))
+}
+
+/// `unpack` returning the first candidate that `wanted` accepts.
+pub fn extract<F: Fn(&Path) -> bool>(
+    bytes: &[u8],
+    label: &str,
+    file_name: &str,
+    dir: &Path,
+    wanted: F,
+) -> Result<PathBuf, GraphError> {
+    unpack(bytes, label, file_name, dir)?
+        .candidates(wanted)
+        .into_iter()
+        .next()
+        .ok_or_else(|| GraphError::Other(format!("Failed to find a graph file in '{}'", label)))
+}
+
+/// Tarball in `bytes`, bzip2 or gzip compressed or plain, read through the
+/// decoder so entries stream out without holding the whole tarball.
+fn tar_stream<'a>(bytes: &'a [u8], label: &str) -> Result<Archive<impl Read + 'a>, GraphError> {
+    let mut decoder: Box<dyn Read + 'a> = match ArchiveKind::detect(bytes) {
+        ArchiveKind::Bzip2 => Box::new(BzDecoder::new(bytes)),
+        ArchiveKind::Gzip => Box::new(GzDecoder::new(bytes)),
+        _ => Box::new(bytes),
+    };
+    // peek at the first header block, then put it back in front
+    let mut head = Vec::with_capacity(512);
+    decoder
+        .by_ref()
+        .take(512)
+        .read_to_end(&mut head)
+        .map_err(|e| GraphError::io(format!("Failed to decompress '{}'", label), e))?;
+    if !is_tar(&head) {
+        return Err(GraphError::Other(format!("'{}' is not a tarball", label)));
+    }
+    Ok(Archive::new(Cursor::new(head).chain(decoder)))
+}
+
+/// Paths of the regular files in the tarball `bytes`, in archive order,
+/// decompressing in one pass.
+pub fn tar_entries(bytes: &[u8], label: &str) -> Result<Vec<PathBuf>, GraphError> {
+    let read_err =
+        |e: io::Error| GraphError::io(format!("Failed to read tarball of '{}'", label), e);
+    let mut paths = Vec::new();
+    for entry in tar_stream(bytes, label)?.entries().map_err(read_err)? {
+        let entry = entry.map_err(read_err)?;
+        if entry.header().entry_type().is_file() {
+            paths.push(entry.path().map_err(read_err)?.into_owned());
+        }
+    }
+    Ok(paths)
+}
+
+/// Runs `f` on the contents of the first regular file of the tarball
+/// `bytes` that `wanted` accepts, streaming it through the decoder in one
+/// pass; None when no entry is accepted.
+pub fn with_tar_entry<T, W, F>(
+    bytes: &[u8],
+    label: &str,
+    wanted: W,
+    f: F,
+) -> Result<Option<T>, GraphError>
+where
+    W: Fn(&Path) -> bool,
+    F: FnOnce(&mut dyn Read) -> Result<T, GraphError>,
+{
+    let read_err =
+        |e: io::Error| GraphError::io(format!("Failed to read tarball of '{}'", label), e);
+    for entry in tar_stream(bytes, label)?.entries().map_err(read_err)? {
+        let mut entry = entry.map_err(read_err)?;
+        if entry.header().entry_type().is_file() && wanted(&entry.path().map_err(read_err)?) {
+            return f(&mut entry).map(Some);
+        }
+    }
+    Ok(None)
+}
+
+fn read_all<R: Read>(mut r: R) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    r.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Last path segment of `url` without a `.gz`, `.bz2` or `.zip` suffix, used
+/// to name single-file downloads.
+pub fn file_name_of(url: &str) -> String {
+    let last = url.rsplit('/').next().unwrap_or(url);
+    let last = last.split(['?', '#']).next().unwrap_or(last);
+    let stem = [".gz", ".bz2", ".zip"]
+        .iter()
+        .find_map(|ext| last.strip_suffix(ext))
+        .unwrap_or(last);
+    if stem.is_empty() {
+        "graph".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Opens the file `extract` returned.
+pub(crate) fn open(path: &Path, label: &str) -> Result<File, GraphError> {
+    File::open(path).map_err(|e| GraphError::io(format!("Failed to open '{}'", label), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    #[test]
+    fn test_extract() {
+        let text = b"%%MatrixMarket matrix coordinate pattern symmetric\n3 3 2\n2 1\n3 2\n";
+        let mtx = |p: &Path| p.extension().and_then(|e| e.to_str()) == Some("mtx");
+
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(text).unwrap();
+        let gz = gz.finish().unwrap();
+        assert_eq!(ArchiveKind::detect(&gz), ArchiveKind::Gzip);
+        let dir = tempfile::tempdir().unwrap();
+        let name = file_name_of("http://example.org/data/g.mtx.gz");
+        assert_eq!(name, "g.mtx");
+        let path = extract(&gz, "gz", &name, dir.path(), mtx).unwrap();
+        assert_eq!(fs::read(path).unwrap(), text);
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::FileOptions::default();
+        zip.start_file("readme.txt", options).unwrap();
+        zip.write_all(b"not a graph").unwrap();
+        zip.start_file("inner/g.mtx", options).unwrap();
+        zip.write_all(text).unwrap();
+        let zip = zip.finish().unwrap().into_inner();
+        assert_eq!(ArchiveKind::detect(&zip), ArchiveKind::Zip);
+        let dir = tempfile::tempdir().unwrap();
+        let path = extract(&zip, "zip", "g", dir.path(), mtx).unwrap();
+        assert!(path.ends_with("inner/g.mtx"));
+        let dir = tempfile::tempdir().unwrap();
+        assert!(extract(&zip, "zip", "g", dir.path(), |_| false).is_err());
+
+        assert_eq!(ArchiveKind::detect(text), ArchiveKind::Plain);
+    }
+
+    #[test]
+    fn test_tar_streaming() {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, contents) in [
+            ("net/README", "x"),
+            ("net/out.a", "1 2\n"),
+            ("net/out.b", "2 3\n3 4\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, contents.as_bytes())
+                .unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+        let mut bz = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        bz.write_all(&tar).unwrap();
+        let bz = bz.finish().unwrap();
+
+        let paths = tar_entries(&bz, "net").unwrap();
+        assert_eq!(paths.len(), 3);
+        let out_b = |p: &Path| p == Path::new("net/out.b");
+        let contents = with_tar_entry(&bz, "net", out_b, |r| {
+            let mut s = String::new();
+            r.read_to_string(&mut s).unwrap();
+            Ok(s)
+        })
+        .unwrap();
+        assert_eq!(contents.as_deref(), Some("2 3\n3 4\n"));
+        let out_c = |p: &Path| p == Path::new("net/out.c");
+        assert!(with_tar_entry(&bz, "net", out_c, |_| Ok(()))
+            .unwrap()
+            .is_none());
+        assert_eq!(tar_entries(&tar, "net").unwrap(), paths);
+        assert!(tar_entries(b"plain text", "x").is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone, Copy, Debug)]
+pub enum NodeAttribute<'a> {
+    Scalar(&'a [f64]),
+    Categorical(&'a [usize]),
+}
+
+impl NormalUndiGraph {
+    /// Newman's assortativity coefficient of a node attribute over the edges:
+    /// the Pearson correlation across edge endpoints for scalar values, and
+    /// `(sum e_ii - sum a_i^2) / (1 - sum a_i^2)` for categories. NaN when the
+    /// coefficient is undefined, e.g. all endpoints share one value.
+    pub fn attribute_assortativity(&self, values: NodeAttribute) -> Result<f64, String> {
+        let len = match values {
+            NodeAttribute::Scalar(x) => x.len(),
+            NodeAttribute::Categorical(c) => c.len(),
+        };
+        if len != self.n {
+            return Err(format!(
+                "Graph '{}' has {} nodes but {} attribute values were given",
+                self.name, self.n, len
+            ));
+        }
+        if self.m == 0 {
+            return Ok(f64::NAN);
+        }
+        let ends = 2.0 * self.m as f64;
+        match values {
+            NodeAttribute::Scalar(x) => {
+                // each edge counted in both orientations makes the moments symmetric
+                let (mut sum, mut sum_sq, mut cross) = (0.0, 0.0, 0.0);
+                for (u, adj) in self.adjs.iter().enumerate() {
+                    for &v in adj {
+                        sum += x[u];
+                        sum_sq += x[u] * x[u];
+                        cross += x[u] * x[v];
+                    }
+                }
+                let mean = sum / ends;
+                let var = sum_sq / ends - mean * mean;
+                Ok((cross / ends - mean * mean) / var)
+            }
+            NodeAttribute::Categorical(c) => {
+                let mut same = 0usize;
+                let mut ends_per_class: HashMap<usize, usize> = HashMap::new();
+                for (u, adj) in self.adjs.iter().enumerate() {
+                    *ends_per_class.entry(c[u]).or_insert(0) += adj.len();
+                    same += adj.iter().filter(|&&v| c[v] == c[u]).count();
+                }
+                let expected: f64 = ends_per_class
+                    .values()
+                    .map(|&a| (a as f64 / ends).powi(2))
+                    .sum();
+                Ok((same as f64 / ends - expected) / (1.0 - expected))
+            }
+        }
+    }
+    pub fn degree_assortativity(&self) -> f64 {
+        let degrees: Vec<f64> = self.adjs.iter().map(|adj| adj.len() as f64).collect();
+        self.attribute_assortativity(NodeAttribute::Scalar(&degrees))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_degree_assortativity() {
+        // stars are perfectly disassortative
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        assert!((star.degree_assortativity() + 1.0).abs() < 1e-12);
+        assert!(NormalUndiGraph::from_apollo(0)
+            .degree_assortativity()
+            .is_nan());
+        assert!(NormalUndiGraph::from_pseudofractal(3).degree_assortativity() < 0.0);
+    }
+
+    #[test]
+    fn test_attribute_assortativity() {
+        // two triangles joined by one edge, labelled by triangle
+        let g = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3), (2, 3)],
+        );
+        let labels = [0, 0, 0, 1, 1, 1];
+        let r = g
+            .attribute_assortativity(NodeAttribute::Categorical(&labels))
+            .unwrap();
+        // e_ii = 3/7 for both classes, a_i = 1/2
+        assert!((r - (6.0 / 7.0 - 0.5) / 0.5).abs() < 1e-12);
+        let scalar = [0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+        let rs = g
+            .attribute_assortativity(NodeAttribute::Scalar(&scalar))
+            .unwrap();
+        assert!((rs - r).abs() < 1e-12);
+        assert!(g
+            .attribute_assortativity(NodeAttribute::Categorical(&[0, 1]))
+            .is_err());
+    }
+}
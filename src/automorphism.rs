@@ -0,0 +1,195 @@
+use super::dsu::DSU;
+use super::normal_graph::NormalUndiGraph;
+
+/// Evaluates `f` only on orbit representatives, given `orbits[u]` as the
+/// smallest node of the orbit of `u` as returned by `automorphism_orbits`, so
+/// that cached orbits can be reused across computations.
+pub fn broadcast_per_orbit<T: Clone>(orbits: &[usize], mut f: impl FnMut(usize) -> T) -> Vec<T> {
+    let mut values: Vec<T> = Vec::with_capacity(orbits.len());
+    for (u, &rep) in orbits.iter().enumerate() {
+        let value = if rep == u { f(u) } else { values[rep].clone() };
+        values.push(value);
+    }
+    values
+}
+
+impl NormalUndiGraph {
+    // Coarsest equitable refinement of `colors` by colour refinement, colours
+    // renumbered by the rank of their signature so that two refinements of
+    // isomorphic colourings assign the same colours.
+    fn refine(&self, colors: &[usize]) -> Vec<usize> {
+        let mut colors = colors.to_vec();
+        let mut num_colors = 0;
+        loop {
+            let signatures: Vec<(usize, Vec<usize>)> = (0..self.n)
+                .map(|u| {
+                    let mut around: Vec<usize> = self.adjs[u].iter().map(|&v| colors[v]).collect();
+                    around.sort_unstable();
+                    (colors[u], around)
+                })
+                .collect();
+            let mut distinct: Vec<&(usize, Vec<usize>)> = signatures.iter().collect();
+            distinct.sort_unstable();
+            distinct.dedup();
+            let refined = signatures
+                .iter()
+                .map(|s| distinct.binary_search(&s).unwrap())
+                .collect();
+            if distinct.len() == num_colors {
+                return refined;
+            }
+            num_colors = distinct.len();
+            colors = refined;
+        }
+    }
+    // Automorphism mapping every node of colour `c` under `a` to a node of
+    // colour `c` under `b`, by individualization and refinement.
+    fn find_isomorphism(&self, a: &[usize], b: &[usize]) -> Option<Vec<usize>> {
+        let (a, b) = (self.refine(a), self.refine(b));
+        let (mut hist_a, mut hist_b) = (a.clone(), b.clone());
+        hist_a.sort_unstable();
+        hist_b.sort_unstable();
+        if hist_a != hist_b {
+            return None;
+        }
+        let mut counts = vec![0; self.n];
+        for &c in &a {
+            counts[c] += 1;
+        }
+        let Some(cell) = (0..self.n).find(|&c| counts[c] > 1) else {
+            let mut node_of = vec![0; self.n];
+            for (v, &c) in b.iter().enumerate() {
+                node_of[c] = v;
+            }
+            let perm: Vec<usize> = a.iter().map(|&c| node_of[c]).collect();
+            let preserves_edges = self.adjs.iter().enumerate().all(|(u, adj)| {
+                adj.iter()
+                    .all(|&v| self.adjs[perm[u]].binary_search(&perm[v]).is_ok())
+            });
+            return preserves_edges.then_some(perm);
+        };
+        let x = a.iter().position(|&c| c == cell).unwrap();
+        let mut a = a;
+        a[x] = self.n;
+        (0..self.n).filter(|&y| b[y] == cell).find_map(|y| {
+            let mut b = b.clone();
+            b[y] = self.n;
+            self.find_isomorphism(&a, &b)
+        })
+    }
+    // Some automorphism respecting `colors` that maps `u` to `v`, if any.
+    fn automorphism_mapping(&self, colors: &[usize], u: usize, v: usize) -> Option<Vec<usize>> {
+        let (mut a, mut b) = (colors.to_vec(), colors.to_vec());
+        a[u] = self.n;
+        b[v] = self.n;
+        self.find_isomorphism(&a, &b)
+    }
+    /// Orbits of the automorphism group: `orbits[u]` is the smallest node
+    /// that some automorphism maps to `u`. Candidates come from colour
+    /// refinement and are confirmed by an exact backtracking search, so this
+    /// suits graphs of moderate size.
+    pub fn automorphism_orbits(&self) -> Vec<usize> {
+        let base = self.refine(&vec![0; self.n]);
+        let mut dsu: DSU<usize> = DSU::new();
+        for u in 0..self.n {
+            dsu.add(u);
+        }
+        for u in 0..self.n {
+            for v in u + 1..self.n {
+                if base[v] != base[u] || dsu.find(u) == dsu.find(v) {
+                    continue;
+                }
+                if let Some(perm) = self.automorphism_mapping(&base, u, v) {
+                    for (i, &j) in perm.iter().enumerate() {
+                        dsu.union(i, j);
+                    }
+                }
+            }
+        }
+        let mut smallest = vec![usize::MAX; self.n];
+        let roots: Vec<usize> = (0..self.n).map(|u| dsu.find(u)).collect();
+        for (u, &r) in roots.iter().enumerate() {
+            smallest[r] = smallest[r].min(u);
+        }
+        roots.iter().map(|&r| smallest[r]).collect()
+    }
+    /// Evaluates an isomorphism-invariant per-node `f` once per automorphism
+    /// orbit and copies the result to the other nodes of the orbit.
+    pub fn compute_per_orbit<T: Clone>(&self, f: impl FnMut(usize) -> T) -> Vec<T> {
+        broadcast_per_orbit(&self.automorphism_orbits(), f)
+    }
+    /// Order of the automorphism group, as a float since it overflows
+    /// integers on the fractal families. Multiplies orbit sizes along a chain
+    /// of point stabilizers.
+    pub fn automorphism_group_size(&self) -> f64 {
+        let mut colors = self.refine(&vec![0; self.n]);
+        let mut size = 1.0;
+        loop {
+            let mut counts = vec![0; self.n];
+            for &c in &colors {
+                counts[c] += 1;
+            }
+            let Some(cell) = (0..self.n).find(|&c| counts[c] > 1) else {
+                return size;
+            };
+            let x = colors.iter().position(|&c| c == cell).unwrap();
+            let orbit = (0..self.n)
+                .filter(|&y| {
+                    y == x
+                        || colors[y] == cell && self.automorphism_mapping(&colors, x, y).is_some()
+                })
+                .count();
+            size *= orbit as f64;
+            colors[x] = self.n;
+            colors = self.refine(&colors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_automorphism_orbits() {
+        let path = NormalUndiGraph::from_edges("P4".to_string(), 4, &[(0, 1), (1, 2), (2, 3)]);
+        assert_eq!(path.automorphism_orbits(), vec![0, 1, 1, 0]);
+        assert_eq!(path.automorphism_group_size(), 2.0);
+        // colour refinement cannot split these regular graphs, the search must
+        let two_triangles = NormalUndiGraph::from_ring_of_cliques(2, 3);
+        let triangles = NormalUndiGraph::from_edges(
+            "2C3".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)],
+        );
+        assert_eq!(triangles.automorphism_orbits(), vec![0; 6]);
+        assert_eq!(triangles.automorphism_group_size(), 72.0);
+        assert_eq!(two_triangles.automorphism_orbits(), vec![0, 1, 0, 0, 1, 0]);
+        let pseudofractal = NormalUndiGraph::from_pseudofractal(2);
+        let orbits = pseudofractal.automorphism_orbits();
+        assert_eq!(orbits, vec![0, 0, 0, 3, 3, 3, 6, 6, 6, 9, 9, 9, 9, 9, 9]);
+        assert_eq!(pseudofractal.automorphism_group_size(), 6.0);
+        // symmetries of the central triangle times a swap in each outer one
+        assert_eq!(
+            NormalUndiGraph::from_koch(1).automorphism_group_size(),
+            48.0
+        );
+    }
+
+    #[test]
+    fn test_compute_per_orbit() {
+        let g = NormalUndiGraph::from_koch(2);
+        let mut calls = 0;
+        let eccentricities = g.compute_per_orbit(|u| {
+            calls += 1;
+            g.distances_from(u).into_iter().max().unwrap()
+        });
+        assert_eq!(eccentricities, g.eccentricities().unwrap());
+        let orbits = g.automorphism_orbits();
+        let num_orbits = (0..g.n).filter(|&u| orbits[u] == u).count();
+        assert_eq!(calls, num_orbits);
+        assert!(num_orbits < g.n / 4);
+        let degrees = broadcast_per_orbit(&orbits, |u| g.adjs[u].len());
+        assert_eq!(degrees, g.degrees());
+    }
+}
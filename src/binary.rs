@@ -0,0 +1,305 @@
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use super::csr::CsrGraph;
+use super::error::GraphError;
+use super::normal_graph::NormalUndiGraph;
+
+// `.ggb` layout, every integer a little-endian u64: the magic, n, m, the
+// name length, the UTF-8 name padded to 8 bytes, the n + 1 CSR offsets and
+// the 2m sorted neighbours. Offsets and neighbours are 8-byte aligned so a
+// mapped file can be read in place.
+pub const MAGIC: &[u8; 8] = b"GGBCSR01";
+const HEADER_LEN: usize = 32;
+
+fn padded(len: usize) -> usize {
+    len.div_ceil(8) * 8
+}
+
+fn malformed(file: &Path, what: &str) -> GraphError {
+    GraphError::Other(format!(
+        "'{}' is not a valid .ggb file: {}",
+        file.display(),
+        what
+    ))
+}
+
+fn word(bytes: &[u8], i: usize) -> u64 {
+    u64::from_le_bytes(bytes[8 * i..8 * i + 8].try_into().unwrap())
+}
+
+// n, m and the name length after checking the magic
+fn parse_header(file: &Path, head: &[u8]) -> Result<(usize, usize, usize), GraphError> {
+    if &head[..8] != MAGIC {
+        return Err(malformed(file, "bad magic"));
+    }
+    let (n, m, name_len) = (word(head, 1), word(head, 2), word(head, 3));
+    let fits = |x: u64| usize::try_from(x).map_err(|_| malformed(file, "size overflow"));
+    let (n, m, name_len) = (fits(n)?, fits(m)?, fits(name_len)?);
+    if n.checked_add(1).is_none() || m.checked_mul(2).is_none() {
+        return Err(malformed(file, "size overflow"));
+    }
+    Ok((n, m, name_len))
+}
+
+// byte position of the offsets, checking that the file of `file_len` bytes
+// holds exactly the name, offsets and neighbours the header announces
+fn body_start(
+    file: &Path,
+    n: usize,
+    m: usize,
+    name_len: usize,
+    file_len: u64,
+) -> Result<usize, GraphError> {
+    let start = name_len
+        .div_ceil(8)
+        .checked_mul(8)
+        .and_then(|name| name.checked_add(HEADER_LEN));
+    let expected = n
+        .checked_add(1 + 2 * m)
+        .and_then(|words| words.checked_mul(8))
+        .zip(start)
+        .and_then(|(body, start)| body.checked_add(start));
+    match (start, expected) {
+        (Some(start), Some(len)) if len as u64 == file_len => Ok(start),
+        _ => Err(malformed(file, "file size does not match header")),
+    }
+}
+
+// offsets from 0 to 2m without decreasing, neighbours within range and
+// strictly increasing per node
+fn validate(
+    file: &Path,
+    n: usize,
+    m: usize,
+    offsets: &[usize],
+    neighbors: &[usize],
+) -> Result<(), GraphError> {
+    if offsets[0] != 0 || offsets[n] != 2 * m || offsets.windows(2).any(|w| w[0] > w[1]) {
+        return Err(malformed(file, "inconsistent offsets"));
+    }
+    if neighbors.iter().any(|&v| v >= n) {
+        return Err(malformed(file, "neighbour out of range"));
+    }
+    let unsorted = offsets
+        .windows(2)
+        .any(|w| neighbors[w[0]..w[1]].windows(2).any(|p| p[0] >= p[1]));
+    if unsorted {
+        return Err(malformed(file, "neighbours not sorted"));
+    }
+    Ok(())
+}
+
+impl NormalUndiGraph {
+    /// Writes the graph in the binary `.ggb` format.
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P) -> Result<(), GraphError> {
+        let path = path.as_ref();
+        let io_err = |e| GraphError::io(format!("Failed to write '{}'", path.display()), e);
+        let mut w = BufWriter::new(File::create(path).map_err(io_err)?);
+        let write = |w: &mut BufWriter<File>| -> io::Result<()> {
+            w.write_all(MAGIC)?;
+            for x in [self.n, self.m, self.name.len()] {
+                w.write_all(&(x as u64).to_le_bytes())?;
+            }
+            w.write_all(self.name.as_bytes())?;
+            w.write_all(&[0u8; 8][..padded(self.name.len()) - self.name.len()])?;
+            let mut offset = 0u64;
+            w.write_all(&offset.to_le_bytes())?;
+            for adj in &self.adjs {
+                offset += adj.len() as u64;
+                w.write_all(&offset.to_le_bytes())?;
+            }
+            for &v in self.adjs.iter().flatten() {
+                w.write_all(&(v as u64).to_le_bytes())?;
+            }
+            w.flush()
+        };
+        write(&mut w).map_err(io_err)
+    }
+    /// Reads a graph written by `save_binary`.
+    pub fn load_binary<P: AsRef<Path>>(path: P) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        let io_err = |e| GraphError::io(format!("Failed to read '{}'", path.display()), e);
+        let file = File::open(path).map_err(io_err)?;
+        let file_len = file.metadata().map_err(io_err)?.len();
+        let mut r = BufReader::new(file);
+        let mut head = [0u8; HEADER_LEN];
+        r.read_exact(&mut head).map_err(io_err)?;
+        let (n, m, name_len) = parse_header(path, &head)?;
+        // nothing is allocated before the sizes are checked against the file
+        let start = body_start(path, n, m, name_len, file_len)?;
+        let mut name = vec![0u8; start - HEADER_LEN];
+        r.read_exact(&mut name).map_err(io_err)?;
+        name.truncate(name_len);
+        let name = String::from_utf8(name).map_err(|_| malformed(path, "name is not UTF-8"))?;
+        let mut read_words = |len: usize| -> Result<Vec<usize>, GraphError> {
+            let mut buf = [0u8; 8];
+            (0..len)
+                .map(|_| {
+                    r.read_exact(&mut buf).map_err(io_err)?;
+                    usize::try_from(u64::from_le_bytes(buf))
+                        .map_err(|_| malformed(path, "size overflow"))
+                })
+                .collect()
+        };
+        let offsets = read_words(n + 1)?;
+        let neighbors = read_words(2 * m)?;
+        validate(path, n, m, &offsets, &neighbors)?;
+        if r.read(&mut [0u8]).map_err(io_err)? != 0 {
+            return Err(malformed(path, "trailing bytes"));
+        }
+        let adjs = offsets
+            .windows(2)
+            .map(|w| neighbors[w[0]..w[1]].to_vec())
+            .collect();
+        Ok(Self { name, n, m, adjs })
+    }
+    /// Maps a `.ggb` file into memory without copying its adjacency, see
+    /// [`MmapGraph`].
+    pub fn load_mmap<P: AsRef<Path>>(path: P) -> Result<MmapGraph, GraphError> {
+        MmapGraph::open(path)
+    }
+}
+
+/// Read-only CSR graph backed by a memory-mapped `.ggb` file; neighbour
+/// slices point straight into the mapping. The file must not be modified
+/// while mapped. Only available on 64-bit little-endian targets, where the
+/// on-disk words are native `usize`.
+pub struct MmapGraph {
+    pub name: String,
+    pub n: usize,
+    pub m: usize,
+    mmap: Mmap,
+    // byte position of the offsets in the mapping
+    start: usize,
+}
+
+impl MmapGraph {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        if cfg!(not(all(
+            target_pointer_width = "64",
+            target_endian = "little"
+        ))) {
+            return Err(GraphError::Other(
+                "Mapping .ggb files needs a 64-bit little-endian target".to_string(),
+            ));
+        }
+        let io_err = |e| GraphError::io(format!("Failed to map '{}'", path.display()), e);
+        let file = File::open(path).map_err(io_err)?;
+        // SAFETY: the mapping is only read, and callers are told not to
+        // modify the file while it is mapped
+        let mmap = unsafe { Mmap::map(&file) }.map_err(io_err)?;
+        if mmap.len() < HEADER_LEN {
+            return Err(malformed(path, "truncated header"));
+        }
+        let (n, m, name_len) = parse_header(path, &mmap[..HEADER_LEN])?;
+        let start = body_start(path, n, m, name_len, mmap.len() as u64)?;
+        let name = std::str::from_utf8(&mmap[HEADER_LEN..HEADER_LEN + name_len])
+            .map_err(|_| malformed(path, "name is not UTF-8"))?
+            .to_string();
+        let graph = Self {
+            name,
+            n,
+            m,
+            mmap,
+            start,
+        };
+        validate(path, n, m, graph.offsets(), graph.all_neighbors())?;
+        Ok(graph)
+    }
+    fn words(&self) -> &[usize] {
+        // SAFETY: any bit pattern is a valid usize; the mapping is page
+        // aligned and `start` a multiple of 8, so the prefix is empty
+        let (prefix, words, _) = unsafe { self.mmap[self.start..].align_to::<usize>() };
+        debug_assert!(prefix.is_empty());
+        words
+    }
+    pub fn offsets(&self) -> &[usize] {
+        &self.words()[..self.n + 1]
+    }
+    fn all_neighbors(&self) -> &[usize] {
+        &self.words()[self.n + 1..]
+    }
+    pub fn neighbors(&self, u: usize) -> &[usize] {
+        let offsets = self.offsets();
+        &self.all_neighbors()[offsets[u]..offsets[u + 1]]
+    }
+    pub fn degree(&self, u: usize) -> usize {
+        let offsets = self.offsets();
+        offsets[u + 1] - offsets[u]
+    }
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.neighbors(u).binary_search(&v).is_ok()
+    }
+    pub fn to_csr(&self) -> CsrGraph {
+        CsrGraph {
+            name: self.name.clone(),
+            n: self.n,
+            m: self.m,
+            offsets: self.offsets().to_vec(),
+            neighbors: self.all_neighbors().to_vec(),
+        }
+    }
+    pub fn to_normal(&self) -> NormalUndiGraph {
+        self.to_csr().to_normal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("apollo.ggb");
+        let mut g = NormalUndiGraph::from_apollo(3);
+        g.name = "apollo-3".to_string();
+        g.save_binary(&path).unwrap();
+        let loaded = NormalUndiGraph::load_binary(&path).unwrap();
+        assert_eq!(
+            (loaded.name.as_str(), loaded.n, loaded.m),
+            ("apollo-3", g.n, g.m)
+        );
+        assert_eq!(loaded.adjs, g.adjs);
+
+        let mapped = NormalUndiGraph::load_mmap(&path).unwrap();
+        assert_eq!((mapped.n, mapped.m), (g.n, g.m));
+        for u in 0..g.n {
+            assert_eq!(mapped.neighbors(u), &g.adjs[u][..]);
+        }
+        assert!(mapped.has_edge(0, g.adjs[0][0]));
+        assert_eq!(mapped.to_normal().adjs, g.adjs);
+
+        // truncation and corruption are rejected by both loaders
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+        assert!(NormalUndiGraph::load_binary(&path).is_err());
+        assert!(NormalUndiGraph::load_mmap(&path).is_err());
+        let mut corrupted = bytes.clone();
+        let last = corrupted.len() - 8;
+        corrupted[last..].copy_from_slice(&(g.n as u64).to_le_bytes());
+        std::fs::write(&path, &corrupted).unwrap();
+        assert!(NormalUndiGraph::load_binary(&path).is_err());
+        assert!(NormalUndiGraph::load_mmap(&path).is_err());
+
+        // a huge name length fails before allocating, unsorted lists fail
+        let mut huge = bytes.clone();
+        huge[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &huge).unwrap();
+        assert!(NormalUndiGraph::load_binary(&path).is_err());
+        assert!(NormalUndiGraph::load_mmap(&path).is_err());
+        let mut unsorted = bytes.clone();
+        let first = 32 + 8 * (g.name.len().div_ceil(8) + g.n + 1);
+        let (a, b) = (g.adjs[0][0] as u64, g.adjs[0][1] as u64);
+        unsorted[first..first + 8].copy_from_slice(&b.to_le_bytes());
+        unsorted[first + 8..first + 16].copy_from_slice(&a.to_le_bytes());
+        std::fs::write(&path, &unsorted).unwrap();
+        assert!(NormalUndiGraph::load_binary(&path).is_err());
+        assert!(NormalUndiGraph::load_mmap(&path).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use super::dsu::DSU;
+use super::weighted_graph::WeightedUndiGraph;
+
+// Edges of `g` by increasing weight, ties by endpoints.
+fn sorted_edges(g: &WeightedUndiGraph) -> Vec<(usize, usize, f64)> {
+    let mut edges: Vec<(usize, usize, f64)> = Vec::with_capacity(g.m);
+    for (u, adj) in g.adjs.iter().enumerate() {
+        edges.extend(adj.iter().filter(|&&(v, _)| u < v).map(|&(v, w)| (u, v, w)));
+    }
+    edges.sort_by(|a, b| a.2.total_cmp(&b.2).then((a.0, a.1).cmp(&(b.0, b.1))));
+    edges
+}
+
+/// Smallest `w` such that `s` and `t` are connected by edges of weight at
+/// most `w`, found by adding edges in increasing weight to a DSU. `Some(-inf)`
+/// when `s == t` and None when they are disconnected.
+pub fn bottleneck_threshold(g: &WeightedUndiGraph, s: usize, t: usize) -> Option<f64> {
+    if s == t {
+        return Some(f64::NEG_INFINITY);
+    }
+    let mut dsu: DSU<usize> = DSU::new();
+    for (u, v, w) in sorted_edges(g) {
+        if dsu.union(u, v) && dsu.find(s) == dsu.find(t) {
+            return Some(w);
+        }
+    }
+    None
+}
+
+/// Smallest `w` at which the edges of weight at most `w` connect the whole
+/// graph, i.e. the largest weight of a minimum bottleneck spanning tree.
+/// None when the graph is disconnected.
+pub fn connectivity_threshold(g: &WeightedUndiGraph) -> Option<f64> {
+    let forest = bottleneck_spanning_forest(g);
+    if forest.len() + 1 < g.n {
+        return None;
+    }
+    Some(forest.last().map_or(f64::NEG_INFINITY, |e| e.2))
+}
+
+/// Kruskal spanning forest, which is a minimum bottleneck spanning forest:
+/// the tree path between any two nodes minimizes its heaviest edge. Edges
+/// come in increasing weight.
+pub fn bottleneck_spanning_forest(g: &WeightedUndiGraph) -> Vec<(usize, usize, f64)> {
+    let mut dsu: DSU<usize> = DSU::new();
+    sorted_edges(g)
+        .into_iter()
+        .filter(|&(u, v, _)| dsu.union(u, v))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bottleneck_threshold() {
+        // two routes from 0 to 3: 0-1-3 with max 5 and 0-2-3 with max 4
+        let g = WeightedUndiGraph::from_edges(
+            "w".to_string(),
+            5,
+            &[(0, 1, 1.0), (1, 3, 5.0), (0, 2, 4.0), (2, 3, 2.0)],
+        );
+        assert_eq!(bottleneck_threshold(&g, 0, 3), Some(4.0));
+        assert_eq!(bottleneck_threshold(&g, 0, 1), Some(1.0));
+        assert_eq!(bottleneck_threshold(&g, 0, 4), None);
+        assert_eq!(connectivity_threshold(&g), None);
+        let forest = bottleneck_spanning_forest(&g);
+        assert_eq!(forest, vec![(0, 1, 1.0), (2, 3, 2.0), (0, 2, 4.0)]);
+        let mut g = g;
+        g.add_edge(4, 1, 3.0);
+        assert_eq!(connectivity_threshold(&g), Some(4.0));
+    }
+}
@@ -0,0 +1,239 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::mem::size_of;
+use std::path::PathBuf;
+
+use tempfile::TempDir;
+
+use super::canonical::edge_checksum;
+use super::config::ComputeConfig;
+use super::csr::CsrGraph;
+use super::error::GraphError;
+use super::io::{parse_edges, GraphFormat};
+
+/// Collects edges into a `CsrGraph` without a hash set of edges. Edges are
+/// buffered up to the memory budget of the config, spilled to disk as sorted
+/// runs and merged with duplicates removed when the graph is built.
+pub struct GraphBuilder {
+    name: String,
+    config: ComputeConfig,
+    // edges held before a spill, unbounded without a memory budget
+    capacity: usize,
+    buffer: Vec<(usize, usize)>,
+    spill_dir: Option<TempDir>,
+    runs: Vec<PathBuf>,
+}
+
+// One sorted run on disk, read back as little-endian `u64` pairs.
+struct Run {
+    reader: BufReader<File>,
+}
+
+impl Run {
+    fn next_edge(&mut self) -> io::Result<Option<(usize, usize)>> {
+        let mut buf = [0u8; 16];
+        match self.reader.read_exact(&mut buf) {
+            Ok(()) => {
+                let u = u64::from_le_bytes(buf[..8].try_into().unwrap());
+                let v = u64::from_le_bytes(buf[8..].try_into().unwrap());
+                Ok(Some((u as usize, v as usize)))
+            }
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl GraphBuilder {
+    pub fn new(name: &str, config: &ComputeConfig) -> Self {
+        let capacity = match config.memory_budget {
+            Some(budget) => (budget / size_of::<(usize, usize)>()).max(1),
+            None => usize::MAX,
+        };
+        Self {
+            name: name.to_string(),
+            config: config.clone(),
+            capacity,
+            buffer: Vec::new(),
+            spill_dir: None,
+            runs: Vec::new(),
+        }
+    }
+    /// Adds the undirected edge `(u, v)`; self-loops are dropped and repeated
+    /// edges kept only once.
+    pub fn push_edge(&mut self, u: usize, v: usize) -> Result<(), GraphError> {
+        if u == v {
+            return Ok(());
+        }
+        self.buffer.push((u.min(v), u.max(v)));
+        if self.buffer.len() >= self.capacity {
+            self.spill()?;
+        }
+        Ok(())
+    }
+    pub fn num_runs(&self) -> usize {
+        self.runs.len()
+    }
+    fn spill(&mut self) -> Result<(), GraphError> {
+        self.buffer.sort_unstable();
+        self.buffer.dedup();
+        if self.spill_dir.is_none() {
+            self.spill_dir = Some(self.config.tempdir()?);
+        }
+        let path = self
+            .spill_dir
+            .as_ref()
+            .unwrap()
+            .path()
+            .join(format!("run{}", self.runs.len()));
+        let io_err = |e| GraphError::io(format!("Failed to spill edges of '{}'", self.name), e);
+        let mut wf = BufWriter::new(File::create(&path).map_err(io_err)?);
+        for &(u, v) in &self.buffer {
+            wf.write_all(&(u as u64).to_le_bytes()).map_err(io_err)?;
+            wf.write_all(&(v as u64).to_le_bytes()).map_err(io_err)?;
+        }
+        wf.flush().map_err(io_err)?;
+        self.runs.push(path);
+        self.buffer.clear();
+        Ok(())
+    }
+    // Calls `f` on every distinct edge in increasing order, merging the runs.
+    fn for_each_edge<F: FnMut(usize, usize)>(&self, mut f: F) -> Result<(), GraphError> {
+        if self.runs.is_empty() {
+            self.buffer.iter().for_each(|&(u, v)| f(u, v));
+            return Ok(());
+        }
+        let io_err = |e| GraphError::io(format!("Failed to merge edges of '{}'", self.name), e);
+        let mut runs = Vec::with_capacity(self.runs.len());
+        let mut heap = BinaryHeap::new();
+        for (i, path) in self.runs.iter().enumerate() {
+            let mut run = Run {
+                reader: BufReader::new(File::open(path).map_err(io_err)?),
+            };
+            if let Some(edge) = run.next_edge().map_err(io_err)? {
+                heap.push(Reverse((edge, i)));
+            }
+            runs.push(run);
+        }
+        let mut last = None;
+        while let Some(Reverse((edge, i))) = heap.pop() {
+            if Some(edge) != last {
+                f(edge.0, edge.1);
+                last = Some(edge);
+            }
+            if let Some(next) = runs[i].next_edge().map_err(io_err)? {
+                heap.push(Reverse((next, i)));
+            }
+        }
+        Ok(())
+    }
+    /// Builds the graph with nodes numbered by increasing id, returning the
+    /// original id of every node as well.
+    pub fn build(mut self) -> Result<(CsrGraph, Vec<usize>), GraphError> {
+        if self.runs.is_empty() {
+            self.buffer.sort_unstable();
+            self.buffer.dedup();
+        } else if !self.buffer.is_empty() {
+            self.spill()?;
+        }
+        let mut degrees: HashMap<usize, usize> = HashMap::new();
+        let mut m = 0;
+        self.for_each_edge(|u, v| {
+            *degrees.entry(u).or_default() += 1;
+            *degrees.entry(v).or_default() += 1;
+            m += 1;
+        })?;
+        let mut old_ids: Vec<usize> = degrees.keys().copied().collect();
+        old_ids.sort_unstable();
+        let n = old_ids.len();
+        let mut offsets = vec![0; n + 1];
+        for (i, u) in old_ids.iter().enumerate() {
+            offsets[i + 1] = offsets[i] + degrees[u];
+        }
+        drop(degrees);
+        // edges arrive sorted, so every neighbour list fills in increasing order
+        let mut next = offsets.clone();
+        let mut neighbors = vec![0; 2 * m];
+        let id = |u: usize| old_ids.binary_search(&u).unwrap();
+        self.for_each_edge(|u, v| {
+            let (a, b) = (id(u), id(v));
+            neighbors[next[a]] = b;
+            next[a] += 1;
+            neighbors[next[b]] = a;
+            next[b] += 1;
+        })?;
+        let g = CsrGraph {
+            name: self.name.clone(),
+            n,
+            m,
+            offsets,
+            neighbors,
+        };
+        Ok((g, old_ids))
+    }
+}
+
+impl CsrGraph {
+    /// Parses an edge list through a `GraphBuilder`, so memory stays within
+    /// the budget of `config` apart from the finished graph itself.
+    pub fn from_file_streaming<R: Read>(
+        name: &str,
+        r: R,
+        format: GraphFormat,
+        config: &ComputeConfig,
+    ) -> Result<(Self, Vec<usize>), GraphError> {
+        let mut builder = GraphBuilder::new(name, config);
+        let header = parse_edges(name, r, format, config, |u, v| builder.push_edge(u, v))?;
+        let (g, old_ids) = builder.build()?;
+        if let Some(header) = header {
+            let edges = (0..g.n).flat_map(|u| {
+                let old_ids = &old_ids;
+                g.neighbors(u)
+                    .iter()
+                    .filter(move |&&v| u < v)
+                    .map(move |&v| (old_ids[u], old_ids[v]))
+            });
+            header.verify(name, g.n, g.m, edge_checksum(edges))?;
+        }
+        Ok((g, old_ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::general_graph::GeneralUndiGraph;
+    use crate::normal_graph::NormalUndiGraph;
+
+    #[test]
+    fn test_streaming_matches_in_memory() {
+        let mut text = String::from("# ring with chords, every edge twice\n");
+        for i in 0..200 {
+            let (u, v) = (10 * i, 10 * ((i + 1) % 200));
+            text += &format!("{} {}\n{} {}\n{} {}\n", u, v, v, u, u, 10 * ((i * 7) % 200));
+        }
+        let general = GeneralUndiGraph::read("g", text.as_bytes(), GraphFormat::EdgeList).unwrap();
+        let (expected, expected_ids) = general.canonicalize();
+        // 64 edges of 16 bytes per run
+        let config = ComputeConfig {
+            memory_budget: Some(1024),
+            ..ComputeConfig::serial()
+        };
+        let mut builder = GraphBuilder::new("g", &config);
+        for (u, v) in general.edges.iter() {
+            builder.push_edge(*u, *v).unwrap();
+        }
+        assert!(builder.num_runs() > 1);
+        for config in [config, ComputeConfig::serial()] {
+            let (csr, old_ids) =
+                CsrGraph::from_file_streaming("g", text.as_bytes(), GraphFormat::EdgeList, &config)
+                    .unwrap();
+            assert_eq!(old_ids, expected_ids);
+            let normal: NormalUndiGraph = csr.to_normal();
+            assert_eq!((normal.n, normal.m), (expected.n, expected.m));
+            assert_eq!(normal.adjs, expected.adjs);
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::io::{self, Write};
+
+use super::config::splitmix64;
+use super::error::GraphError;
+use super::general_graph::GeneralUndiGraph;
+use super::normal_graph::NormalUndiGraph;
+
+// marks header lines; edge-list readers without header support skip them
+// as `#` comments
+pub const HEADER_PREFIX: &str = "#!";
+
+/// Metadata block written ahead of an edge list by `write_canonical` and
+/// checked by the edge-list readers, so that truncated or mismatched files
+/// fail to load instead of loading as a smaller graph.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Header {
+    // crate version that wrote the file
+    pub version: String,
+    pub name: String,
+    pub directed: bool,
+    // including isolated nodes, which an edge list cannot show
+    pub nodes: usize,
+    pub edges: usize,
+    // `edge_checksum` of the edges
+    pub checksum: u64,
+}
+
+/// Hash of an undirected edge set that ignores edge order and orientation.
+pub fn edge_checksum<I: Iterator<Item = (usize, usize)>>(edges: I) -> u64 {
+    edges.fold(0u64, |sum, (u, v)| {
+        let (a, b) = (u.min(v) as u64, u.max(v) as u64);
+        sum.wrapping_add(splitmix64(splitmix64(a) ^ b))
+    })
+}
+
+impl Header {
+    pub fn undirected<I: Iterator<Item = (usize, usize)>>(
+        name: &str,
+        nodes: usize,
+        edges: usize,
+        edge_iter: I,
+    ) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            name: name.to_string(),
+            directed: false,
+            nodes,
+            edges,
+            checksum: edge_checksum(edge_iter),
+        }
+    }
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} general_graph {}", HEADER_PREFIX, self.version)?;
+        writeln!(w, "{} name: {}", HEADER_PREFIX, self.name)?;
+        writeln!(w, "{} directed: {}", HEADER_PREFIX, self.directed)?;
+        writeln!(w, "{} nodes: {}", HEADER_PREFIX, self.nodes)?;
+        writeln!(w, "{} edges: {}", HEADER_PREFIX, self.edges)?;
+        writeln!(w, "{} checksum: {:016x}", HEADER_PREFIX, self.checksum)
+    }
+    // Takes one header line, ignoring unknown keys from later versions.
+    pub(crate) fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let body = line.trim_start_matches(HEADER_PREFIX).trim();
+        if let Some(version) = body.strip_prefix("general_graph ") {
+            self.version = version.trim().to_string();
+            return Ok(());
+        }
+        let Some((key, value)) = body.split_once(':') else {
+            return Ok(());
+        };
+        let value = value.trim();
+        let bad = || format!("Malformed header line: {}", line);
+        match key.trim() {
+            "name" => self.name = value.to_string(),
+            "directed" => self.directed = value.parse().map_err(|_| bad())?,
+            "nodes" => self.nodes = value.parse().map_err(|_| bad())?,
+            "edges" => self.edges = value.parse().map_err(|_| bad())?,
+            "checksum" => self.checksum = u64::from_str_radix(value, 16).map_err(|_| bad())?,
+            _ => {}
+        }
+        Ok(())
+    }
+    /// Fails unless an undirected graph read from the file matches the
+    /// header. Isolated nodes are lost in an edge list, so only `nodes`
+    /// above the header count is an error.
+    pub fn verify(
+        &self,
+        file: &str,
+        nodes: usize,
+        edges: usize,
+        checksum: u64,
+    ) -> Result<(), GraphError> {
+        let mismatch = |what: String| {
+            Err(GraphError::Other(format!(
+                "'{}' does not match its header: {}",
+                file, what
+            )))
+        };
+        if self.directed {
+            return mismatch("directed graph read as undirected".to_string());
+        }
+        if edges != self.edges {
+            return mismatch(format!("{} edges, header says {}", edges, self.edges));
+        }
+        if nodes > self.nodes {
+            return mismatch(format!("{} nodes, header says {}", nodes, self.nodes));
+        }
+        if checksum != self.checksum {
+            return mismatch("checksum differs".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl GeneralUndiGraph {
+    /// Writes the header followed by the edges as in `Display`.
+    pub fn write_canonical<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        Header::undirected(
+            &self.name,
+            self.num_nodes(),
+            self.num_edges(),
+            self.edges.iter().copied(),
+        )
+        .write(w)?;
+        write!(w, "{}", self)
+    }
+}
+
+impl NormalUndiGraph {
+    /// Writes the header followed by the edges as in `Display`.
+    pub fn write_canonical<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let edges = self
+            .adjs
+            .iter()
+            .enumerate()
+            .flat_map(|(u, adj)| adj.iter().filter(move |&&v| u < v).map(move |&v| (u, v)));
+        Header::undirected(&self.name, self.n, self.m, edges).write(w)?;
+        write!(w, "{}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::GraphFormat;
+
+    #[test]
+    fn test_round_trip_and_truncation() {
+        let g = NormalUndiGraph::from_koch(2);
+        let mut buf = Vec::new();
+        g.write_canonical(&mut buf).unwrap();
+        let text = String::from_utf8(buf).unwrap();
+        let read = GeneralUndiGraph::read("koch", text.as_bytes(), GraphFormat::EdgeList).unwrap();
+        assert_eq!((read.num_nodes(), read.num_edges()), (g.n, g.m));
+
+        // dropping the last edge line is detected
+        let truncated = &text[..text.trim_end().rfind('\n').unwrap() + 1];
+        assert!(
+            GeneralUndiGraph::read("koch", truncated.as_bytes(), GraphFormat::EdgeList).is_err()
+        );
+        // so is an edit that keeps the edge count
+        let edited = text.replacen("\n0\t", "\n1\t", 1);
+        match GeneralUndiGraph::read("koch", edited.as_bytes(), GraphFormat::EdgeList) {
+            Err(e) => assert!(e.to_string().contains("header")),
+            Ok(_) => panic!("edited file accepted"),
+        }
+        assert!(text.starts_with("#! general_graph "));
+        let mut header = Header::default();
+        for line in text.lines().take_while(|l| l.starts_with(HEADER_PREFIX)) {
+            header.parse_line(line).unwrap();
+        }
+        assert_eq!(
+            (header.nodes, header.edges, header.directed),
+            (g.n, g.m, false)
+        );
+    }
+}
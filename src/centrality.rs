@@ -0,0 +1,486 @@
+use rand::rngs::StdRng;
+use rand::seq::index;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::checkpoint::{parse_token, read_tokens, Resumable};
+use super::config::ComputeConfig;
+use super::normal_graph::NormalUndiGraph;
+
+/// Brandes' betweenness centrality as a resumable task, accumulating the
+/// dependencies of one source per step.
+pub struct Betweenness<'a> {
+    g: &'a NormalUndiGraph,
+    pub next_source: usize,
+    // each unordered pair counted twice until `finish`
+    pub scores: Vec<f64>,
+}
+
+impl<'a> Betweenness<'a> {
+    pub fn new(g: &'a NormalUndiGraph) -> Self {
+        Self {
+            g,
+            next_source: 0,
+            scores: vec![0.0; g.n],
+        }
+    }
+    /// Betweenness of every node over unordered pairs, once all steps ran.
+    pub fn finish(self) -> Vec<f64> {
+        self.scores.into_iter().map(|x| x / 2.0).collect()
+    }
+}
+
+impl<'a> Resumable for Betweenness<'a> {
+    fn save_state<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} {}", self.g.n, self.next_source)?;
+        for x in &self.scores {
+            writeln!(w, "{}", x)?;
+        }
+        Ok(())
+    }
+    fn load_state<R: Read>(&mut self, r: &mut R) -> io::Result<()> {
+        let tokens = read_tokens(r)?;
+        let mut it = tokens.iter();
+        let n: usize = parse_token(it.next())?;
+        let next_source: usize = parse_token(it.next())?;
+        if n != self.g.n || next_source > n {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "state belongs to another graph",
+            ));
+        }
+        for x in self.scores.iter_mut() {
+            *x = parse_token(it.next())?;
+        }
+        self.next_source = next_source;
+        Ok(())
+    }
+    fn step(&mut self) -> bool {
+        if self.next_source >= self.g.n {
+            return false;
+        }
+        accumulate_dependencies(self.g, self.next_source, &mut self.scores);
+        self.next_source += 1;
+        true
+    }
+}
+
+// Adds the dependencies of source `s` on every other node to `scores`.
+fn accumulate_dependencies(g: &NormalUndiGraph, s: usize, scores: &mut [f64]) {
+    let mut order = Vec::with_capacity(g.n);
+    let mut sigma = vec![0.0; g.n];
+    let mut dist = vec![usize::MAX; g.n];
+    sigma[s] = 1.0;
+    dist[s] = 0;
+    let mut queue = VecDeque::from([s]);
+    while let Some(u) = queue.pop_front() {
+        order.push(u);
+        for &v in &g.adjs[u] {
+            if dist[v] == usize::MAX {
+                dist[v] = dist[u] + 1;
+                queue.push_back(v);
+            }
+            if dist[v] == dist[u] + 1 {
+                sigma[v] += sigma[u];
+            }
+        }
+    }
+    let mut delta = vec![0.0; g.n];
+    for &v in order.iter().rev() {
+        for &u in &g.adjs[v] {
+            if dist[u] != usize::MAX && dist[u] + 1 == dist[v] {
+                delta[u] += sigma[u] / sigma[v] * (1.0 + delta[v]);
+            }
+        }
+        if v != s {
+            scores[v] += delta[v];
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    /// Betweenness centrality over unordered pairs by Brandes' algorithm;
+    /// see `Betweenness` for a checkpointable run.
+    pub fn betweenness_centrality(&self) -> Vec<f64> {
+        // a serial config has no token, so this cannot fail
+        self.betweenness_centrality_with(&ComputeConfig::serial())
+            .unwrap()
+    }
+    /// `betweenness_centrality` with the sources split among the threads of
+    /// `config`, each chunk of sources holding its own score vector. Reports
+    /// finished sources and stops early once the token of `config` is
+    /// cancelled.
+    pub fn betweenness_centrality_with(&self, config: &ComputeConfig) -> Result<Vec<f64>, String> {
+        let sources: Vec<usize> = (0..self.n).collect();
+        self.betweenness_from(&sources, config)
+    }
+    /// Estimate of betweenness centrality from Brandes' accumulation out of
+    /// `samples` uniformly chosen sources, scaled by `n / samples`. Exact
+    /// when `samples >= n`.
+    pub fn betweenness_centrality_sampled(
+        &self,
+        samples: usize,
+        seed: u64,
+        config: &ComputeConfig,
+    ) -> Result<Vec<f64>, String> {
+        if samples >= self.n {
+            return self.betweenness_centrality_with(config);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let sources = index::sample(&mut rng, self.n, samples).into_vec();
+        let scale = self.n as f64 / samples as f64;
+        Ok(self
+            .betweenness_from(&sources, config)?
+            .into_iter()
+            .map(|x| x * scale)
+            .collect())
+    }
+    // dependencies of `sources` summed over unordered pairs
+    fn betweenness_from(
+        &self,
+        sources: &[usize],
+        config: &ComputeConfig,
+    ) -> Result<Vec<f64>, String> {
+        let chunks = config.reduction_chunks(sources.len());
+        let done = AtomicU64::new(0);
+        let partials = config.par_map(chunks, |c| {
+            let mut scores = vec![0.0; self.n];
+            for &s in sources.iter().skip(c).step_by(chunks) {
+                config.check_cancelled("Betweenness centrality")?;
+                accumulate_dependencies(self, s, &mut scores);
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                config.report("betweenness", done, Some(sources.len() as u64));
+            }
+            Ok(scores)
+        });
+        let partials = partials.into_iter().collect::<Result<Vec<_>, String>>()?;
+        Ok((0..self.n)
+            .map(|u| partials.iter().map(|scores| scores[u]).sum::<f64>() / 2.0)
+            .collect())
+    }
+    /// Harmonic centrality `sum_{v != u} 1 / d(u, v)`, with unreachable nodes
+    /// contributing 0, by one BFS per node.
+    pub fn harmonic_centrality(&self) -> Vec<f64> {
+        self.harmonic_centrality_with(&ComputeConfig::serial())
+            .unwrap()
+    }
+    /// `harmonic_centrality` with the BFS runs spread over the threads of
+    /// `config`, reporting and cancelled like `betweenness_centrality_with`.
+    pub fn harmonic_centrality_with(&self, config: &ComputeConfig) -> Result<Vec<f64>, String> {
+        let done = AtomicU64::new(0);
+        config
+            .par_map(self.n, |u| {
+                config.check_cancelled("Harmonic centrality")?;
+                let sum = self
+                    .distances_from(u)
+                    .into_iter()
+                    .filter(|&d| d != 0 && d != usize::MAX)
+                    .map(|d| 1.0 / d as f64)
+                    .sum();
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                config.report("harmonic", done, Some(self.n as u64));
+                Ok(sum)
+            })
+            .into_iter()
+            .collect()
+    }
+    /// Unbiased estimate of harmonic centrality from BFS runs out of `samples`
+    /// uniformly chosen pivots, scaled by `(n - 1) / pivots` per node. Exact
+    /// when `samples >= n`.
+    pub fn harmonic_centrality_sampled(&self, samples: usize, seed: u64) -> Vec<f64> {
+        if samples >= self.n {
+            return self.harmonic_centrality();
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let pivots = index::sample(&mut rng, self.n, samples);
+        let mut sums = vec![0.0; self.n];
+        let mut is_pivot = vec![false; self.n];
+        for s in pivots.iter() {
+            is_pivot[s] = true;
+            for (u, d) in self.distances_from(s).into_iter().enumerate() {
+                if d != 0 && d != usize::MAX {
+                    sums[u] += 1.0 / d as f64;
+                }
+            }
+        }
+        sums.iter()
+            .zip(is_pivot)
+            .map(|(&sum, pivot)| {
+                let k = samples - pivot as usize;
+                if k == 0 {
+                    0.0
+                } else {
+                    sum * (self.n - 1) as f64 / k as f64
+                }
+            })
+            .collect()
+    }
+    /// Wasserman-Faust closeness, which scales the closeness within each
+    /// component by the fraction of nodes reachable, so it stays comparable
+    /// across components of disconnected graphs.
+    pub fn closeness_centrality(&self) -> Vec<f64> {
+        self.closeness_centrality_with(&ComputeConfig::serial())
+            .unwrap()
+    }
+    /// `closeness_centrality` with the BFS runs spread over the threads of
+    /// `config`, reporting and cancelled like `betweenness_centrality_with`.
+    pub fn closeness_centrality_with(&self, config: &ComputeConfig) -> Result<Vec<f64>, String> {
+        let done = AtomicU64::new(0);
+        config
+            .par_map(self.n, |u| {
+                config.check_cancelled("Closeness centrality")?;
+                let (reached, total) = self
+                    .distances_from(u)
+                    .into_iter()
+                    .filter(|&d| d != 0 && d != usize::MAX)
+                    .fold((0usize, 0usize), |(r, t), d| (r + 1, t + d));
+                let done = done.fetch_add(1, Ordering::Relaxed) + 1;
+                config.report("closeness", done, Some(self.n as u64));
+                Ok(if total == 0 {
+                    0.0
+                } else {
+                    (reached as f64 / (self.n - 1) as f64) * (reached as f64 / total as f64)
+                })
+            })
+            .into_iter()
+            .collect()
+    }
+    /// PageRank by power iteration over the adjacency lists, stopping once
+    /// the L1 change drops below `tol` or after `max_iter` rounds. Isolated
+    /// nodes hand their score back through the teleport distribution.
+    pub fn pagerank(&self, damping: f64, tol: f64, max_iter: usize) -> Vec<f64> {
+        if self.n == 0 {
+            return Vec::new();
+        }
+        let uniform = vec![1.0 / self.n as f64; self.n];
+        // uniform seeds are always valid, so this cannot fail
+        self.personalized_pagerank(&uniform, damping, tol, max_iter)
+            .unwrap()
+    }
+    /// `pagerank` teleporting to `seeds`, rescaled to sum to 1, instead of
+    /// to a uniform node. Fails unless there is one finite, non-negative
+    /// weight per node with a positive sum; an empty graph has empty ranks.
+    pub fn personalized_pagerank(
+        &self,
+        seeds: &[f64],
+        damping: f64,
+        tol: f64,
+        max_iter: usize,
+    ) -> Result<Vec<f64>, String> {
+        if seeds.len() != self.n {
+            return Err(format!(
+                "Expected {} seed weights, got {}",
+                self.n,
+                seeds.len()
+            ));
+        }
+        if self.n == 0 {
+            return Ok(Vec::new());
+        }
+        if seeds.iter().any(|s| !(s.is_finite() && *s >= 0.0)) {
+            return Err("Seed weights must be finite and non-negative".to_string());
+        }
+        let total: f64 = seeds.iter().sum();
+        if total <= 0.0 {
+            return Err("Seed weights must not all be zero".to_string());
+        }
+        let teleport: Vec<f64> = seeds.iter().map(|s| s / total).collect();
+        let mut rank = teleport.clone();
+        let mut next = vec![0.0; self.n];
+        for _ in 0..max_iter {
+            let mut dangling = 0.0;
+            next.iter_mut().for_each(|x| *x = 0.0);
+            for (u, adj) in self.adjs.iter().enumerate() {
+                if adj.is_empty() {
+                    dangling += rank[u];
+                    continue;
+                }
+                let share = rank[u] / adj.len() as f64;
+                for &v in adj {
+                    next[v] += share;
+                }
+            }
+            let mut change = 0.0;
+            for u in 0..self.n {
+                let x = damping * next[u] + (1.0 - damping + damping * dangling) * teleport[u];
+                change += (x - rank[u]).abs();
+                rank[u] = x;
+            }
+            if change < tol {
+                break;
+            }
+        }
+        Ok(rank)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint::run_with_checkpoints;
+    use crate::progress::{CancellationToken, ProgressFn};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_betweenness() {
+        let path =
+            NormalUndiGraph::from_edges("P5".to_string(), 5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_eq!(path.betweenness_centrality(), vec![0.0, 3.0, 4.0, 3.0, 0.0]);
+        // two shortest paths between 1 and 3 split the credit
+        let square =
+            NormalUndiGraph::from_edges("C4".to_string(), 4, &[(0, 1), (1, 2), (2, 3), (3, 0)]);
+        assert_eq!(square.betweenness_centrality(), vec![0.5; 4]);
+
+        let g = NormalUndiGraph::from_apollo(3);
+        let dir = tempfile::tempdir().unwrap();
+        let ckpt = dir.path().join("bc.ckpt");
+        let mut partial = Betweenness::new(&g);
+        for _ in 0..10 {
+            partial.step();
+        }
+        partial
+            .save_state(&mut std::fs::File::create(&ckpt).unwrap())
+            .unwrap();
+        let mut resumed = Betweenness::new(&g);
+        run_with_checkpoints(&mut resumed, &ckpt, 4).unwrap();
+        let expected = g.betweenness_centrality();
+        for (a, b) in resumed.finish().iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        let config = ComputeConfig {
+            num_threads: 4,
+            ..ComputeConfig::serial()
+        };
+        for (a, b) in g
+            .betweenness_centrality_with(&config)
+            .unwrap()
+            .iter()
+            .zip(&expected)
+        {
+            assert!((a - b).abs() < 1e-9);
+        }
+        assert_eq!(
+            g.harmonic_centrality_with(&config).unwrap(),
+            g.harmonic_centrality()
+        );
+        assert_eq!(
+            g.closeness_centrality_with(&config).unwrap(),
+            g.closeness_centrality()
+        );
+        let sampled = g.betweenness_centrality_sampled(g.n, 0, &config).unwrap();
+        for (a, b) in sampled.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        // half the sources still find the hubs of the Apollonian network
+        let sampled = g
+            .betweenness_centrality_sampled(g.n / 2, 3, &config)
+            .unwrap();
+        let total: f64 = expected.iter().sum();
+        let err: f64 = sampled
+            .iter()
+            .zip(&expected)
+            .map(|(a, b)| (a - b).abs())
+            .sum();
+        assert!(err < 0.3 * total);
+        // bit-identical whatever the number of threads
+        let deterministic = |num_threads| ComputeConfig {
+            num_threads,
+            deterministic: true,
+            ..ComputeConfig::serial()
+        };
+        assert_eq!(
+            g.betweenness_centrality_with(&deterministic(1)).unwrap(),
+            g.betweenness_centrality_with(&deterministic(5)).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_cancel_and_progress() {
+        let g = NormalUndiGraph::from_koch(2);
+        let max_done = Arc::new(AtomicU64::new(0));
+        let sink = max_done.clone();
+        let n = g.n as u64;
+        let config = ComputeConfig {
+            num_threads: 3,
+            progress: Some(ProgressFn::new(move |_, done, total| {
+                assert_eq!(total, Some(n));
+                sink.fetch_max(done, Ordering::Relaxed);
+            })),
+            ..ComputeConfig::serial()
+        };
+        assert!(g.harmonic_centrality_with(&config).is_ok());
+        assert_eq!(max_done.load(Ordering::Relaxed), n);
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let config = ComputeConfig {
+            cancel: Some(token),
+            ..config
+        };
+        assert!(g.betweenness_centrality_with(&config).is_err());
+        assert!(g.harmonic_centrality_with(&config).is_err());
+    }
+
+    #[test]
+    fn test_pagerank() {
+        // without teleports an undirected walk settles on degree / 2m
+        let g = NormalUndiGraph::from_apollo(2);
+        let rank = g.pagerank(1.0, 1e-12, 10_000);
+        for (r, adj) in rank.iter().zip(&g.adjs) {
+            assert!((r - adj.len() as f64 / (2 * g.m) as f64).abs() < 1e-9);
+        }
+        let star = NormalUndiGraph::from_edges("star".to_string(), 5, &[(0, 1), (0, 2), (0, 3)]);
+        let rank = star.pagerank(0.85, 1e-12, 1000);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(rank[0] > rank[1] && rank[1] > rank[4]);
+        // restarts at one end of a path favour the nodes near it
+        let path = NormalUndiGraph::from_edges("P4".to_string(), 4, &[(0, 1), (1, 2), (2, 3)]);
+        let rank = path
+            .personalized_pagerank(&[1.0, 0.0, 0.0, 0.0], 0.85, 1e-12, 1000)
+            .unwrap();
+        assert!(rank[1] > rank[2] && rank[2] > rank[3]);
+        assert!(path
+            .personalized_pagerank(&[1.0; 3], 0.85, 1e-12, 1000)
+            .is_err());
+        assert!(path
+            .personalized_pagerank(&[0.0; 4], 0.85, 1e-12, 1000)
+            .is_err());
+        let negative = [2.0, -1.0, 0.0, 0.0];
+        assert!(path
+            .personalized_pagerank(&negative, 0.85, 1e-12, 1000)
+            .is_err());
+        let empty = NormalUndiGraph::from_edges("empty".to_string(), 0, &[]);
+        assert!(empty.pagerank(0.85, 1e-12, 1000).is_empty());
+        assert_eq!(
+            empty.personalized_pagerank(&[], 0.85, 1e-12, 1000),
+            Ok(Vec::new())
+        );
+    }
+
+    #[test]
+    fn test_harmonic_centrality() {
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(star.harmonic_centrality(), vec![3.0, 2.0, 2.0, 2.0]);
+        // an isolated node adds nothing and has centrality 0
+        let g = NormalUndiGraph::from_edges("g".to_string(), 3, &[(0, 1)]);
+        assert_eq!(g.harmonic_centrality(), vec![1.0, 1.0, 0.0]);
+        assert_eq!(g.closeness_centrality(), vec![0.5, 0.5, 0.0]);
+    }
+
+    #[test]
+    fn test_harmonic_centrality_sampled() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let exact = g.harmonic_centrality();
+        assert_eq!(g.harmonic_centrality_sampled(g.n, 0), exact);
+        let approx = g.harmonic_centrality_sampled(g.n / 2, 1);
+        let err: f64 = exact
+            .iter()
+            .zip(&approx)
+            .map(|(e, a)| (e - a).abs() / e)
+            .sum::<f64>()
+            / g.n as f64;
+        assert!(err < 0.1);
+    }
+}
@@ -0,0 +1,180 @@
+use std::fs::{rename, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::normal_graph::NormalUndiGraph;
+
+/// A computation split into steps whose progress can be written to disk and
+/// restored, so that it can resume after an interruption.
+pub trait Resumable {
+    fn save_state<W: Write>(&self, w: &mut W) -> io::Result<()>;
+    fn load_state<R: Read>(&mut self, r: &mut R) -> io::Result<()>;
+    /// Runs one unit of work, returning false once there is none left.
+    fn step(&mut self) -> bool;
+}
+
+/// Runs `task` to completion, resuming from the checkpoint at `path` if one
+/// exists and rewriting it every `every` steps. The checkpoint is replaced
+/// atomically and removed once the task finishes.
+pub fn run_with_checkpoints<T: Resumable, P: AsRef<Path>>(
+    task: &mut T,
+    path: P,
+    every: usize,
+) -> Result<(), String> {
+    let path = path.as_ref();
+    if path.exists() {
+        let f = File::open(path).or(Err(format!(
+            "Failed to open checkpoint '{}'",
+            path.display()
+        )))?;
+        task.load_state(&mut BufReader::new(f)).or(Err(format!(
+            "Failed to load checkpoint '{}'",
+            path.display()
+        )))?;
+    }
+    let tmp = path.with_extension("tmp");
+    let mut steps = 0;
+    while task.step() {
+        steps += 1;
+        if steps % every.max(1) == 0 {
+            let save = || -> io::Result<()> {
+                let mut wf = BufWriter::new(File::create(&tmp)?);
+                task.save_state(&mut wf)?;
+                wf.flush()?;
+                rename(&tmp, path)
+            };
+            save().or(Err(format!(
+                "Failed to write checkpoint '{}'",
+                path.display()
+            )))?;
+        }
+    }
+    if path.exists() {
+        std::fs::remove_file(path).or(Err(format!(
+            "Failed to remove checkpoint '{}'",
+            path.display()
+        )))?;
+    }
+    Ok(())
+}
+
+// Reads the whitespace-separated tokens of a saved state.
+pub(crate) fn read_tokens<R: Read>(r: &mut R) -> io::Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    for line in BufReader::new(r).lines() {
+        tokens.extend(line?.split_whitespace().map(|x| x.to_string()));
+    }
+    Ok(tokens)
+}
+
+pub(crate) fn parse_token<T: std::str::FromStr>(token: Option<&String>) -> io::Result<T> {
+    token.and_then(|x| x.parse().ok()).ok_or(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "malformed state",
+    ))
+}
+
+/// All-pairs BFS recording the eccentricity and distance sum of every node,
+/// one source per step. Eccentricities ignore unreachable nodes.
+pub struct AllPairsBfs<'a> {
+    g: &'a NormalUndiGraph,
+    pub next_source: usize,
+    pub eccentricities: Vec<usize>,
+    pub distance_sums: Vec<usize>,
+}
+
+impl<'a> AllPairsBfs<'a> {
+    pub fn new(g: &'a NormalUndiGraph) -> Self {
+        Self {
+            g,
+            next_source: 0,
+            eccentricities: vec![0; g.n],
+            distance_sums: vec![0; g.n],
+        }
+    }
+}
+
+impl<'a> Resumable for AllPairsBfs<'a> {
+    fn save_state<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} {}", self.g.n, self.next_source)?;
+        for u in 0..self.next_source {
+            writeln!(w, "{} {}", self.eccentricities[u], self.distance_sums[u])?;
+        }
+        Ok(())
+    }
+    fn load_state<R: Read>(&mut self, r: &mut R) -> io::Result<()> {
+        let tokens = read_tokens(r)?;
+        let mut it = tokens.iter();
+        let n: usize = parse_token(it.next())?;
+        let next_source: usize = parse_token(it.next())?;
+        if n != self.g.n || next_source > n {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "state belongs to another graph",
+            ));
+        }
+        for u in 0..next_source {
+            self.eccentricities[u] = parse_token(it.next())?;
+            self.distance_sums[u] = parse_token(it.next())?;
+        }
+        self.next_source = next_source;
+        Ok(())
+    }
+    fn step(&mut self) -> bool {
+        if self.next_source >= self.g.n {
+            return false;
+        }
+        let u = self.next_source;
+        let reachable = self
+            .g
+            .distances_from(u)
+            .into_iter()
+            .filter(|&d| d != usize::MAX);
+        let (mut ecc, mut sum) = (0, 0);
+        for d in reachable {
+            ecc = ecc.max(d);
+            sum += d;
+        }
+        self.eccentricities[u] = ecc;
+        self.distance_sums[u] = sum;
+        self.next_source += 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_all_pairs() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("apsp.ckpt");
+
+        // a run interrupted after 7 sources, its last checkpoint at 6
+        let mut task = AllPairsBfs::new(&g);
+        let mut wf = File::create(&path).unwrap();
+        for _ in 0..6 {
+            task.step();
+        }
+        task.save_state(&mut wf).unwrap();
+        drop(wf);
+        task.step();
+
+        let mut resumed = AllPairsBfs::new(&g);
+        run_with_checkpoints(&mut resumed, &path, 3).unwrap();
+        assert!(!path.exists());
+        let mut fresh = AllPairsBfs::new(&g);
+        while fresh.step() {}
+        assert_eq!(resumed.eccentricities, g.eccentricities().unwrap());
+        assert_eq!(resumed.distance_sums, fresh.distance_sums);
+
+        let other = NormalUndiGraph::from_koch(1);
+        let mut state = Vec::new();
+        fresh.save_state(&mut state).unwrap();
+        assert!(AllPairsBfs::new(&other)
+            .load_state(&mut state.as_slice())
+            .is_err());
+    }
+}
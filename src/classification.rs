@@ -0,0 +1,88 @@
+use super::normal_graph::NormalUndiGraph;
+
+impl NormalUndiGraph {
+    /// Label spreading (Zhou et al.): iterates `F = alpha S F + (1 - alpha) Y`
+    /// with `S = D^{-1/2} A D^{-1/2}` and `Y` the one-hot seed labels, then
+    /// normalizes each row into a distribution over the labels. Nodes the
+    /// seeds never reach get a uniform distribution.
+    pub fn label_spreading(
+        &self,
+        seed_labels: &[Option<usize>],
+        alpha: f64,
+        iterations: usize,
+    ) -> Vec<Vec<f64>> {
+        let k = seed_labels.iter().flatten().max().map_or(0, |&l| l + 1);
+        let inv_sqrt: Vec<f64> = self
+            .adjs
+            .iter()
+            .map(|adj| {
+                if adj.is_empty() {
+                    0.0
+                } else {
+                    1.0 / (adj.len() as f64).sqrt()
+                }
+            })
+            .collect();
+        let mut y = vec![vec![0.0; k]; self.n];
+        for (row, label) in y.iter_mut().zip(seed_labels) {
+            if let Some(l) = label {
+                row[*l] = 1.0;
+            }
+        }
+        let mut f = y.clone();
+        for _ in 0..iterations {
+            f = (0..self.n)
+                .map(|u| {
+                    let mut row: Vec<f64> = y[u].iter().map(|x| (1.0 - alpha) * x).collect();
+                    for &v in &self.adjs[u] {
+                        let w = alpha * inv_sqrt[u] * inv_sqrt[v];
+                        for (r, x) in row.iter_mut().zip(&f[v]) {
+                            *r += w * x;
+                        }
+                    }
+                    row
+                })
+                .collect();
+        }
+        for row in f.iter_mut() {
+            let total: f64 = row.iter().sum();
+            if total > 0.0 {
+                row.iter_mut().for_each(|x| *x /= total);
+            } else {
+                row.iter_mut().for_each(|x| *x = 1.0 / k as f64);
+            }
+        }
+        f
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_label_spreading() {
+        // two 5-cliques joined by the single edge 4-5
+        let mut edges = Vec::new();
+        for offset in [0, 5] {
+            for i in 0..5 {
+                for j in i + 1..5 {
+                    edges.push((offset + i, offset + j));
+                }
+            }
+        }
+        edges.push((4, 5));
+        let g = NormalUndiGraph::from_edges("barbell".to_string(), 11, &edges);
+        let mut seeds = vec![None; 11];
+        seeds[0] = Some(0);
+        seeds[9] = Some(1);
+        let dist = g.label_spreading(&seeds, 0.9, 50);
+        for (u, row) in dist.iter().enumerate().take(10) {
+            assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            let predicted = if row[0] > row[1] { 0 } else { 1 };
+            assert_eq!(predicted, u / 5);
+        }
+        // the isolated node 10 is never reached
+        assert_eq!(dist[10], vec![0.5, 0.5]);
+    }
+}
@@ -0,0 +1,234 @@
+/// Deterministic families with known analytic properties at generation `g`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Family {
+    // `NormalUndiGraph::from_apollo`
+    Apollo,
+    // `NormalUndiGraph::from_koch`
+    Koch,
+    // `NormalUndiGraph::from_pseudo_ext` with the given `m`
+    PseudoExt(usize),
+    // `NormalUndiGraph::from_pseudofractal`, the same as `PseudoExt(1)`
+    Pseudofractal,
+    // (u, v)-flower of Rozenfeld, Havlin and ben-Avraham: a cycle of
+    // `u + v` nodes at generation 1, every edge then replaced by two
+    // parallel paths of lengths `u` and `v`
+    Flower(FlowerShape),
+}
+
+/// Path lengths `1 <= u <= v` with `v >= 2` of a (u, v)-flower, checked on
+/// construction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowerShape {
+    u: usize,
+    v: usize,
+}
+
+impl FlowerShape {
+    /// None unless `1 <= u <= v` and `v >= 2`; the (1, 1)-flower is a
+    /// single doubled edge.
+    pub fn new(u: usize, v: usize) -> Option<Self> {
+        (1 <= u && u <= v && v >= 2).then_some(Self { u, v })
+    }
+    pub fn u(&self) -> usize {
+        self.u
+    }
+    pub fn v(&self) -> usize {
+        self.v
+    }
+}
+
+impl Family {
+    /// `Family::Flower` of the (u, v)-flower, None for invalid lengths as
+    /// in `FlowerShape::new`.
+    pub fn flower(u: usize, v: usize) -> Option<Self> {
+        FlowerShape::new(u, v).map(Family::Flower)
+    }
+    pub fn num_nodes(&self, g: u32) -> usize {
+        match *self {
+            Family::Apollo => 2 * 3usize.pow(g) + 2,
+            Family::Koch => 2 * 4usize.pow(g) + 1,
+            Family::PseudoExt(m) => (3 * (2 * m + 1).pow(g) + 3) / 2,
+            Family::Pseudofractal => Family::PseudoExt(1).num_nodes(g),
+            Family::Flower(FlowerShape { u, v }) => {
+                let w = u + v;
+                ((w - 2) * w.pow(g) + w) / (w - 1)
+            }
+        }
+    }
+    pub fn num_edges(&self, g: u32) -> usize {
+        match *self {
+            Family::Apollo => 6 * 3usize.pow(g),
+            Family::Koch => 3 * 4usize.pow(g),
+            Family::PseudoExt(m) => 3 * (2 * m + 1).pow(g),
+            Family::Pseudofractal => Family::PseudoExt(1).num_edges(g),
+            Family::Flower(FlowerShape { u, v }) => (u + v).pow(g),
+        }
+    }
+    pub fn average_degree(&self, g: u32) -> f64 {
+        2.0 * self.num_edges(g) as f64 / self.num_nodes(g) as f64
+    }
+    /// Exponent `gamma` of the power-law degree distribution, None for the
+    /// Koch networks whose degrees decay exponentially.
+    pub fn degree_exponent(&self) -> Option<f64> {
+        match *self {
+            Family::Apollo | Family::Pseudofractal => Some(1.0 + 3f64.ln() / 2f64.ln()),
+            // degrees grow by m + 1 per generation as the nodes grow by 2m + 1
+            Family::PseudoExt(m) => Some(1.0 + ((2 * m + 1) as f64).ln() / ((m + 1) as f64).ln()),
+            Family::Flower(FlowerShape { u, v }) => Some(1.0 + ((u + v) as f64).ln() / 2f64.ln()),
+            Family::Koch => None,
+        }
+    }
+    /// Fractal (box-counting) dimension `ln(u + v) / ln u` of the
+    /// (u, v)-flowers with `u > 1`; the other families are small worlds.
+    pub fn fractal_dimension(&self) -> Option<f64> {
+        match *self {
+            Family::Flower(FlowerShape { u, v }) if u > 1 => {
+                Some(((u + v) as f64).ln() / (u as f64).ln())
+            }
+            _ => None,
+        }
+    }
+    /// Mean steps of a simple random walk from one initial hub to another
+    /// (nodes 0 and 1 of the generators). Hubs are swapped by a symmetry,
+    /// so this is half the commute time `2 m R`. The hub resistance `R`
+    /// shrinks by `uv / (u + v)` per generation in the flowers, starting
+    /// from a single edge, and by `2 / (m + 2)` in `PseudoExt(m)`, starting
+    /// from a triangle. The Koch triangles hang off single nodes and leave
+    /// it at 2/3. None for the Apollonian network.
+    pub fn hub_hitting_time(&self, g: u32) -> Option<f64> {
+        let g = g as i32;
+        match *self {
+            Family::Flower(FlowerShape { u, v }) => Some(((u * v) as f64).powi(g)),
+            Family::PseudoExt(m) => Some(2.0 * (2.0 * (2 * m + 1) as f64 / (m + 2) as f64).powi(g)),
+            Family::Pseudofractal => Family::PseudoExt(1).hub_hitting_time(g as u32),
+            Family::Koch => Some(2.0 * 4f64.powi(g)),
+            Family::Apollo => None,
+        }
+    }
+    /// Spectral dimension `2 ln(N growth) / ln(T growth)` from the growth of
+    /// the nodes and of the walk times per generation, both self-similar in
+    /// the flowers (`2 ln(u + v) / ln(uv)`, Hwang et al.) and the
+    /// pseudofractal families. None for the Apollonian and Koch networks.
+    pub fn spectral_dimension(&self) -> Option<f64> {
+        match *self {
+            Family::Flower(FlowerShape { u, v }) => {
+                Some(2.0 * ((u + v) as f64).ln() / ((u * v) as f64).ln())
+            }
+            Family::PseudoExt(m) if m > 0 => {
+                let growth = (2 * m + 1) as f64;
+                Some(2.0 * growth.ln() / (2.0 * growth / (m + 2) as f64).ln())
+            }
+            Family::Pseudofractal => Family::PseudoExt(1).spectral_dimension(),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::normal_graph::NormalUndiGraph;
+
+    #[test]
+    fn test_counts_match_generators() {
+        for g in 0..5 {
+            let cases = [
+                (Family::Apollo, NormalUndiGraph::from_apollo(g)),
+                (Family::Koch, NormalUndiGraph::from_koch(g)),
+                (Family::PseudoExt(2), NormalUndiGraph::from_pseudo_ext(2, g)),
+                (
+                    Family::Pseudofractal,
+                    NormalUndiGraph::from_pseudofractal(g),
+                ),
+            ];
+            for (family, graph) in cases {
+                assert_eq!(family.num_nodes(g as u32), graph.n, "{:?} {}", family, g);
+                assert_eq!(family.num_edges(g as u32), graph.m, "{:?} {}", family, g);
+                let mean = graph.summary().mean_degree;
+                assert!((family.average_degree(g as u32) - mean).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flower() {
+        // the pseudofractal graph at generation g is the (1, 2)-flower at g + 1
+        let flower = |u, v| Family::flower(u, v).unwrap();
+        for g in 0..5 {
+            assert_eq!(
+                flower(1, 2).num_nodes(g + 1),
+                Family::Pseudofractal.num_nodes(g)
+            );
+            assert_eq!(
+                flower(1, 2).num_edges(g + 1),
+                Family::Pseudofractal.num_edges(g)
+            );
+            assert_eq!(
+                flower(1, 2).hub_hitting_time(g + 1),
+                Family::Pseudofractal.hub_hitting_time(g)
+            );
+        }
+        assert_eq!(
+            flower(1, 2).spectral_dimension(),
+            Family::Pseudofractal.spectral_dimension()
+        );
+        assert_eq!(flower(2, 2).num_nodes(1), 4);
+        assert_eq!(flower(2, 2).fractal_dimension(), Some(2.0));
+        assert_eq!(flower(2, 2).spectral_dimension(), Some(2.0));
+        assert_eq!(flower(1, 3).fractal_dimension(), None);
+        assert_eq!(flower(2, 2).degree_exponent(), Some(3.0));
+        for (u, v) in [(0, 2), (1, 1), (3, 2), (0, 0)] {
+            assert_eq!(Family::flower(u, v), None);
+        }
+        assert_eq!(Family::Koch.degree_exponent(), None);
+    }
+
+    #[test]
+    fn test_pseudo_ext_degree_exponent() {
+        let gamma = Family::PseudoExt(2).degree_exponent().unwrap();
+        assert!((gamma - (1.0 + 5f64.ln() / 3f64.ln())).abs() < 1e-12);
+        assert_eq!(
+            Family::PseudoExt(1).degree_exponent(),
+            Family::Pseudofractal.degree_exponent()
+        );
+        // the hub degrees of the generator triple with every generation
+        let max_degree = |g| {
+            let graph = NormalUndiGraph::from_pseudo_ext(2, g);
+            graph.adjs.iter().map(|adj| adj.len()).max().unwrap()
+        };
+        for g in 1..4 {
+            assert_eq!(max_degree(g + 1), 3 * max_degree(g));
+        }
+    }
+
+    #[test]
+    fn test_hub_hitting_times() {
+        let cases = [
+            (
+                Family::Koch,
+                NormalUndiGraph::from_koch as fn(usize) -> NormalUndiGraph,
+            ),
+            (Family::Pseudofractal, NormalUndiGraph::from_pseudofractal),
+            (Family::PseudoExt(2), |g| {
+                NormalUndiGraph::from_pseudo_ext(2, g)
+            }),
+        ];
+        for (family, generate) in cases {
+            let times: Vec<f64> = (0..4)
+                .map(|g| generate(g).mean_hitting_times(1).unwrap()[0])
+                .collect();
+            for (g, &time) in times.iter().enumerate() {
+                let exact = family.hub_hitting_time(g as u32).unwrap();
+                assert!((time - exact).abs() < 1e-6 * exact, "{:?} {}", family, g);
+            }
+            // the walk times grow as the spectral dimension predicts
+            if let Some(d_s) = family.spectral_dimension() {
+                let growth = (family.num_edges(3) / family.num_edges(2)) as f64;
+                let estimate = 2.0 * growth.ln() / (times[3] / times[2]).ln();
+                assert!((estimate - d_s).abs() < 1e-6, "{:?}", family);
+            }
+        }
+        assert_eq!(Family::Apollo.hub_hitting_time(2), None);
+        assert_eq!(Family::Koch.spectral_dimension(), None);
+    }
+}
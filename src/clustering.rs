@@ -0,0 +1,311 @@
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::normal_graph::NormalUndiGraph;
+
+/// Sampled estimate with a 95% normal-approximation confidence interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Estimate {
+    pub value: f64,
+    pub low: f64,
+    pub high: f64,
+}
+
+impl Estimate {
+    // fraction `closed / samples` of Bernoulli trials, scaled by `scale`
+    fn from_fraction(closed: usize, samples: usize, scale: f64) -> Self {
+        if samples == 0 {
+            return Self {
+                value: 0.0,
+                low: 0.0,
+                high: 0.0,
+            };
+        }
+        let p = closed as f64 / samples as f64;
+        let half = 1.96 * (p * (1.0 - p) / samples as f64).sqrt();
+        Self {
+            value: p * scale,
+            low: (p - half).max(0.0) * scale,
+            high: (p + half).min(1.0) * scale,
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    /// Triangles through every node, by intersecting sorted out-neighbour
+    /// lists of the degeneracy orientation, in O(m * degeneracy).
+    pub fn triangle_counts(&self) -> Vec<usize> {
+        let out = self.orient_by_degeneracy();
+        let mut cnt = vec![0; self.n];
+        for (u, adj) in out.iter().enumerate() {
+            for &v in adj {
+                for &w in &out[v] {
+                    if adj.binary_search(&w).is_ok() {
+                        cnt[u] += 1;
+                        cnt[v] += 1;
+                        cnt[w] += 1;
+                    }
+                }
+            }
+        }
+        cnt
+    }
+    /// Common neighbours of the endpoints of every edge `(u, v)`, `u < v`,
+    /// listed in increasing order of `(u, v)`, i.e. the triangles through
+    /// each edge, counted along the degeneracy orientation.
+    pub fn edge_embeddedness(&self) -> Vec<(usize, usize, usize)> {
+        // edges of `u` towards larger ids start at `first[u]`
+        let mut first = Vec::with_capacity(self.n + 1);
+        let mut edges = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            first.push(edges.len());
+            let i = adj.partition_point(|&v| v <= u);
+            edges.extend(adj[i..].iter().map(|&v| (u, v, 0)));
+        }
+        let index = |a: usize, b: usize| {
+            let (u, v) = (a.min(b), a.max(b));
+            let adj = &self.adjs[u];
+            first[u] + adj.binary_search(&v).unwrap() - adj.partition_point(|&w| w <= u)
+        };
+        let out = self.orient_by_degeneracy();
+        for (u, adj) in out.iter().enumerate() {
+            for &v in adj {
+                for &w in &out[v] {
+                    if adj.binary_search(&w).is_ok() {
+                        edges[index(u, v)].2 += 1;
+                        edges[index(u, w)].2 += 1;
+                        edges[index(v, w)].2 += 1;
+                    }
+                }
+            }
+        }
+        edges
+    }
+    /// Topological overlap of Onnela et al., the common neighbours of every
+    /// edge over the other neighbours of its endpoints; 0 for an edge whose
+    /// endpoints have no other neighbours.
+    pub fn edge_overlap(&self) -> Vec<(usize, usize, f64)> {
+        self.edge_embeddedness()
+            .into_iter()
+            .map(|(u, v, c)| {
+                let others = self.adjs[u].len() + self.adjs[v].len() - 2 - c;
+                let overlap = if others == 0 {
+                    0.0
+                } else {
+                    c as f64 / others as f64
+                };
+                (u, v, overlap)
+            })
+            .collect()
+    }
+    /// Edge clustering coefficient of Radicchi et al., `(c + 1) / min(d_u -
+    /// 1, d_v - 1)` for `c` common neighbours; NaN at leaves, where it is
+    /// undefined.
+    pub fn edge_clustering(&self) -> Vec<(usize, usize, f64)> {
+        self.edge_embeddedness()
+            .into_iter()
+            .map(|(u, v, c)| {
+                let room = self.adjs[u].len().min(self.adjs[v].len()) - 1;
+                let clustering = if room == 0 {
+                    f64::NAN
+                } else {
+                    (c + 1) as f64 / room as f64
+                };
+                (u, v, clustering)
+            })
+            .collect()
+    }
+    /// Number of triangles in the graph.
+    pub fn triangle_count(&self) -> usize {
+        self.triangle_counts().into_iter().sum::<usize>() / 3
+    }
+    /// Local clustering coefficient of `u` alone, by marking its neighbours,
+    /// in O(sum of neighbour degrees).
+    pub fn local_clustering_of(&self, u: usize) -> f64 {
+        let adj = &self.adjs[u];
+        let d = adj.len();
+        if d < 2 {
+            return 0.0;
+        }
+        let links: usize = adj
+            .iter()
+            .map(|&v| {
+                self.adjs[v]
+                    .iter()
+                    .filter(|w| adj.binary_search(w).is_ok())
+                    .count()
+            })
+            .sum();
+        // every link between neighbours is seen from both ends
+        links as f64 / (d * (d - 1)) as f64
+    }
+    /// Local clustering coefficient, 0 for nodes of degree below 2.
+    pub fn local_clustering(&self) -> Vec<f64> {
+        self.triangle_counts()
+            .into_iter()
+            .zip(&self.adjs)
+            .map(|(t, adj)| {
+                let d = adj.len();
+                if d < 2 {
+                    0.0
+                } else {
+                    2.0 * t as f64 / (d * (d - 1)) as f64
+                }
+            })
+            .collect()
+    }
+    pub fn average_clustering(&self) -> f64 {
+        if self.n == 0 {
+            return 0.0;
+        }
+        self.local_clustering().iter().sum::<f64>() / self.n as f64
+    }
+    /// Mean local clustering over the nodes of degree at least 2, unlike
+    /// `average_clustering` which counts the others as 0.
+    pub fn global_clustering(&self) -> f64 {
+        let (sum, cnt) = self
+            .local_clustering()
+            .into_iter()
+            .zip(&self.adjs)
+            .filter(|(_, adj)| adj.len() >= 2)
+            .fold((0.0, 0usize), |(sum, cnt), (c, _)| (sum + c, cnt + 1));
+        if cnt == 0 {
+            0.0
+        } else {
+            sum / cnt as f64
+        }
+    }
+    /// Three times the triangles over the connected triples.
+    pub fn transitivity(&self) -> f64 {
+        let triples: usize = self
+            .adjs
+            .iter()
+            .map(|adj| adj.len() * adj.len().saturating_sub(1) / 2)
+            .sum();
+        if triples == 0 {
+            return 0.0;
+        }
+        3.0 * self.triangle_count() as f64 / triples as f64
+    }
+    // whether a uniformly random wedge centred at `u` is closed; `u` needs
+    // degree at least 2
+    fn sample_wedge<R: Rng>(&self, u: usize, rng: &mut R) -> bool {
+        let adj = &self.adjs[u];
+        let i = rng.gen_range(0..adj.len());
+        let mut j = rng.gen_range(0..adj.len() - 1);
+        if j >= i {
+            j += 1;
+        }
+        self.adjs[adj[i]].binary_search(&adj[j]).is_ok()
+    }
+    /// Triangle count estimated from `samples` wedges drawn uniformly over
+    /// the whole graph, each centre picked with weight `d (d - 1) / 2`. The
+    /// closed fraction estimates `transitivity`, independent of graph size.
+    pub fn triangles_approx(&self, samples: usize, seed: u64) -> Estimate {
+        let wedges: Vec<u64> = self
+            .adjs
+            .iter()
+            .map(|adj| (adj.len() * adj.len().saturating_sub(1) / 2) as u64)
+            .collect();
+        let total: u64 = wedges.iter().sum();
+        let Ok(centres) = WeightedIndex::new(&wedges) else {
+            return Estimate::from_fraction(0, 0, 0.0);
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+        let closed = (0..samples)
+            .filter(|_| {
+                let u = centres.sample(&mut rng);
+                self.sample_wedge(u, &mut rng)
+            })
+            .count();
+        Estimate::from_fraction(closed, samples, total as f64 / 3.0)
+    }
+    /// `global_clustering` estimated from `samples` wedges, each at a centre
+    /// drawn uniformly among the nodes of degree at least 2.
+    pub fn clustering_global_approx(&self, samples: usize, seed: u64) -> Estimate {
+        let centres: Vec<usize> = (0..self.n).filter(|&u| self.adjs[u].len() >= 2).collect();
+        if centres.is_empty() {
+            return Estimate::from_fraction(0, 0, 0.0);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let closed = (0..samples)
+            .filter(|_| {
+                let u = centres[rng.gen_range(0..centres.len())];
+                self.sample_wedge(u, &mut rng)
+            })
+            .count();
+        Estimate::from_fraction(closed, samples, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clustering() {
+        let k4 = NormalUndiGraph::from_apollo(0);
+        assert_eq!(k4.triangle_counts(), vec![3; 4]);
+        assert_eq!(k4.average_clustering(), 1.0);
+        let paw =
+            NormalUndiGraph::from_edges("paw".to_string(), 4, &[(0, 1), (1, 2), (2, 0), (2, 3)]);
+        assert_eq!(paw.local_clustering(), vec![1.0, 1.0, 1.0 / 3.0, 0.0]);
+        assert_eq!(paw.local_clustering_of(2), 1.0 / 3.0);
+        assert_eq!(paw.triangle_count(), 1);
+        assert_eq!(paw.transitivity(), 3.0 / 5.0);
+        assert_eq!(paw.global_clustering(), (2.0 + 1.0 / 3.0) / 3.0);
+    }
+
+    #[test]
+    fn test_edge_embeddedness() {
+        let paw =
+            NormalUndiGraph::from_edges("paw".to_string(), 4, &[(0, 1), (1, 2), (2, 0), (2, 3)]);
+        assert_eq!(
+            paw.edge_embeddedness(),
+            vec![(0, 1, 1), (0, 2, 1), (1, 2, 1), (2, 3, 0)]
+        );
+        let overlap: Vec<f64> = paw.edge_overlap().into_iter().map(|e| e.2).collect();
+        assert_eq!(overlap, vec![1.0, 0.5, 0.5, 0.0]);
+        let clustering = paw.edge_clustering();
+        assert_eq!(clustering[0].2, 2.0);
+        assert!(clustering[3].2.is_nan());
+
+        let g = NormalUndiGraph::from_apollo(3);
+        for (u, v, c) in g.edge_embeddedness() {
+            let common = g.adjs[u].iter().filter(|w| g.adjs[v].contains(w)).count();
+            assert_eq!(c, common);
+        }
+        let total: usize = g.edge_embeddedness().iter().map(|e| e.2).sum();
+        assert_eq!(total, 3 * g.triangle_count());
+    }
+
+    #[test]
+    fn test_apollonian_clustering() {
+        // generation i puts a node into each of the 4 * 3^(i-1) open faces,
+        // closing three triangles with the corners of its face
+        let g = NormalUndiGraph::from_apollo(4);
+        let (mut faces, mut triangles) = (4usize, 4usize);
+        for _ in 0..4 {
+            triangles += 3 * faces;
+            faces *= 3;
+        }
+        assert_eq!(g.triangle_count(), triangles);
+        let local = g.local_clustering();
+        for u in [0, 5, g.n - 1] {
+            assert!((g.local_clustering_of(u) - local[u]).abs() < 1e-12);
+        }
+        // the clustering of Apollonian networks tends to about 0.828
+        assert!(g.global_clustering() > 0.8);
+        assert!(g.transitivity() < g.global_clustering());
+
+        let tri = g.triangles_approx(20_000, 1);
+        assert!(tri.low <= triangles as f64 && triangles as f64 <= tri.high);
+        assert!((tri.value - triangles as f64).abs() < 0.05 * triangles as f64);
+        let cc = g.clustering_global_approx(20_000, 2);
+        assert!(cc.low <= g.global_clustering() && g.global_clustering() <= cc.high);
+        let empty = NormalUndiGraph::from_edges("K2".to_string(), 2, &[(0, 1)]);
+        assert_eq!(empty.triangles_approx(10, 0).value, 0.0);
+        assert_eq!(empty.clustering_global_approx(10, 0).value, 0.0);
+    }
+}
@@ -0,0 +1,272 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use super::normal_graph::NormalUndiGraph;
+use super::weighted_graph::WeightedUndiGraph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchingStrategy {
+    // greedy matching over the edges in order of decreasing weight
+    HeavyEdge,
+    // visit nodes in seeded random order and match each to a random free neighbour
+    Random(u64),
+}
+
+pub struct Coarsening {
+    pub graph: WeightedUndiGraph,
+    // prolongation[u] is the coarse node containing fine node u
+    pub prolongation: Vec<usize>,
+    // number of fine nodes merged into each coarse node
+    pub node_weights: Vec<usize>,
+}
+
+impl Coarsening {
+    /// Interpolates a vector on the coarse nodes back to the fine nodes.
+    pub fn prolong(&self, coarse: &[f64]) -> Vec<f64> {
+        self.prolongation.iter().map(|&c| coarse[c]).collect()
+    }
+    /// Sums a vector on the fine nodes into the coarse nodes.
+    pub fn restrict(&self, fine: &[f64]) -> Vec<f64> {
+        let mut coarse = vec![0.0; self.graph.n];
+        for (&c, x) in self.prolongation.iter().zip(fine) {
+            coarse[c] += x;
+        }
+        coarse
+    }
+}
+
+impl WeightedUndiGraph {
+    /// Contracts a maximal matching: matched pairs become one coarse node and
+    /// parallel edges between coarse nodes are merged by summing weights.
+    pub fn coarsen(&self, strategy: MatchingStrategy) -> Coarsening {
+        let mut mate = vec![usize::MAX; self.n];
+        match strategy {
+            MatchingStrategy::HeavyEdge => {
+                let mut edges: Vec<(usize, usize, f64)> = Vec::with_capacity(self.m);
+                for (u, adj) in self.adjs.iter().enumerate() {
+                    edges.extend(adj.iter().filter(|&&(v, _)| u < v).map(|&(v, w)| (u, v, w)));
+                }
+                edges.sort_by(|a, b| b.2.total_cmp(&a.2));
+                for (u, v, _) in edges {
+                    if mate[u] == usize::MAX && mate[v] == usize::MAX {
+                        mate[u] = v;
+                        mate[v] = u;
+                    }
+                }
+            }
+            MatchingStrategy::Random(seed) => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let mut order: Vec<usize> = (0..self.n).collect();
+                order.shuffle(&mut rng);
+                for u in order {
+                    if mate[u] != usize::MAX {
+                        continue;
+                    }
+                    let free: Vec<usize> = self.adjs[u]
+                        .iter()
+                        .map(|&(v, _)| v)
+                        .filter(|&v| mate[v] == usize::MAX)
+                        .collect();
+                    if let Some(&v) = free.choose(&mut rng) {
+                        mate[u] = v;
+                        mate[v] = u;
+                    }
+                }
+            }
+        }
+        for (u, m) in mate.iter_mut().enumerate() {
+            if *m == usize::MAX {
+                *m = u;
+            }
+        }
+        let mut prolongation = vec![usize::MAX; self.n];
+        let mut node_weights = Vec::new();
+        for u in 0..self.n {
+            if prolongation[u] == usize::MAX {
+                prolongation[u] = node_weights.len();
+                prolongation[mate[u]] = node_weights.len();
+                node_weights.push(if mate[u] == u { 1 } else { 2 });
+            }
+        }
+        let mut edges = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &(v, w) in adj.iter().filter(|&&(v, _)| u < v) {
+                edges.push((prolongation[u], prolongation[v], w));
+            }
+        }
+        Coarsening {
+            graph: WeightedUndiGraph::from_edges(
+                format!("{}-coarse", self.name),
+                node_weights.len(),
+                &edges,
+            ),
+            prolongation,
+            node_weights,
+        }
+    }
+}
+
+// Union-find over the nodes `0..n` with union by size and iterative path
+// halving, so contracting long paths builds no deep parent chains.
+struct NodeUnion {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl NodeUnion {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+        }
+    }
+    fn find(&mut self, mut x: usize) -> usize {
+        while self.parent[x] != x {
+            self.parent[x] = self.parent[self.parent[x]];
+            x = self.parent[x];
+        }
+        x
+    }
+    // false when `x` and `y` were already joined
+    fn union(&mut self, x: usize, y: usize) -> bool {
+        let (mut x, mut y) = (self.find(x), self.find(y));
+        if x == y {
+            return false;
+        }
+        if self.size[x] < self.size[y] {
+            std::mem::swap(&mut x, &mut y);
+        }
+        self.parent[y] = x;
+        self.size[x] += self.size[y];
+        true
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn coarsen(&self, strategy: MatchingStrategy) -> Coarsening {
+        WeightedUndiGraph::from_normal(self).coarsen(strategy)
+    }
+    // Quotient graph of the classes of `classes`, numbered by their
+    // smallest node, with the class of every node.
+    fn contract_classes(&self, classes: &mut NodeUnion, name: String) -> (Self, Vec<usize>) {
+        let mut label = vec![usize::MAX; self.n];
+        let mut map = Vec::with_capacity(self.n);
+        let mut cnt = 0;
+        for u in 0..self.n {
+            let root = classes.find(u);
+            if label[root] == usize::MAX {
+                label[root] = cnt;
+                cnt += 1;
+            }
+            map.push(label[root]);
+        }
+        let mut edges = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            edges.extend(adj.iter().filter(|&&v| u < v).map(|&v| (map[u], map[v])));
+        }
+        (Self::from_edges(name, cnt, &edges), map)
+    }
+    /// Minor obtained by contracting `edges`, each of which must be an edge
+    /// of the graph. Loops and parallel edges that arise are dropped.
+    /// Returns the minor and, for every node, the node it was merged into.
+    pub fn contract_edges(&self, edges: &[(usize, usize)]) -> Result<(Self, Vec<usize>), String> {
+        let mut classes = NodeUnion::new(self.n);
+        for &(u, v) in edges {
+            if u >= self.n || self.adjs[u].binary_search(&v).is_err() {
+                return Err(format!("({}, {}) is not an edge of '{}'", u, v, self.name));
+            }
+            classes.union(u, v);
+        }
+        Ok(self.contract_classes(&mut classes, format!("{}-minor", self.name)))
+    }
+    /// Minor with `target_n` nodes from contracting uniformly shuffled edges
+    /// until that many remain. Stops at one node per connected component
+    /// when `target_n` is below the number of components.
+    pub fn random_minor(&self, target_n: usize, seed: u64) -> (Self, Vec<usize>) {
+        let mut edges: Vec<(usize, usize)> = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            edges.extend(adj.iter().filter(|&&v| u < v).map(|&v| (u, v)));
+        }
+        edges.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut classes = NodeUnion::new(self.n);
+        let mut remaining = self.n;
+        for (u, v) in edges {
+            if remaining <= target_n {
+                break;
+            }
+            if classes.union(u, v) {
+                remaining -= 1;
+            }
+        }
+        self.contract_classes(&mut classes, format!("{}-minor", self.name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_coarsen() {
+        let g = NormalUndiGraph::from_apollo(3);
+        for strategy in [MatchingStrategy::HeavyEdge, MatchingStrategy::Random(4)] {
+            let c = g.coarsen(strategy);
+            assert_eq!(c.node_weights.iter().sum::<usize>(), g.n);
+            assert!(c.graph.n < g.n && c.graph.n >= g.n / 2);
+            // only the contracted edges disappear from the total weight
+            let contracted = g.n - c.graph.n;
+            assert_eq!(c.graph.total_weight(), (g.m - contracted) as f64);
+            let ones = vec![1.0; c.graph.n];
+            assert_eq!(c.prolong(&ones), vec![1.0; g.n]);
+            let sizes: Vec<f64> = c.node_weights.iter().map(|&w| w as f64).collect();
+            assert_eq!(c.restrict(&vec![1.0; g.n]), sizes);
+        }
+    }
+
+    #[test]
+    fn test_minors() {
+        // contracting a perimeter edge of the square with a diagonal leaves a triangle
+        let g = NormalUndiGraph::from_edges(
+            "sq".to_string(),
+            4,
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (0, 2)],
+        );
+        let (minor, map) = g.contract_edges(&[(1, 2)]).unwrap();
+        assert_eq!(map, vec![0, 1, 1, 2]);
+        assert_eq!((minor.n, minor.m), (3, 3));
+        assert!(g.contract_edges(&[(1, 3)]).is_err());
+
+        let g = NormalUndiGraph::from_apollo(4);
+        let (minor, map) = g.random_minor(20, 7);
+        assert_eq!(minor.n, 20);
+        assert_eq!(minor.num_components(), 1);
+        assert!(map.iter().all(|&c| c < 20));
+        assert_eq!(g.random_minor(20, 7).1, map);
+        let split = NormalUndiGraph::from_edges("2K2".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert_eq!(split.random_minor(1, 0).0.n, 2);
+
+        // contracting a long path in order keeps the parent chains shallow
+        let n = 1_000_000;
+        let path: Vec<(usize, usize)> = (1..n).map(|v| (v - 1, v)).collect();
+        let g = NormalUndiGraph::from_edges("path".to_string(), n, &path);
+        let (minor, map) = g.contract_edges(&path).unwrap();
+        assert_eq!((minor.n, minor.m), (1, 0));
+        assert!(map.iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn test_heavy_edge_matching() {
+        // path 0-1-2-3 with a heavy middle edge
+        let g = WeightedUndiGraph::from_edges(
+            "p".to_string(),
+            4,
+            &[(0, 1, 1.0), (1, 2, 5.0), (2, 3, 1.0)],
+        );
+        let c = g.coarsen(MatchingStrategy::HeavyEdge);
+        assert_eq!(c.prolongation, vec![0, 1, 1, 2]);
+        assert_eq!(c.node_weights, vec![1, 2, 1]);
+        assert_eq!(c.graph.weight(0, 1), Some(1.0));
+        assert_eq!(c.graph.weight(1, 2), Some(1.0));
+    }
+}
@@ -0,0 +1,247 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone, Debug)]
+pub struct ApproxSolution {
+    pub nodes: Vec<usize>,
+    // certified lower bound on the optimum size
+    pub lower_bound: usize,
+    // worst-case approximation ratio guaranteed by the algorithm
+    pub ratio_bound: f64,
+}
+
+impl ApproxSolution {
+    /// Ratio of the solution size to the certified lower bound, an a posteriori
+    /// upper bound on the actual approximation ratio.
+    pub fn observed_ratio(&self) -> f64 {
+        if self.lower_bound == 0 {
+            1.0
+        } else {
+            self.nodes.len() as f64 / self.lower_bound as f64
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    /// Greedy set-cover heuristic: repeatedly takes the node dominating the most
+    /// undominated nodes. Guarantees a ratio of H(Δ + 1).
+    pub fn greedy_dominating_set(&self) -> ApproxSolution {
+        let mut dominated = vec![false; self.n];
+        let gain = |u: usize, dominated: &[bool]| {
+            !dominated[u] as usize + self.adjs[u].iter().filter(|&&v| !dominated[v]).count()
+        };
+        let mut heap: BinaryHeap<(usize, Reverse<usize>)> = (0..self.n)
+            .map(|u| (self.adjs[u].len() + 1, Reverse(u)))
+            .collect();
+        let mut nodes = Vec::new();
+        let mut remaining = self.n;
+        while remaining > 0 {
+            let (stale, Reverse(u)) = heap.pop().unwrap();
+            let fresh = gain(u, &dominated);
+            if fresh < stale {
+                heap.push((fresh, Reverse(u)));
+                continue;
+            }
+            nodes.push(u);
+            for v in std::iter::once(u).chain(self.adjs[u].iter().copied()) {
+                if !dominated[v] {
+                    dominated[v] = true;
+                    remaining -= 1;
+                }
+            }
+        }
+        let max_deg = self.adjs.iter().map(|adj| adj.len()).max().unwrap_or(0);
+        nodes.sort_unstable();
+        ApproxSolution {
+            nodes,
+            lower_bound: self.n.div_ceil(max_deg + 1),
+            ratio_bound: (1..=max_deg + 1).map(|k| 1.0 / k as f64).sum(),
+        }
+    }
+    /// Both endpoints of a greedy maximal matching; the matching size is a
+    /// lower bound on any vertex cover.
+    pub fn vertex_cover_2approx(&self) -> ApproxSolution {
+        let mut covered = vec![false; self.n];
+        let mut matching = 0;
+        for (u, adj) in self.adjs.iter().enumerate() {
+            if covered[u] {
+                continue;
+            }
+            if let Some(&v) = adj.iter().find(|&&v| v > u && !covered[v]) {
+                covered[u] = true;
+                covered[v] = true;
+                matching += 1;
+            }
+        }
+        ApproxSolution {
+            nodes: (0..self.n).filter(|&u| covered[u]).collect(),
+            lower_bound: matching,
+            ratio_bound: 2.0,
+        }
+    }
+    /// Min-degree greedy independent set: repeatedly takes a node of minimum
+    /// degree in the remaining graph, ties broken by seeded random keys.
+    pub fn maximal_independent_set(&self, seed: u64) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let keys: Vec<u64> = (0..self.n).map(|_| rng.gen()).collect();
+        let mut deg: Vec<usize> = self.adjs.iter().map(|adj| adj.len()).collect();
+        let mut removed = vec![false; self.n];
+        let mut heap: BinaryHeap<Reverse<(usize, u64, usize)>> =
+            (0..self.n).map(|u| Reverse((deg[u], keys[u], u))).collect();
+        let mut set = Vec::new();
+        while let Some(Reverse((d, _, u))) = heap.pop() {
+            if removed[u] || d != deg[u] {
+                continue;
+            }
+            set.push(u);
+            removed[u] = true;
+            for &v in &self.adjs[u] {
+                if removed[v] {
+                    continue;
+                }
+                removed[v] = true;
+                for &w in &self.adjs[v] {
+                    if !removed[w] {
+                        deg[w] -= 1;
+                        heap.push(Reverse((deg[w], keys[w], w)));
+                    }
+                }
+            }
+        }
+        set.sort_unstable();
+        set
+    }
+    /// Local search by (1,2)-swaps: a solution node is replaced by two
+    /// non-adjacent neighbours whose only solution neighbour it is, and any
+    /// freed node is added back. Stops at a local optimum or after `max_rounds`.
+    pub fn improve_independent_set(
+        &self,
+        set: &[usize],
+        seed: u64,
+        max_rounds: usize,
+    ) -> Vec<usize> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut in_set = vec![false; self.n];
+        let mut tight = vec![0usize; self.n];
+        let insert = |u: usize, in_set: &mut [bool], tight: &mut [usize]| {
+            in_set[u] = true;
+            for &v in &self.adjs[u] {
+                tight[v] += 1;
+            }
+        };
+        for &u in set {
+            insert(u, &mut in_set, &mut tight);
+        }
+        for _ in 0..max_rounds {
+            let mut improved = false;
+            let mut order: Vec<usize> = (0..self.n).filter(|&u| in_set[u]).collect();
+            order.shuffle(&mut rng);
+            for x in order {
+                if !in_set[x] {
+                    continue;
+                }
+                let cands: Vec<usize> = self.adjs[x]
+                    .iter()
+                    .copied()
+                    .filter(|&v| !in_set[v] && tight[v] == 1)
+                    .collect();
+                let pair = cands.iter().enumerate().find_map(|(i, &u)| {
+                    cands[i + 1..]
+                        .iter()
+                        .find(|&&v| self.adjs[u].binary_search(&v).is_err())
+                        .map(|&v| (u, v))
+                });
+                if let Some((u, v)) = pair {
+                    in_set[x] = false;
+                    for &w in &self.adjs[x] {
+                        tight[w] -= 1;
+                    }
+                    insert(u, &mut in_set, &mut tight);
+                    insert(v, &mut in_set, &mut tight);
+                    for &w in &self.adjs[x] {
+                        if !in_set[w] && tight[w] == 0 {
+                            insert(w, &mut in_set, &mut tight);
+                        }
+                    }
+                    improved = true;
+                }
+            }
+            if !improved {
+                break;
+            }
+        }
+        (0..self.n).filter(|&u| in_set[u]).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greedy_dominating_set() {
+        let g = NormalUndiGraph::from_pseudofractal(3);
+        let sol = g.greedy_dominating_set();
+        let mut dominated = vec![false; g.n];
+        for &u in &sol.nodes {
+            dominated[u] = true;
+            for &v in &g.adjs[u] {
+                dominated[v] = true;
+            }
+        }
+        assert!(dominated.iter().all(|&d| d));
+        assert!(sol.lower_bound <= sol.nodes.len());
+        // two of the three hubs already dominate the first generation
+        let g = NormalUndiGraph::from_pseudofractal(1);
+        assert_eq!(g.greedy_dominating_set().nodes.len(), 2);
+    }
+
+    fn assert_independent(g: &NormalUndiGraph, set: &[usize]) {
+        let mut in_set = vec![false; g.n];
+        for &u in set {
+            in_set[u] = true;
+        }
+        for &u in set {
+            assert!(g.adjs[u].iter().all(|&v| !in_set[v]));
+        }
+        // maximality: every other node has a neighbour in the set
+        for u in (0..g.n).filter(|&u| !in_set[u]) {
+            assert!(g.adjs[u].iter().any(|&v| in_set[v]));
+        }
+    }
+
+    #[test]
+    fn test_independent_set() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let set = g.maximal_independent_set(11);
+        assert_independent(&g, &set);
+        assert_eq!(set, g.maximal_independent_set(11));
+        let improved = g.improve_independent_set(&set, 11, 100);
+        assert_independent(&g, &improved);
+        assert!(improved.len() >= set.len());
+
+        // a star picked from its centre is improved to all leaves at once
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(star.improve_independent_set(&[0], 0, 10), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_vertex_cover_2approx() {
+        let g = NormalUndiGraph::from_koch(2);
+        let sol = g.vertex_cover_2approx();
+        let mut in_cover = vec![false; g.n];
+        for &u in &sol.nodes {
+            in_cover[u] = true;
+        }
+        for (u, adj) in g.adjs.iter().enumerate() {
+            assert!(adj.iter().all(|&v| in_cover[u] || in_cover[v]));
+        }
+        assert_eq!(sol.nodes.len(), 2 * sol.lower_bound);
+        assert!(sol.observed_ratio() <= 2.0);
+    }
+}
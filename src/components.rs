@@ -0,0 +1,54 @@
+use super::normal_graph::NormalUndiGraph;
+
+impl NormalUndiGraph {
+    /// Component label of every node, numbered in order of the smallest node.
+    pub fn connected_components(&self) -> Vec<usize> {
+        let mut label = vec![usize::MAX; self.n];
+        let mut cnt = 0;
+        for s in 0..self.n {
+            if label[s] != usize::MAX {
+                continue;
+            }
+            label[s] = cnt;
+            let mut stack = vec![s];
+            while let Some(u) = stack.pop() {
+                for &v in &self.adjs[u] {
+                    if label[v] == usize::MAX {
+                        label[v] = cnt;
+                        stack.push(v);
+                    }
+                }
+            }
+            cnt += 1;
+        }
+        label
+    }
+    pub fn num_components(&self) -> usize {
+        self.connected_components()
+            .into_iter()
+            .max()
+            .map_or(0, |c| c + 1)
+    }
+    /// Sizes of the components, indexed by component label.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes = vec![0; self.num_components()];
+        for c in self.connected_components() {
+            sizes[c] += 1;
+        }
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connected_components() {
+        let g = NormalUndiGraph::from_edges("g".to_string(), 6, &[(0, 3), (3, 4), (1, 5)]);
+        assert_eq!(g.connected_components(), vec![0, 1, 2, 0, 0, 1]);
+        assert_eq!(g.num_components(), 3);
+        assert_eq!(g.component_sizes(), vec![3, 2, 1]);
+        assert_eq!(NormalUndiGraph::from_koch(2).num_components(), 1);
+    }
+}
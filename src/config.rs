@@ -0,0 +1,177 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::PathBuf;
+use std::thread;
+
+use tempfile::{Builder, TempDir};
+
+use super::progress::{CancellationToken, ProgressFn};
+
+/// Resource limits for parallel and external-memory code paths.
+#[derive(Clone, Debug)]
+pub struct ComputeConfig {
+    // worker threads for parallel loops; 1 runs everything inline
+    pub num_threads: usize,
+    // bytes an operation may hold in memory at once, None for no limit
+    pub memory_budget: Option<usize>,
+    // parent of temporary directories, None for the system default
+    pub temp_dir: Option<PathBuf>,
+    // checked between units of work; a cancelled operation returns an error
+    pub cancel: Option<CancellationToken>,
+    // receives progress instead of the terminal progress bar
+    pub progress: Option<ProgressFn>,
+    // fixed reduction order and per-item RNG streams, so results are
+    // bit-identical across runs and thread counts
+    pub deterministic: bool,
+}
+
+// partial results combined by a deterministic reduction, whatever the
+// number of threads
+const DETERMINISTIC_CHUNKS: usize = 64;
+
+impl Default for ComputeConfig {
+    fn default() -> Self {
+        Self {
+            num_threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            memory_budget: None,
+            temp_dir: None,
+            cancel: None,
+            progress: None,
+            deterministic: false,
+        }
+    }
+}
+
+impl ComputeConfig {
+    /// Single-threaded configuration without limits.
+    pub fn serial() -> Self {
+        Self {
+            num_threads: 1,
+            ..Self::default()
+        }
+    }
+    /// Fails if `bytes` exceed the memory budget.
+    pub fn check_memory(&self, bytes: usize, what: &str) -> Result<(), String> {
+        match self.memory_budget {
+            Some(budget) if bytes > budget => Err(format!(
+                "{} needs {} bytes, over the memory budget of {} bytes",
+                what, bytes, budget
+            )),
+            _ => Ok(()),
+        }
+    }
+    /// Fails once the cancellation token has been cancelled.
+    pub fn check_cancelled(&self, what: &str) -> Result<(), String> {
+        match &self.cancel {
+            Some(token) if token.is_cancelled() => Err(format!("{} was cancelled", what)),
+            _ => Ok(()),
+        }
+    }
+    pub fn report(&self, stage: &str, done: u64, total: Option<u64>) {
+        if let Some(progress) = &self.progress {
+            progress.report(stage, done, total);
+        }
+    }
+    /// Number of partial results a reduction over `n` items should be split
+    /// into: one per thread, or a fixed count in deterministic mode. Partials
+    /// are then combined in index order.
+    pub fn reduction_chunks(&self, n: usize) -> usize {
+        let chunks = if self.deterministic {
+            DETERMINISTIC_CHUNKS
+        } else {
+            self.num_threads
+        };
+        chunks.clamp(1, n.max(1))
+    }
+    /// Independent random stream `stream` derived from `seed`.
+    pub fn stream_rng(seed: u64, stream: u64) -> StdRng {
+        StdRng::seed_from_u64(splitmix64(seed ^ splitmix64(stream)))
+    }
+    /// Fresh temporary directory under `temp_dir`, deleted on drop.
+    pub fn tempdir(&self) -> Result<TempDir, String> {
+        let result = match &self.temp_dir {
+            Some(parent) => Builder::new().tempdir_in(parent),
+            None => Builder::new().tempdir(),
+        };
+        result.or(Err("Failed to return a temp dir".to_string()))
+    }
+    /// `(0..n).map(f)` evaluated on up to `num_threads` scoped threads, each
+    /// taking a contiguous block of indices, or on a rayon pool of that size
+    /// with the `parallel` feature.
+    pub fn par_map<T: Send, F: Fn(usize) -> T + Sync>(&self, n: usize, f: F) -> Vec<T> {
+        let threads = self.num_threads.clamp(1, n.max(1));
+        if threads == 1 {
+            return (0..n).map(f).collect();
+        }
+        #[cfg(feature = "parallel")]
+        if let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(threads).build() {
+            use rayon::prelude::*;
+            return pool.install(|| (0..n).into_par_iter().map(&f).collect());
+        }
+        let block = n.div_ceil(threads);
+        let f = &f;
+        thread::scope(|scope| {
+            let handles: Vec<_> = (0..threads)
+                .map(|t| {
+                    scope.spawn(move || {
+                        (t * block..n.min((t + 1) * block))
+                            .map(f)
+                            .collect::<Vec<T>>()
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        })
+    }
+}
+
+pub(crate) fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e3779b97f4a7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_config() {
+        let config = ComputeConfig {
+            num_threads: 3,
+            memory_budget: Some(1000),
+            ..ComputeConfig::serial()
+        };
+        assert_eq!(
+            config.par_map(10, |i| i * i),
+            (0..10).map(|i| i * i).collect::<Vec<_>>()
+        );
+        assert!(config.par_map(0, |i| i).is_empty());
+        assert!(config.check_memory(1000, "x").is_ok());
+        assert!(config
+            .check_memory(1001, "x")
+            .unwrap_err()
+            .contains("memory budget"));
+        let parent = tempfile::tempdir().unwrap();
+        let config = ComputeConfig {
+            temp_dir: Some(parent.path().to_path_buf()),
+            ..ComputeConfig::serial()
+        };
+        assert!(config.tempdir().unwrap().path().starts_with(parent.path()));
+        let token = CancellationToken::new();
+        let config = ComputeConfig {
+            cancel: Some(token.clone()),
+            ..ComputeConfig::serial()
+        };
+        assert!(config.check_cancelled("x").is_ok());
+        token.cancel();
+        assert_eq!(
+            config.check_cancelled("x"),
+            Err("x was cancelled".to_string())
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use super::normal_graph::NormalUndiGraph;
+
+// Residual network whose arcs come in pairs `2i`, `2i + 1` reversing each other.
+struct FlowNetwork {
+    arcs: Vec<Vec<usize>>,
+    to: Vec<usize>,
+    cap: Vec<usize>,
+}
+
+impl FlowNetwork {
+    fn new(n: usize) -> Self {
+        Self {
+            arcs: vec![Vec::new(); n],
+            to: Vec::new(),
+            cap: Vec::new(),
+        }
+    }
+    // arc `u -> v` of capacity `forward` and `v -> u` of capacity `backward`
+    fn add_arc(&mut self, u: usize, v: usize, forward: usize, backward: usize) {
+        self.arcs[u].push(self.to.len());
+        self.to.push(v);
+        self.cap.push(forward);
+        self.arcs[v].push(self.to.len());
+        self.to.push(u);
+        self.cap.push(backward);
+    }
+    // Value of a maximum `s`-`t` flow by shortest augmenting paths, stopping
+    // once it reaches `limit`. Leaves the network untouched.
+    fn max_flow(&self, s: usize, t: usize, limit: usize) -> usize {
+        let mut cap = self.cap.clone();
+        let mut flow = 0;
+        while flow < limit {
+            let mut via = vec![usize::MAX; self.arcs.len()];
+            let mut queue = VecDeque::from([s]);
+            while let Some(u) = queue.pop_front() {
+                for &a in &self.arcs[u] {
+                    let v = self.to[a];
+                    if cap[a] > 0 && v != s && via[v] == usize::MAX {
+                        via[v] = a;
+                        queue.push_back(v);
+                    }
+                }
+            }
+            if via[t] == usize::MAX {
+                break;
+            }
+            let mut bottleneck = usize::MAX;
+            let mut v = t;
+            while v != s {
+                bottleneck = bottleneck.min(cap[via[v]]);
+                v = self.to[via[v] ^ 1];
+            }
+            let mut v = t;
+            while v != s {
+                cap[via[v]] -= bottleneck;
+                cap[via[v] ^ 1] += bottleneck;
+                v = self.to[via[v] ^ 1];
+            }
+            flow += bottleneck;
+        }
+        flow.min(limit)
+    }
+}
+
+impl NormalUndiGraph {
+    /// Minimum number of edges whose removal disconnects the graph, as the
+    /// smallest unit-capacity max-flow from node 0 to any other node.
+    pub fn edge_connectivity(&self) -> usize {
+        if self.n < 2 {
+            return 0;
+        }
+        let mut network = FlowNetwork::new(self.n);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &v in adj.iter().filter(|&&v| u < v) {
+                network.add_arc(u, v, 1, 1);
+            }
+        }
+        let mut best = self.adjs.iter().map(|adj| adj.len()).min().unwrap();
+        for t in 1..self.n {
+            best = best.min(network.max_flow(0, t, best));
+        }
+        best
+    }
+    /// Minimum number of nodes whose removal disconnects the graph or leaves a
+    /// single node, by Even's algorithm: node-split max-flows between the
+    /// first `k + 1` nodes and every node not adjacent to them, `k` being the
+    /// best bound so far.
+    pub fn vertex_connectivity(&self) -> usize {
+        if self.n < 2 {
+            return 0;
+        }
+        // node u enters at 2u and leaves at 2u + 1
+        let mut network = FlowNetwork::new(2 * self.n);
+        for u in 0..self.n {
+            network.add_arc(2 * u, 2 * u + 1, 1, 0);
+            for &v in &self.adjs[u] {
+                network.add_arc(2 * u + 1, 2 * v, self.n, 0);
+            }
+        }
+        let mut best = self.n - 1;
+        let mut i = 0;
+        while i <= best && i < self.n {
+            for j in i + 1..self.n {
+                if self.adjs[i].binary_search(&j).is_err() {
+                    best = best.min(network.max_flow(2 * i + 1, 2 * j, best));
+                }
+            }
+            i += 1;
+        }
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connectivity() {
+        // two triangles sharing node 2
+        let bowtie = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            5,
+            &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 2)],
+        );
+        assert_eq!(bowtie.edge_connectivity(), 2);
+        assert_eq!(bowtie.vertex_connectivity(), 1);
+        let complete = NormalUndiGraph::from_ring_of_cliques(1, 6);
+        assert_eq!(complete.edge_connectivity(), 5);
+        assert_eq!(complete.vertex_connectivity(), 5);
+        let lattice = NormalUndiGraph::from_kleinberg(4, 1, 0, 2.0, 0).unwrap();
+        assert_eq!(lattice.edge_connectivity(), 2);
+        assert_eq!(lattice.vertex_connectivity(), 2);
+        let ring = NormalUndiGraph::from_ring_of_cliques(4, 4);
+        assert_eq!(ring.edge_connectivity(), 2);
+        let disconnected = NormalUndiGraph::from_edges("g".to_string(), 3, &[(0, 1)]);
+        assert_eq!(disconnected.edge_connectivity(), 0);
+        assert_eq!(disconnected.vertex_connectivity(), 0);
+    }
+}
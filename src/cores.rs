@@ -0,0 +1,102 @@
+use super::normal_graph::NormalUndiGraph;
+
+impl NormalUndiGraph {
+    /// Core number of every node by the Batagelj-Zaversnik bucket algorithm,
+    /// in O(n + m).
+    pub fn core_numbers(&self) -> Vec<usize> {
+        self.core_decomposition().0
+    }
+    // core numbers together with the order in which the bucket algorithm
+    // peels the nodes, a degeneracy ordering
+    fn core_decomposition(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut deg: Vec<usize> = self.adjs.iter().map(|adj| adj.len()).collect();
+        let max_deg = deg.iter().copied().max().unwrap_or(0);
+        // bin[d] is the first position of degree d in `order`
+        let mut bin = vec![0usize; max_deg + 2];
+        for &d in &deg {
+            bin[d + 1] += 1;
+        }
+        for d in 1..bin.len() {
+            bin[d] += bin[d - 1];
+        }
+        let mut pos = vec![0usize; self.n];
+        let mut order = vec![0usize; self.n];
+        let mut next = bin.clone();
+        for u in 0..self.n {
+            pos[u] = next[deg[u]];
+            order[pos[u]] = u;
+            next[deg[u]] += 1;
+        }
+        for i in 0..self.n {
+            let u = order[i];
+            for &v in &self.adjs[u] {
+                if deg[v] > deg[u] {
+                    // swap v with the first node of its bin, then shrink the bin
+                    let dv = deg[v];
+                    let (pv, pw) = (pos[v], bin[dv]);
+                    let w = order[pw];
+                    order.swap(pv, pw);
+                    pos[v] = pw;
+                    pos[w] = pv;
+                    bin[dv] += 1;
+                    deg[v] -= 1;
+                }
+            }
+        }
+        (deg, order)
+    }
+    pub fn degeneracy(&self) -> usize {
+        self.core_numbers().into_iter().max().unwrap_or(0)
+    }
+    /// Acyclic orientation along the degeneracy ordering: every edge points
+    /// from the node peeled first to the one peeled later, so nodes point to
+    /// neighbours of equal or higher core and out-degrees are at most the
+    /// degeneracy. Out-neighbours are sorted.
+    pub fn orient_by_degeneracy(&self) -> Vec<Vec<usize>> {
+        let (_, order) = self.core_decomposition();
+        let mut rank = vec![0; self.n];
+        for (i, &u) in order.iter().enumerate() {
+            rank[u] = i;
+        }
+        self.adjs
+            .iter()
+            .enumerate()
+            .map(|(u, adj)| adj.iter().copied().filter(|&v| rank[v] > rank[u]).collect())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_core_numbers() {
+        // every Apollonian network is 3-degenerate with all nodes in the 3-core
+        let g = NormalUndiGraph::from_apollo(3);
+        assert!(g.core_numbers().iter().all(|&c| c == 3));
+        // triangle with a pendant path
+        let g = NormalUndiGraph::from_edges(
+            "paw".to_string(),
+            5,
+            &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)],
+        );
+        assert_eq!(g.core_numbers(), vec![2, 2, 2, 1, 1]);
+        assert_eq!(g.degeneracy(), 2);
+    }
+
+    #[test]
+    fn test_orient_by_degeneracy() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let out = g.orient_by_degeneracy();
+        assert_eq!(out.iter().map(|adj| adj.len()).sum::<usize>(), g.m);
+        assert!(out.iter().all(|adj| adj.len() <= g.degeneracy()));
+        let core = g.core_numbers();
+        for (u, adj) in out.iter().enumerate() {
+            for &v in adj {
+                assert!(core[v] >= core[u]);
+                assert!(out[v].binary_search(&u).is_err());
+            }
+        }
+    }
+}
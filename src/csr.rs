@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use super::normal_graph::NormalUndiGraph;
+
+/// Compressed sparse row storage: the sorted neighbours of `u` are
+/// `neighbors[offsets[u]..offsets[u + 1]]`, all in one allocation.
+#[derive(Clone)]
+pub struct CsrGraph {
+    pub name: String,
+    pub n: usize,
+    pub m: usize,
+    pub offsets: Vec<usize>,
+    pub neighbors: Vec<usize>,
+}
+
+impl fmt::Display for CsrGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "# CsrGraph: {}\n# Nodes: {} Edges: {}\n",
+            self.name, self.n, self.m
+        )?;
+        for u in 0..self.n {
+            let adj = self.neighbors(u);
+            let i = adj.partition_point(|v| v <= &u);
+            for v in adj[i..].iter() {
+                writeln!(f, "{}\t{}", u, v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl CsrGraph {
+    pub fn neighbors(&self, u: usize) -> &[usize] {
+        &self.neighbors[self.offsets[u]..self.offsets[u + 1]]
+    }
+    pub fn degree(&self, u: usize) -> usize {
+        self.offsets[u + 1] - self.offsets[u]
+    }
+    pub fn has_edge(&self, u: usize, v: usize) -> bool {
+        self.neighbors(u).binary_search(&v).is_ok()
+    }
+    /// BFS hop counts from `source`, `usize::MAX` for unreachable nodes.
+    pub fn distances_from(&self, source: usize) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.n];
+        let mut queue = VecDeque::from([source]);
+        dist[source] = 0;
+        while let Some(u) = queue.pop_front() {
+            for &v in self.neighbors(u) {
+                if dist[v] == usize::MAX {
+                    dist[v] = dist[u] + 1;
+                    queue.push_back(v);
+                }
+            }
+        }
+        dist
+    }
+    pub fn to_normal(&self) -> NormalUndiGraph {
+        NormalUndiGraph {
+            name: self.name.clone(),
+            n: self.n,
+            m: self.m,
+            adjs: (0..self.n).map(|u| self.neighbors(u).to_vec()).collect(),
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn to_csr(&self) -> CsrGraph {
+        let mut offsets = Vec::with_capacity(self.n + 1);
+        offsets.push(0);
+        for adj in &self.adjs {
+            offsets.push(offsets.last().unwrap() + adj.len());
+        }
+        CsrGraph {
+            name: self.name.clone(),
+            n: self.n,
+            m: self.m,
+            offsets,
+            neighbors: self.adjs.concat(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csr() {
+        let g = NormalUndiGraph::from_koch(3);
+        let csr = g.to_csr();
+        assert_eq!((csr.offsets.len(), csr.neighbors.len()), (g.n + 1, 2 * g.m));
+        for u in 0..g.n {
+            assert_eq!(csr.neighbors(u), &g.adjs[u][..]);
+            assert_eq!(csr.degree(u), g.adjs[u].len());
+        }
+        assert_eq!(csr.distances_from(5), g.distances_from(5));
+        assert!(csr.has_edge(0, 1) && !csr.has_edge(0, g.n - 1));
+        assert_eq!(csr.to_normal().adjs, g.adjs);
+        assert_eq!(
+            format!("{}", csr),
+            format!("{}", g).replace("NormalUndiGraph", "CsrGraph")
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::normal_graph::NormalUndiGraph;
+
+struct RootedBfs {
+    parent: Vec<usize>,
+    dist: Vec<usize>,
+    // child of the root on the tree path to each node
+    branch: Vec<usize>,
+}
+
+impl NormalUndiGraph {
+    fn rooted_bfs(&self, root: usize) -> RootedBfs {
+        let mut parent = vec![usize::MAX; self.n];
+        let mut dist = vec![usize::MAX; self.n];
+        let mut branch = vec![usize::MAX; self.n];
+        let mut queue = VecDeque::from([root]);
+        parent[root] = root;
+        dist[root] = 0;
+        branch[root] = root;
+        while let Some(u) = queue.pop_front() {
+            for &v in &self.adjs[u] {
+                if dist[v] == usize::MAX {
+                    parent[v] = u;
+                    dist[v] = dist[u] + 1;
+                    branch[v] = if u == root { v } else { branch[u] };
+                    queue.push_back(v);
+                }
+            }
+        }
+        RootedBfs {
+            parent,
+            dist,
+            branch,
+        }
+    }
+    /// Length of the shortest cycle, or `None` for forests.
+    pub fn girth(&self) -> Option<usize> {
+        let mut best: Option<usize> = None;
+        for root in 0..self.n {
+            let tree = self.rooted_bfs(root);
+            for (u, adj) in self.adjs.iter().enumerate() {
+                if tree.dist[u] == usize::MAX {
+                    continue;
+                }
+                for &w in adj {
+                    if u < w && tree.parent[u] != w && tree.parent[w] != u {
+                        let len = tree.dist[u] + tree.dist[w] + 1;
+                        best = Some(best.map_or(len, |b| b.min(len)));
+                    }
+                }
+            }
+        }
+        best
+    }
+    /// Horton's algorithm: candidate cycles from every BFS tree, filtered by
+    /// Gaussian elimination over GF(2) in order of increasing length. Each
+    /// cycle is returned as its node sequence, starting and ending implicitly
+    /// at the first node.
+    pub fn minimum_cycle_basis(&self) -> Vec<Vec<usize>> {
+        let mut edge_index: HashMap<(usize, usize), usize> = HashMap::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &v in adj {
+                if u < v {
+                    let idx = edge_index.len();
+                    edge_index.insert((u, v), idx);
+                }
+            }
+        }
+        let words = edge_index.len().div_ceil(64);
+        let dim = edge_index.len() + self.num_components() - self.n;
+        if dim == 0 {
+            return Vec::new();
+        }
+        let bit = |bits: &mut Vec<u64>, u: usize, v: usize| {
+            let idx = edge_index[&(u.min(v), u.max(v))];
+            bits[idx / 64] ^= 1 << (idx % 64);
+        };
+        let mut seen: HashSet<Vec<u64>> = HashSet::new();
+        let mut candidates: Vec<(Vec<u64>, Vec<usize>)> = Vec::new();
+        for root in 0..self.n {
+            let tree = self.rooted_bfs(root);
+            for (x, adj) in self.adjs.iter().enumerate() {
+                if tree.dist[x] == usize::MAX {
+                    continue;
+                }
+                for &y in adj {
+                    if x > y || tree.parent[x] == y || tree.parent[y] == x {
+                        continue;
+                    }
+                    if x != root && y != root && tree.branch[x] == tree.branch[y] {
+                        continue;
+                    }
+                    let mut bits = vec![0u64; words];
+                    let mut nodes = Vec::with_capacity(tree.dist[x] + tree.dist[y] + 1);
+                    let mut u = x;
+                    while u != root {
+                        nodes.push(u);
+                        bit(&mut bits, u, tree.parent[u]);
+                        u = tree.parent[u];
+                    }
+                    nodes.push(root);
+                    nodes.reverse();
+                    let mut u = y;
+                    while u != root {
+                        nodes.push(u);
+                        bit(&mut bits, u, tree.parent[u]);
+                        u = tree.parent[u];
+                    }
+                    bit(&mut bits, x, y);
+                    if seen.insert(bits.clone()) {
+                        candidates.push((bits, nodes));
+                    }
+                }
+            }
+        }
+        candidates.sort_by_key(|(_, nodes)| nodes.len());
+        let mut basis: Vec<(usize, Vec<u64>)> = Vec::with_capacity(dim);
+        let mut cycles = Vec::with_capacity(dim);
+        for (bits, nodes) in candidates {
+            let mut reduced = bits;
+            for (pivot, row) in &basis {
+                if reduced[pivot / 64] >> (pivot % 64) & 1 == 1 {
+                    for (a, b) in reduced.iter_mut().zip(row) {
+                        *a ^= b;
+                    }
+                }
+            }
+            if let Some(w) = reduced.iter().position(|&x| x != 0) {
+                let pivot = w * 64 + reduced[w].trailing_zeros() as usize;
+                basis.push((pivot, reduced));
+                cycles.push(nodes);
+                if cycles.len() == dim {
+                    break;
+                }
+            }
+        }
+        cycles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_girth() {
+        assert_eq!(NormalUndiGraph::from_apollo(2).girth(), Some(3));
+        let c5 = NormalUndiGraph::from_edges(
+            "C5".to_string(),
+            5,
+            &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)],
+        );
+        assert_eq!(c5.girth(), Some(5));
+        let path = NormalUndiGraph::from_edges("P3".to_string(), 3, &[(0, 1), (1, 2)]);
+        assert_eq!(path.girth(), None);
+        assert!(path.minimum_cycle_basis().is_empty());
+    }
+
+    #[test]
+    fn test_minimum_cycle_basis() {
+        // Koch networks are trees of triangles: the basis is exactly the triangles
+        let g = NormalUndiGraph::from_koch(2);
+        let basis = g.minimum_cycle_basis();
+        assert_eq!(basis.len(), g.m - g.n + 1);
+        assert!(basis.iter().all(|c| c.len() == 3));
+
+        // two squares sharing an edge: basis is both squares, not the hexagon
+        let g = NormalUndiGraph::from_edges(
+            "domino".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (1, 4), (4, 5), (5, 2)],
+        );
+        let basis = g.minimum_cycle_basis();
+        assert_eq!(basis.len(), 2);
+        assert_eq!(basis.iter().map(|c| c.len()).sum::<usize>(), 8);
+        for cycle in &basis {
+            for i in 0..cycle.len() {
+                let (u, v) = (cycle[i], cycle[(i + 1) % cycle.len()]);
+                assert!(g.adjs[u].binary_search(&v).is_ok());
+            }
+        }
+    }
+}
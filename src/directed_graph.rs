@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+use super::error::GraphError;
+use super::general_graph::GeneralUndiGraph;
+use super::normal_graph::NormalUndiGraph;
+
+pub struct GeneralDiGraph {
+    pub name: String,
+    pub nodes: HashSet<usize>,
+    // arcs `(u, v)` from `u` to `v`
+    pub edges: HashSet<(usize, usize)>,
+}
+
+pub struct NormalDiGraph {
+    pub name: String,
+    pub n: usize,
+    pub m: usize,
+    // sorted successors and predecessors of every node
+    pub out_adjs: Vec<Vec<usize>>,
+    pub in_adjs: Vec<Vec<usize>>,
+}
+
+impl fmt::Display for GeneralDiGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "# GeneralDiGraph: {}\n# Nodes: {} Edges: {}\n",
+            self.name,
+            self.num_nodes(),
+            self.num_edges()
+        )?;
+        let mut edges: Vec<&(usize, usize)> = self.edges.iter().collect();
+        edges.sort_unstable();
+        for (u, v) in edges {
+            writeln!(f, "{}\t{}", u, v)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NormalDiGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "# NormalDiGraph: {}\n# Nodes: {} Edges: {}\n",
+            self.name, self.n, self.m
+        )?;
+        for (u, adj) in self.out_adjs.iter().enumerate() {
+            for v in adj {
+                writeln!(f, "{}\t{}", u, v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl GeneralDiGraph {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            nodes: HashSet::new(),
+            edges: HashSet::new(),
+        }
+    }
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+    /// Adds the arc from `u` to `v`; self-loops are ignored.
+    pub fn add_edge(&mut self, u: usize, v: usize) {
+        if u == v {
+            return;
+        }
+        self.nodes.insert(u);
+        self.nodes.insert(v);
+        self.edges.insert((u, v));
+    }
+    /// Reads `u v` lines for arcs `u -> v`, with `#` or `%` comments,
+    /// failing on the first malformed line.
+    pub fn from_file(name: &str, f: File) -> Result<Self, GraphError> {
+        let mut g = Self::new(name.to_string());
+        let reader = BufReader::new(f);
+        for (i, line) in reader.lines().enumerate() {
+            let line = line.map_err(|e| {
+                GraphError::io(format!("Failed to read line {} of '{}'", i + 1, name), e)
+            })?;
+            if line.starts_with('#') || line.starts_with('%') {
+                continue;
+            }
+            let mut split = line.split_whitespace();
+            let mut next = || -> Result<usize, GraphError> {
+                split
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| GraphError::Parse {
+                        name: name.to_string(),
+                        line: i + 1,
+                        content: line.clone(),
+                    })
+            };
+            let (u, v) = (next()?, next()?);
+            g.add_edge(u, v);
+        }
+        Ok(g)
+    }
+    /// Largest strongly connected component, ties going to the one with the
+    /// smallest id.
+    pub fn lcc(mut self) -> Self {
+        let mut ids: Vec<usize> = self.nodes.iter().copied().collect();
+        ids.sort_unstable();
+        let normal = NormalDiGraph::from_general(&self);
+        let comp = normal.strongly_connected_components();
+        let mut sizes: HashMap<usize, usize> = HashMap::new();
+        for &c in &comp {
+            *sizes.entry(c).or_insert(0) += 1;
+        }
+        // node with the smallest id among those in a largest component
+        let best = (0..normal.n).max_by_key(|&u| (sizes[&comp[u]], std::cmp::Reverse(u)));
+        if let Some(best) = best {
+            let keep: HashSet<usize> = (0..normal.n)
+                .filter(|&u| comp[u] == comp[best])
+                .map(|u| ids[u])
+                .collect();
+            self.nodes.retain(|u| keep.contains(u));
+            self.edges
+                .retain(|(u, v)| keep.contains(u) && keep.contains(v));
+        }
+        self
+    }
+    /// Forgets arc directions, merging reciprocal arcs.
+    pub fn as_undirected(&self) -> GeneralUndiGraph {
+        let mut g = GeneralUndiGraph::new(self.name.clone());
+        for &(u, v) in &self.edges {
+            g.add_edge(u, v);
+        }
+        g
+    }
+}
+
+impl NormalDiGraph {
+    pub fn from_edges(name: String, n: usize, edges: &[(usize, usize)]) -> Self {
+        let mut out_adjs: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut in_adjs: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for &(u, v) in edges {
+            if u != v {
+                out_adjs[u].push(v);
+            }
+        }
+        let mut m = 0;
+        for (u, adj) in out_adjs.iter_mut().enumerate() {
+            adj.sort_unstable();
+            adj.dedup();
+            m += adj.len();
+            for &v in adj.iter() {
+                in_adjs[v].push(u);
+            }
+        }
+        Self {
+            name,
+            n,
+            m,
+            out_adjs,
+            in_adjs,
+        }
+    }
+    /// Numbers nodes by increasing id of `g`.
+    pub fn from_general(g: &GeneralDiGraph) -> Self {
+        let mut ids: Vec<usize> = g.nodes.iter().copied().collect();
+        ids.sort_unstable();
+        let o2n: HashMap<usize, usize> = ids.iter().enumerate().map(|(i, &u)| (u, i)).collect();
+        let edges: Vec<(usize, usize)> = g.edges.iter().map(|(u, v)| (o2n[u], o2n[v])).collect();
+        Self::from_edges(g.name.clone(), ids.len(), &edges)
+    }
+    pub fn out_degrees(&self) -> Vec<usize> {
+        self.out_adjs.iter().map(|adj| adj.len()).collect()
+    }
+    pub fn in_degrees(&self) -> Vec<usize> {
+        self.in_adjs.iter().map(|adj| adj.len()).collect()
+    }
+    /// Strongly connected component of every node by iterative Tarjan,
+    /// components numbered in the order their roots are finished.
+    pub fn strongly_connected_components(&self) -> Vec<usize> {
+        let mut index = vec![usize::MAX; self.n];
+        let mut low = vec![0; self.n];
+        let mut comp = vec![usize::MAX; self.n];
+        let mut on_stack = vec![false; self.n];
+        let mut stack: Vec<usize> = Vec::new();
+        let (mut next_index, mut next_comp) = (0, 0);
+        for root in 0..self.n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            // call stack of nodes with the index of their next successor
+            let mut calls = vec![(root, 0)];
+            index[root] = next_index;
+            low[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (u, ref mut i)) = calls.last_mut() {
+                if let Some(&v) = self.out_adjs[u].get(*i) {
+                    *i += 1;
+                    if index[v] == usize::MAX {
+                        index[v] = next_index;
+                        low[v] = next_index;
+                        next_index += 1;
+                        stack.push(v);
+                        on_stack[v] = true;
+                        calls.push((v, 0));
+                    } else if on_stack[v] {
+                        low[u] = low[u].min(index[v]);
+                    }
+                    continue;
+                }
+                calls.pop();
+                if let Some(&(parent, _)) = calls.last() {
+                    low[parent] = low[parent].min(low[u]);
+                }
+                if low[u] == index[u] {
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        comp[w] = next_comp;
+                        if w == u {
+                            break;
+                        }
+                    }
+                    next_comp += 1;
+                }
+            }
+        }
+        comp
+    }
+    /// Forgets arc directions, merging reciprocal arcs.
+    pub fn as_undirected(&self) -> NormalUndiGraph {
+        let mut edges = Vec::with_capacity(self.m);
+        for (u, adj) in self.out_adjs.iter().enumerate() {
+            edges.extend(adj.iter().map(|&v| (u, v)));
+        }
+        NormalUndiGraph::from_edges(self.name.clone(), self.n, &edges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Seek, SeekFrom, Write};
+
+    #[test]
+    fn test_from_file() {
+        let mut f = tempfile::tempfile().unwrap();
+        write!(f, "% asym\n10 20\n20 30\n30 10\n30 40\n40 30\n40 50\n").unwrap();
+        f.seek(SeekFrom::Start(0)).unwrap();
+        let g = GeneralDiGraph::from_file("d", f).unwrap();
+        assert_eq!((g.num_nodes(), g.num_edges()), (5, 6));
+        let normal = NormalDiGraph::from_general(&g);
+        assert_eq!(normal.out_adjs[2], vec![0, 3]);
+        assert_eq!(normal.in_adjs[2], vec![1, 3]);
+        assert_eq!(normal.out_degrees().iter().sum::<usize>(), normal.m);
+        let undirected = normal.as_undirected();
+        assert_eq!(undirected.m, 5);
+        assert_eq!(g.as_undirected().num_edges(), 5);
+        let lcc = g.lcc();
+        let mut nodes: Vec<usize> = lcc.nodes.into_iter().collect();
+        nodes.sort_unstable();
+        assert_eq!(nodes, vec![10, 20, 30, 40]);
+        assert_eq!(lcc.edges.len(), 5);
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        // cycle 0-1-2, arc 2 -> 3, cycle 3-4, sink 5
+        let g = NormalDiGraph::from_edges(
+            "d".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 0), (2, 3), (3, 4), (4, 3), (4, 5)],
+        );
+        let comp = g.strongly_connected_components();
+        assert_eq!(comp[0], comp[1]);
+        assert_eq!(comp[1], comp[2]);
+        assert_eq!(comp[3], comp[4]);
+        assert_ne!(comp[0], comp[3]);
+        assert_ne!(comp[3], comp[5]);
+        // sinks are finished first
+        assert_eq!(comp[5], 0);
+    }
+}
@@ -20,6 +20,7 @@ impl<T> DSU<T>
 where
     T: std::cmp::Eq + std::hash::Hash + Clone,
 {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
@@ -43,6 +44,7 @@ where
             true
         }
     }
+    #[allow(clippy::clone_on_copy)]
     fn index(&mut self, val: T) -> usize {
         match self.indices.get(&val) {
             Some(i) => i.clone(),
@@ -79,11 +81,68 @@ where
     }
 }
 
+// Stored as `(value, parent index)` pairs in index order; the value index is
+// rebuilt on load, so keys need not be strings in formats like JSON.
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for DSU<T>
+where
+    T: std::cmp::Eq + std::hash::Hash + Clone + serde::Serialize,
+{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.entries.iter().map(|e| (&e.val, e.parent)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for DSU<T>
+where
+    T: std::cmp::Eq + std::hash::Hash + Clone + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+        let pairs = Vec::<(T, usize)>::deserialize(deserializer)?;
+        let mut dsu = Self::new();
+        for (val, parent) in pairs {
+            if dsu.indices.insert(val.clone(), dsu.entries.len()).is_some() {
+                return Err(D::Error::custom("duplicate DSU value"));
+            }
+            dsu.entries.push(DSUEntry { val, parent });
+        }
+        // every parent chain must end at a root, or `find` would not return
+        let n = dsu.entries.len();
+        let mut state = vec![0u8; n];
+        for s in 0..n {
+            let mut x = s;
+            let mut path = Vec::new();
+            while state[x] == 0 {
+                state[x] = 1;
+                path.push(x);
+                let px = dsu.entries[x].parent;
+                if px >= n {
+                    return Err(D::Error::custom("DSU parent out of range"));
+                }
+                if px == x {
+                    break;
+                }
+                x = px;
+            }
+            if state[x] == 1 && dsu.entries[x].parent != x {
+                return Err(D::Error::custom("DSU parents form a cycle"));
+            }
+            for y in path {
+                state[y] = 2;
+            }
+        }
+        Ok(dsu)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn test_add() {
         let mut dsu: DSU<&str> = DSU::new();
         assert_eq!(dsu.add("1"), true);
@@ -118,4 +177,19 @@ mod tests {
         assert_ne!(dsu.find_unchecked("1"), dsu.find_unchecked("3"));
         assert_ne!(dsu.find_unchecked("2"), dsu.find_unchecked("3"));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut dsu: DSU<(u32, u32)> = DSU::new();
+        dsu.union((1, 2), (3, 4));
+        dsu.add((5, 6));
+        let json = serde_json::to_string(&dsu).unwrap();
+        let mut back: DSU<(u32, u32)> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.find((1, 2)), back.find((3, 4)));
+        assert_ne!(back.find((1, 2)), back.find((5, 6)));
+        assert!(serde_json::from_str::<DSU<u32>>("[[1,1],[2,0]]").is_err());
+        assert!(serde_json::from_str::<DSU<u32>>("[[1,0],[1,1]]").is_err());
+        assert!(serde_json::from_str::<DSU<u32>>("[[1,2]]").is_err());
+    }
 }
@@ -0,0 +1,168 @@
+use super::normal_graph::NormalUndiGraph;
+
+// largest finite distance and a node attaining it, or an error when some
+// node is unreachable
+fn farthest(g: &NormalUndiGraph, dist: &[usize]) -> Result<(usize, usize), String> {
+    if dist.contains(&usize::MAX) {
+        return Err(format!(
+            "Graph '{}' is disconnected, eccentricities are unbounded",
+            g.name
+        ));
+    }
+    let (u, &d) = dist
+        .iter()
+        .enumerate()
+        .max_by_key(|&(u, d)| (*d, std::cmp::Reverse(u)))
+        .unwrap();
+    Ok((d, u))
+}
+
+impl NormalUndiGraph {
+    /// Exact eccentricities of all nodes by the bounding-diameters algorithm of
+    /// Takes and Kosters: every BFS tightens lower and upper bounds on all
+    /// other eccentricities, which usually settles them after few searches.
+    pub fn eccentricities(&self) -> Result<Vec<usize>, String> {
+        if self.n == 0 {
+            return Ok(Vec::new());
+        }
+        let mut lo = vec![0usize; self.n];
+        let mut hi = vec![usize::MAX; self.n];
+        let mut open: Vec<usize> = (0..self.n).collect();
+        let mut pick_upper = true;
+        while !open.is_empty() {
+            let key = |&&u: &&usize| (self.adjs[u].len(), std::cmp::Reverse(u));
+            let v = if pick_upper {
+                let best = open.iter().map(|&u| hi[u]).max().unwrap();
+                *open
+                    .iter()
+                    .filter(|&&u| hi[u] == best)
+                    .max_by_key(key)
+                    .unwrap()
+            } else {
+                let best = open.iter().map(|&u| lo[u]).min().unwrap();
+                *open
+                    .iter()
+                    .filter(|&&u| lo[u] == best)
+                    .max_by_key(key)
+                    .unwrap()
+            };
+            pick_upper = !pick_upper;
+            let dist = self.distances_from(v);
+            let (ecc, _) = farthest(self, &dist)?;
+            lo[v] = ecc;
+            hi[v] = ecc;
+            for &w in &open {
+                lo[w] = lo[w].max(dist[w]).max(ecc - dist[w]);
+                hi[w] = hi[w].min(ecc + dist[w]);
+            }
+            open.retain(|&w| lo[w] != hi[w]);
+        }
+        Ok(lo)
+    }
+    /// Eccentricity of `u` alone, by one BFS.
+    pub fn eccentricity(&self, u: usize) -> Result<usize, String> {
+        Ok(farthest(self, &self.distances_from(u))?.0)
+    }
+    pub fn radius(&self) -> Result<usize, String> {
+        Ok(self.eccentricities()?.into_iter().min().unwrap_or(0))
+    }
+    pub fn diameter(&self) -> Result<usize, String> {
+        Ok(self.eccentricities()?.into_iter().max().unwrap_or(0))
+    }
+    /// Nodes whose eccentricity equals the radius.
+    pub fn center(&self) -> Result<Vec<usize>, String> {
+        let ecc = self.eccentricities()?;
+        let radius = ecc.iter().copied().min().unwrap_or(0);
+        Ok((0..self.n).filter(|&u| ecc[u] == radius).collect())
+    }
+    /// Nodes whose eccentricity equals the diameter.
+    pub fn periphery(&self) -> Result<Vec<usize>, String> {
+        let ecc = self.eccentricities()?;
+        let diameter = ecc.iter().copied().max().unwrap_or(0);
+        Ok((0..self.n).filter(|&u| ecc[u] == diameter).collect())
+    }
+    /// Lower bound on the diameter from two BFS: the eccentricity of the node
+    /// farthest from `start`. Returns the bound with both ends of the path.
+    pub fn double_sweep(&self, start: usize) -> Result<(usize, usize, usize), String> {
+        let (_, a) = farthest(self, &self.distances_from(start))?;
+        let (d, b) = farthest(self, &self.distances_from(a))?;
+        Ok((d, a, b))
+    }
+    /// Exact diameter by iFUB (Crescenzi et al.): BFS from a central node,
+    /// then eccentricities of its fringe level by level until the lower
+    /// bound beats twice the next level. Usually far fewer BFS than `diameter`.
+    pub fn diameter_ifub(&self) -> Result<usize, String> {
+        if self.n == 0 {
+            return Ok(0);
+        }
+        let hub = (0..self.n).max_by_key(|&u| self.adjs[u].len()).unwrap();
+        let (mut lb, a, b) = self.double_sweep(hub)?;
+        // middle of the swept path as the root
+        let (dist_a, dist_b) = (self.distances_from(a), self.distances_from(b));
+        let root = (0..self.n)
+            .find(|&u| dist_a[u] == lb / 2 && dist_a[u] + dist_b[u] == lb)
+            .unwrap();
+        let dist = self.distances_from(root);
+        let height = *dist.iter().max().unwrap();
+        let mut levels = vec![Vec::new(); height + 1];
+        for (u, &d) in dist.iter().enumerate() {
+            levels[d].push(u);
+        }
+        lb = lb.max(height);
+        let mut i = height;
+        // pairs within levels below i are at most 2 * i apart
+        while i > 0 && lb < 2 * i {
+            for &u in &levels[i] {
+                lb = lb.max(self.eccentricity(u)?);
+            }
+            if lb > 2 * (i - 1) {
+                break;
+            }
+            i -= 1;
+        }
+        Ok(lb)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eccentricities() {
+        let g = NormalUndiGraph::from_koch(3);
+        let brute: Vec<usize> = (0..g.n)
+            .map(|u| g.distances_from(u).into_iter().max().unwrap())
+            .collect();
+        assert_eq!(g.eccentricities().unwrap(), brute);
+        let disconnected = NormalUndiGraph::from_edges("g".to_string(), 3, &[(0, 1)]);
+        assert!(disconnected.eccentricities().is_err());
+    }
+
+    #[test]
+    fn test_diameter_ifub() {
+        for g in [
+            NormalUndiGraph::from_koch(3),
+            NormalUndiGraph::from_apollo(3),
+            NormalUndiGraph::from_watts_strogatz(200, 4, 0.1, 7),
+        ] {
+            let ecc = g.eccentricities().unwrap();
+            assert_eq!(g.diameter_ifub().unwrap(), g.diameter().unwrap());
+            assert_eq!(g.eccentricity(5).unwrap(), ecc[5]);
+        }
+        let two = NormalUndiGraph::from_edges("two".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert!(two.diameter_ifub().is_err());
+    }
+
+    #[test]
+    fn test_center_periphery() {
+        let path =
+            NormalUndiGraph::from_edges("P5".to_string(), 5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_eq!(path.center().unwrap(), vec![2]);
+        assert_eq!(path.periphery().unwrap(), vec![0, 4]);
+        assert_eq!((path.radius().unwrap(), path.diameter().unwrap()), (2, 4));
+        // the three hubs of a pseudofractal network lie in its centre
+        let g = NormalUndiGraph::from_pseudofractal(3);
+        assert!(g.center().unwrap().starts_with(&[0, 1, 2]));
+    }
+}
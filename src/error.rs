@@ -0,0 +1,38 @@
+use std::io;
+
+use thiserror::Error;
+
+/// Errors of loading graphs from the network or from files.
+#[derive(Debug, Error)]
+pub enum GraphError {
+    #[error("Failed to fetch '{url}': {reason}")]
+    Network { url: String, reason: String },
+    // `line` counts from 1
+    #[error("Malformed line {line} of '{name}': {content}")]
+    Parse {
+        name: String,
+        line: usize,
+        content: String,
+    },
+    #[error("{context}: {source}")]
+    Io {
+        context: String,
+        #[source]
+        source: io::Error,
+    },
+    // budget overruns, cancellation and other failures reported as text
+    #[error("{0}")]
+    Other(String),
+}
+
+impl GraphError {
+    pub(crate) fn io(context: String, source: io::Error) -> Self {
+        GraphError::Io { context, source }
+    }
+}
+
+impl From<String> for GraphError {
+    fn from(message: String) -> Self {
+        GraphError::Other(message)
+    }
+}
@@ -0,0 +1,103 @@
+use super::normal_graph::NormalUndiGraph;
+
+/// Names of the entries of `graph_features`, in order.
+pub const GRAPH_FEATURE_NAMES: [&str; 16] = [
+    "log_nodes",
+    "log_edges",
+    "density",
+    "mean_degree",
+    "degree_std",
+    "degree_skewness",
+    "max_degree_fraction",
+    "average_clustering",
+    "transitivity",
+    "degree_assortativity",
+    "component_fraction",
+    "largest_component_fraction",
+    "degeneracy",
+    "adjacency_radius",
+    "spectral_gap",
+    "algebraic_connectivity",
+];
+
+impl NormalUndiGraph {
+    /// Fixed-length descriptor named by `GRAPH_FEATURE_NAMES`, for feeding
+    /// collections of graphs to classifiers. Sizes are taken as logarithms
+    /// and quantities undefined on a graph, such as the assortativity of a
+    /// regular graph, are reported as 0 so every entry is finite.
+    pub fn graph_features(&self) -> Vec<f64> {
+        if self.n == 0 {
+            return vec![0.0; GRAPH_FEATURE_NAMES.len()];
+        }
+        let n = self.n as f64;
+        let degrees: Vec<f64> = self.adjs.iter().map(|adj| adj.len() as f64).collect();
+        let mean = degrees.iter().sum::<f64>() / n;
+        let central = |k: i32| degrees.iter().map(|d| (d - mean).powi(k)).sum::<f64>() / n;
+        let std = central(2).sqrt();
+        let skewness = if std > 0.0 {
+            central(3) / std.powi(3)
+        } else {
+            0.0
+        };
+        let max_degree = degrees.iter().copied().fold(0.0, f64::max);
+        let sizes = self.component_sizes();
+        let largest = sizes.iter().copied().max().unwrap_or(0);
+        let features = [
+            (n + 1.0).ln(),
+            (self.m as f64 + 1.0).ln(),
+            self.density(),
+            mean,
+            std,
+            skewness,
+            if self.n > 1 {
+                max_degree / (n - 1.0)
+            } else {
+                0.0
+            },
+            self.average_clustering(),
+            self.transitivity(),
+            self.degree_assortativity(),
+            sizes.len() as f64 / n,
+            largest as f64 / n,
+            self.degeneracy() as f64,
+            // unconverged eigenvalues become NaN and are zeroed below
+            self.adjacency_eigenvalues(1)
+                .map_or(f64::NAN, |top| top.first().copied().unwrap_or(0.0)),
+            self.spectral_gap().unwrap_or(f64::NAN),
+            self.algebraic_connectivity().unwrap_or(f64::NAN),
+        ];
+        features
+            .into_iter()
+            .map(|x| if x.is_finite() { x } else { 0.0 })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graph_features() {
+        let k4 = NormalUndiGraph::from_apollo(0);
+        let f = k4.graph_features();
+        assert_eq!(f.len(), GRAPH_FEATURE_NAMES.len());
+        let get = |f: &[f64], name| f[GRAPH_FEATURE_NAMES.iter().position(|&x| x == name).unwrap()];
+        assert_eq!(get(&f, "density"), 1.0);
+        assert_eq!(get(&f, "degree_std"), 0.0);
+        // the undefined assortativity of a regular graph becomes 0
+        assert_eq!(get(&f, "degree_assortativity"), 0.0);
+        assert!((get(&f, "adjacency_radius") - 3.0).abs() < 1e-6);
+        assert!((get(&f, "algebraic_connectivity") - 4.0).abs() < 1e-6);
+
+        // the families are told apart, e.g. by clustering and hub dominance
+        let apollo = NormalUndiGraph::from_apollo(3).graph_features();
+        let koch = NormalUndiGraph::from_koch(2).graph_features();
+        assert!(apollo.iter().chain(&koch).all(|x| x.is_finite()));
+        assert!(get(&apollo, "transitivity") != get(&koch, "transitivity"));
+        assert_eq!(
+            NormalUndiGraph::from_edges("empty".to_string(), 0, &[]).graph_features(),
+            vec![0.0; GRAPH_FEATURE_NAMES.len()]
+        );
+    }
+}
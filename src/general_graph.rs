@@ -1,14 +1,20 @@
 use bytes::BufMut;
-use bzip2::read::BzDecoder;
 use futures::StreamExt;
 use indicatif::{HumanDuration, ProgressBar, ProgressStyle};
 use std::collections::{HashMap, HashSet};
 use std::fmt;
-use std::fs::{read_dir, remove_dir, File};
-use std::io::{BufRead, BufReader, Write};
+use std::fs::File;
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
-use tar::Archive;
-use tempfile::Builder;
+use tokio::runtime::{Builder, Handle};
+
+use super::archive;
+use super::config::ComputeConfig;
+use super::error::GraphError;
+use super::io::GraphFormat;
+use super::konect::KonectOptions;
+use super::normal_graph::NormalUndiGraph;
 
 enum FastDSUEntry {
     Id(usize),
@@ -63,48 +69,71 @@ impl FastDSU {
             false
         }
     }
-    fn retain_map(&self) -> HashMap<usize, bool> {
-        let (root, _) = self
-            .parent
+    // root of the largest set, ties going to the set with the smallest
+    // element; None if empty
+    fn largest_root(&self) -> Option<usize> {
+        let mut min_elem: HashMap<usize, usize> = HashMap::new();
+        for x in self.parent.keys() {
+            let e = min_elem.entry(self.find(x)).or_insert(*x);
+            *e = (*e).min(*x);
+        }
+        self.parent
             .iter()
-            .max_by_key(|(_, y)| match y {
-                Id(_) => &-1,
-                Num(num) => num,
+            .filter_map(|(x, y)| match y {
+                Id(_) => None,
+                Num(num) => Some((*num, std::cmp::Reverse(min_elem[x]), *x)),
             })
-            .unwrap();
-        let root = *root;
-        let retain_iter = self.parent.keys().map(|x| (*x, self.find(x) == root));
-        let retain_vec: Vec<(usize, bool)> = retain_iter.clone().collect();
-        let mut wf = File::create("retain_iter.txt").unwrap();
-        write!(wf, "{:?}", retain_vec).unwrap();
-        HashMap::from_iter(retain_iter)
+            .max()
+            .map(|(_, _, x)| x)
+    }
+    fn retain_map(&self) -> HashMap<usize, bool> {
+        let root = self.largest_root();
+        self.parent
+            .keys()
+            .map(|x| (*x, Some(self.find(x)) == root))
+            .collect()
     }
 }
 
-async fn fetch_raw_bytes(url: &str) -> Result<Vec<u8>, String> {
+// lines or edges processed between cancellation checks and progress reports
+pub(crate) const CHECK_INTERVAL: usize = 1 << 16;
+
+async fn fetch_raw_bytes(url: &str, config: &ComputeConfig) -> Result<Vec<u8>, GraphError> {
+    let network = |reason: &str| GraphError::Network {
+        url: url.to_string(),
+        reason: reason.to_string(),
+    };
     let start = Instant::now();
     let resp = reqwest::get(url)
         .await
-        .or(Err(format!("Failed to GET from '{}'", url)))?;
+        .and_then(|resp| resp.error_for_status())
+        .map_err(|e| network(&e.to_string()))?;
     let total_size = resp
         .content_length()
-        .ok_or(format!("Failed to fetch content length from '{}'", url))?;
+        .ok_or_else(|| network("no content length"))?;
+    config.check_memory(total_size as usize, &format!("Downloading '{}'", url))?;
     let sty=ProgressStyle::with_template(
         "{msg} {wide_bar:.cyan/blue} {bytes}/{total_bytes} {bytes_per_sec} [{elapsed_precise}/{eta_precise}]"
-    ).or(Err("Failed to generate progess style template"))?.progress_chars("##=");
-    let pb = ProgressBar::new(total_size)
-        .with_style(sty)
-        .with_message(format!("Fetching {}", url));
+    ).map_err(|e| GraphError::Other(e.to_string()))?.progress_chars("##=");
+    // a progress callback in `config` replaces the terminal progress bar
+    let pb = match config.progress {
+        Some(_) => ProgressBar::hidden(),
+        None => ProgressBar::new(total_size),
+    }
+    .with_style(sty)
+    .with_message(format!("Fetching {}", url));
 
     let mut payload = Vec::with_capacity(total_size as usize);
     let mut fetched_size: u64 = 0;
     let mut stream = resp.bytes_stream();
 
     while let Some(chunk) = stream.next().await {
-        let chunk = chunk.or(Err(format!("Error while fetching payload of '{}'", url)))?;
+        config.check_cancelled(&format!("Downloading '{}'", url))?;
+        let chunk = chunk.map_err(|e| network(&e.to_string()))?;
         payload.put(&chunk[..]);
         fetched_size = total_size.min(fetched_size + chunk.len() as u64);
         pb.set_position(fetched_size);
+        config.report("download", fetched_size, Some(total_size));
     }
 
     pb.println(format!(
@@ -116,12 +145,216 @@ async fn fetch_raw_bytes(url: &str) -> Result<Vec<u8>, String> {
     Ok(payload)
 }
 
+// Runs `future` on a fresh single-threaded runtime for the blocking loaders,
+// failing instead of panicking when already inside a runtime.
+pub(crate) fn block_on<F: Future>(future: F) -> Result<F::Output, GraphError> {
+    if Handle::try_current().is_ok() {
+        return Err(GraphError::Other(
+            "Blocking loader called inside an async runtime, use the async variant".to_string(),
+        ));
+    }
+    let runtime = Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| GraphError::io("Failed to start async runtime".to_string(), e))?;
+    Ok(runtime.block_on(future))
+}
+
+// Tries the mirrors of `options` in order, moving on only after network
+// failures so that budget overruns and cancellation stop at once. When all
+// mirrors fail, the network error names the dataset and every reason.
+async fn fetch_from_mirrors(
+    internal_name: &str,
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<Vec<u8>, GraphError> {
+    let mut failures = Vec::new();
+    for url in options.tarball_urls(internal_name) {
+        match fetch_raw_bytes(&url, config).await {
+            Err(GraphError::Network { url, reason }) => {
+                failures.push(format!("{}: {}", url, reason))
+            }
+            result => return result,
+        }
+    }
+    Err(GraphError::Network {
+        url: internal_name.to_string(),
+        reason: format!("every mirror failed ({})", failures.join("; ")),
+    })
+}
+
+// Runs the decoding, disk and parsing work `f` on the blocking pool of the
+// runtime, so that concurrent downloads are not held up behind it.
+async fn run_blocking<T, F>(f: F) -> Result<T, GraphError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, GraphError> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| GraphError::Other(format!("Blocking task failed: {}", e)))?
+}
+
+// Downloads and unpacks the archive of `internal_name`, moving its edge list
+// into the cache of `options`.
+pub(crate) async fn download_konect(
+    internal_name: &str,
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<PathBuf, GraphError> {
+    let bytes = fetch_from_mirrors(internal_name, options, config).await?;
+    let (internal_name, options, config) =
+        (internal_name.to_string(), options.clone(), config.clone());
+    run_blocking(move || {
+        let tmp_dir = config.tempdir()?;
+        let candidates = archive::unpack(
+            &bytes,
+            &internal_name,
+            &format!("out.{}", internal_name),
+            tmp_dir.path(),
+        )?
+        .candidates(|path| GraphFormat::Konect.matches(path));
+        let path = options.select_entry(&internal_name, &candidates)?;
+        options.store(&internal_name, path)
+    })
+    .await
+}
+
+// Parses the selected `out.*` entry of the archive of `internal_name` while
+// streaming it through the decoder. A named `out_file` is found in the same
+// pass; otherwise a first listing pass picks the only candidate.
+async fn read_konect_in_memory(
+    name: &str,
+    internal_name: &str,
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<GeneralUndiGraph, GraphError> {
+    let bytes = fetch_from_mirrors(internal_name, options, config).await?;
+    let (name, internal_name) = (name.to_string(), internal_name.to_string());
+    let (options, config) = (options.clone(), config.clone());
+    run_blocking(move || read_konect_tarball(&name, &internal_name, &bytes, &options, &config))
+        .await
+}
+
+// `read_konect_in_memory` on the downloaded `bytes`.
+fn read_konect_tarball(
+    name: &str,
+    internal_name: &str,
+    bytes: &[u8],
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<GeneralUndiGraph, GraphError> {
+    let konect_entries = || -> Result<Vec<PathBuf>, GraphError> {
+        Ok(archive::tar_entries(bytes, internal_name)?
+            .into_iter()
+            .filter(|path| GraphFormat::Konect.matches(path))
+            .collect())
+    };
+    let chosen = match options.out_file {
+        Some(_) => None,
+        None => Some(
+            options
+                .select_entry(internal_name, &konect_entries()?)?
+                .to_path_buf(),
+        ),
+    };
+    let wanted = |path: &Path| match &chosen {
+        Some(chosen) => path == chosen,
+        None => GraphFormat::Konect.matches(path) && options.is_out_file(path),
+    };
+    let graph = archive::with_tar_entry(bytes, internal_name, wanted, |entry| {
+        GeneralUndiGraph::read_with(name, entry, GraphFormat::Konect, config)
+    })?;
+    match graph {
+        Some(graph) => Ok(graph),
+        // list the candidates only to report the missing `out_file`
+        None => Err(options
+            .select_entry(internal_name, &konect_entries()?)
+            .err()
+            .unwrap_or_else(|| {
+                GraphError::Other(format!("No konect file in archive of '{}'", internal_name))
+            })),
+    }
+}
+
+// File names of the `out.*` edge lists in the archive of `internal_name`.
+pub(crate) async fn list_konect_entries(
+    internal_name: &str,
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<Vec<String>, GraphError> {
+    let bytes = fetch_from_mirrors(internal_name, options, config).await?;
+    let (internal_name, options, config) =
+        (internal_name.to_string(), options.clone(), config.clone());
+    run_blocking(move || list_archive_entries(&internal_name, &bytes, &options, &config)).await
+}
+
+fn list_archive_entries(
+    internal_name: &str,
+    bytes: &[u8],
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> Result<Vec<String>, GraphError> {
+    let candidates = if options.in_memory {
+        archive::tar_entries(bytes, internal_name)?
+            .into_iter()
+            .filter(|path| GraphFormat::Konect.matches(path))
+            .collect()
+    } else {
+        let tmp_dir = config.tempdir()?;
+        archive::unpack(
+            bytes,
+            internal_name,
+            &format!("out.{}", internal_name),
+            tmp_dir.path(),
+        )?
+        .candidates(|path| GraphFormat::Konect.matches(path))
+    };
+    Ok(candidates.iter().map(|path| entry_name(path)).collect())
+}
+
+pub(crate) fn entry_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawGeneralUndiGraph"))]
 pub struct GeneralUndiGraph {
     pub name: String,
     pub nodes: HashSet<usize>,
     pub edges: HashSet<(usize, usize)>,
 }
 
+// Deserialized fields, checked before they become a `GeneralUndiGraph` so
+// that every edge is stored as `add_edge` would store it.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawGeneralUndiGraph {
+    name: String,
+    nodes: HashSet<usize>,
+    edges: HashSet<(usize, usize)>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawGeneralUndiGraph> for GeneralUndiGraph {
+    type Error = String;
+
+    fn try_from(raw: RawGeneralUndiGraph) -> Result<Self, String> {
+        let RawGeneralUndiGraph { name, nodes, edges } = raw;
+        for &(u, v) in &edges {
+            if u >= v {
+                return Err(format!("edge ({}, {}) is a self-loop or not ordered", u, v));
+            }
+            if !nodes.contains(&u) || !nodes.contains(&v) {
+                return Err(format!("edge ({}, {}) has an endpoint outside nodes", u, v));
+            }
+        }
+        Ok(Self { name, nodes, edges })
+    }
+}
+
 impl fmt::Display for GeneralUndiGraph {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -159,6 +392,56 @@ impl GeneralUndiGraph {
             self.edges.insert((v, u));
         }
     }
+    /// Removes the edge `(u, v)` if present; its endpoints stay in `nodes`.
+    pub fn remove_edge(&mut self, u: usize, v: usize) -> bool {
+        self.edges.remove(&(u.min(v), u.max(v)))
+    }
+    /// Merges `v` into `u`: the neighbours of `v` become neighbours of `u`
+    /// and `v` is removed. An edge between them is dropped rather than kept
+    /// as a self-loop, and shared neighbours keep a single edge.
+    pub fn merge_nodes(&mut self, u: usize, v: usize) -> Result<(), String> {
+        if u == v {
+            return Err(format!("Cannot merge node {} into itself", u));
+        }
+        for w in [u, v] {
+            if !self.nodes.contains(&w) {
+                return Err(format!("Node {} is not in the graph", w));
+            }
+        }
+        let moved: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .filter(|&&(a, b)| a == v || b == v)
+            .copied()
+            .collect();
+        for (a, b) in moved {
+            self.edges.remove(&(a, b));
+            self.add_edge(u, if a == v { b } else { a });
+        }
+        self.nodes.remove(&v);
+        Ok(())
+    }
+    /// Splits `u` in two: its edges to `moved_neighbors` move to a new node,
+    /// the others stay with `u`. The new node takes the smallest unused id
+    /// above every existing one and is returned; it is not linked to `u`.
+    pub fn split_node(&mut self, u: usize, moved_neighbors: &[usize]) -> Result<usize, String> {
+        if !self.nodes.contains(&u) {
+            return Err(format!("Node {} is not in the graph", u));
+        }
+        if let Some(&w) = moved_neighbors
+            .iter()
+            .find(|&&w| !self.edges.contains(&(u.min(w), u.max(w))))
+        {
+            return Err(format!("Node {} is not a neighbour of {}", w, u));
+        }
+        let fresh = self.nodes.iter().max().map_or(0, |&x| x + 1);
+        self.nodes.insert(fresh);
+        for &w in moved_neighbors {
+            self.remove_edge(u, w);
+            self.add_edge(fresh, w);
+        }
+        Ok(fresh)
+    }
     pub fn new(name: String) -> Self {
         Self {
             name,
@@ -166,57 +449,97 @@ impl GeneralUndiGraph {
             edges: HashSet::new(),
         }
     }
-    #[tokio::main]
-    pub async fn from_konect(name: &str, internal_name: &str) -> Result<Self, String> {
-        let url = format!(
-            "http://konect.cc/files/download.tsv.{}.tar.bz2",
-            internal_name
-        );
-        let tarbz2_bytes = fetch_raw_bytes(&url).await?;
-        let bzdecoder = BzDecoder::new(tarbz2_bytes.as_slice());
-        let mut archive = Archive::new(bzdecoder);
-        let tmp_dir = Builder::new()
-            .tempdir()
-            .or(Err("Failed to return a temp dir"))?;
-        let tmp_dir = tmp_dir.path();
-        let dir_path = tmp_dir.join(internal_name);
-        // println!("Decompressed file will be located under {:?}", &dir_path);
-        if dir_path.try_exists().unwrap_or(false) {
-            remove_dir(&dir_path).or(Err("Failed to remove temp dir"))?;
-        }
-        archive.unpack(tmp_dir).or(Err(format!(
-            "Failed to unpack tarball of '{}'",
-            internal_name
-        )))?;
-        for entry in read_dir(&dir_path).or(Err("Failed to traverse content of temp dir"))? {
-            let file_path = entry
-                .or(Err("Failed to traverse entry of temp dir"))?
-                .path();
-            if let Some(file_name) = file_path.file_name().and_then(|name| name.to_str()) {
-                if file_name.starts_with("out.") {
-                    let f = File::open(file_path).or(Err("Failed to open konect file"))?;
-                    return Ok(Self::from_file(name, f));
-                }
-            }
-        }
-        Err("Failed to find valid konect file in extracted dir".to_string())
+    pub fn from_konect(name: &str, internal_name: &str) -> Result<Self, GraphError> {
+        Self::from_konect_with(name, internal_name, &ComputeConfig::default())
+    }
+    /// `from_konect` with the download held to the memory budget and the
+    /// archive unpacked under the temp dir of `config`, reporting progress
+    /// to and stopping on the cancellation token of `config`.
+    pub fn from_konect_with(
+        name: &str,
+        internal_name: &str,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        Self::from_konect_cached(name, internal_name, &KonectOptions::default(), config)
     }
-    pub fn from_file(name: &str, f: File) -> Self {
-        let mut g = Self::new(name.to_string());
-        let reader = BufReader::new(f);
-        for line in reader.lines() {
-            let line = line.unwrap();
-            if line.starts_with("#") || line.starts_with("%") {
-                continue;
+    /// `from_konect_with` reading the edge list from the cache of `options`
+    /// when a valid copy exists, and storing it there after a download.
+    pub fn from_konect_cached(
+        name: &str,
+        internal_name: &str,
+        options: &KonectOptions,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        block_on(Self::from_konect_async(
+            name,
+            internal_name,
+            options,
+            config,
+        ))?
+    }
+    /// `from_konect_cached` for callers already inside an async runtime;
+    /// several downloads can be awaited concurrently.
+    pub async fn from_konect_async(
+        name: &str,
+        internal_name: &str,
+        options: &KonectOptions,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        let path = match options.cached(internal_name) {
+            Some(path) => path,
+            None if options.in_memory => {
+                return read_konect_in_memory(name, internal_name, options, config).await
             }
-            let mut split = line.split(&[' ', '\t']);
-            let u: usize = split.next().unwrap().parse().unwrap();
-            let v: usize = split.next().unwrap().parse().unwrap();
-            g.add_edge(u, v);
-        }
-        g
+            None => download_konect(internal_name, options, config).await?,
+        };
+        let (name, config) = (name.to_string(), config.clone());
+        run_blocking(move || {
+            let f = File::open(&path)
+                .map_err(|e| GraphError::io("Failed to open konect file".to_string(), e))?;
+            Self::read_with(&name, f, GraphFormat::Konect, &config)
+        })
+        .await
+    }
+    pub fn from_network_repository(name: &str, url: &str) -> Result<Self, GraphError> {
+        Self::from_network_repository_with(name, url, &ComputeConfig::default())
+    }
+    /// Downloads a Matrix Market graph from networkrepository.com, either a
+    /// plain, gzip- or bzip2-compressed `.mtx` file or a zip or tar archive
+    /// holding one, with node ids shifted to start from 0.
+    pub fn from_network_repository_with(
+        name: &str,
+        url: &str,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        block_on(Self::from_network_repository_async(name, url, config))?
+    }
+    pub async fn from_network_repository_async(
+        name: &str,
+        url: &str,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        let bytes = fetch_raw_bytes(url, config).await?;
+        let (name, url, config) = (name.to_string(), url.to_string(), config.clone());
+        run_blocking(move || {
+            let tmp_dir = config.tempdir()?;
+            let format = GraphFormat::MatrixMarket;
+            let path = archive::extract(
+                &bytes,
+                &url,
+                &archive::file_name_of(&url),
+                tmp_dir.path(),
+                |path| format.matches(path),
+            )?;
+            Self::read_with(&name, archive::open(&path, &url)?, format, &config)
+        })
+        .await
+    }
+    /// Reads `u v` lines with `#` or `%` comments, failing on the first
+    /// malformed line instead of panicking.
+    pub fn from_file(name: &str, f: File) -> Result<Self, GraphError> {
+        Self::read(name, f, GraphFormat::EdgeList)
     }
-    pub fn lcc(mut self) -> Self {
+    fn component_dsu(&self) -> FastDSU {
         let mut dsu = FastDSU::new();
         for u in &self.nodes {
             dsu.add(*u);
@@ -224,22 +547,182 @@ impl GeneralUndiGraph {
         for (u, v) in &self.edges {
             dsu.union(*u, *v);
         }
+        dsu
+    }
+    pub fn lcc(self) -> Self {
+        // a serial config has no token, so this cannot fail
+        self.lcc_with(&ComputeConfig::serial()).unwrap()
+    }
+    /// `lcc` reporting the edges merged so far and checking the cancellation
+    /// token of `config` every `CHECK_INTERVAL` edges.
+    pub fn lcc_with(mut self, config: &ComputeConfig) -> Result<Self, String> {
+        let mut dsu = FastDSU::new();
+        for u in &self.nodes {
+            dsu.add(*u);
+        }
+        let total = self.edges.len() as u64;
+        for (i, (u, v)) in self.edges.iter().enumerate() {
+            if i % CHECK_INTERVAL == 0 {
+                config.check_cancelled(&format!("LCC of '{}'", self.name))?;
+                config.report("lcc", i as u64, Some(total));
+            }
+            dsu.union(*u, *v);
+        }
+        config.report("lcc", total, Some(total));
         let rmap = dsu.retain_map();
         self.nodes.retain(|u| rmap[u]);
         self.edges.retain(|(u, v)| rmap[u] && rmap[v]);
-        self
+        Ok(self)
+    }
+    /// Largest connected component, renumbered by increasing original id, as
+    /// a `NormalUndiGraph` in one pass without copying `self`. Also returns
+    /// the original id of every new node. Ties between equally large
+    /// components go to the one containing the smallest id.
+    pub fn canonicalize(&self) -> (NormalUndiGraph, Vec<usize>) {
+        let dsu = self.component_dsu();
+        let root = dsu.largest_root();
+        let mut old_ids: Vec<usize> = self
+            .nodes
+            .iter()
+            .copied()
+            .filter(|u| Some(dsu.find(u)) == root)
+            .collect();
+        old_ids.sort_unstable();
+        let o2n: HashMap<usize, usize> = old_ids.iter().enumerate().map(|(i, &u)| (u, i)).collect();
+        let mut adjs: Vec<Vec<usize>> = vec![Vec::new(); old_ids.len()];
+        let mut m = 0;
+        for (u, v) in &self.edges {
+            if let (Some(&a), Some(&b)) = (o2n.get(u), o2n.get(v)) {
+                adjs[a].push(b);
+                adjs[b].push(a);
+                m += 1;
+            }
+        }
+        for adj in adjs.iter_mut() {
+            adj.sort_unstable();
+        }
+        let g = NormalUndiGraph {
+            name: self.name.clone(),
+            n: old_ids.len(),
+            m,
+            adjs,
+        };
+        (g, old_ids)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::{Seek, Write};
+
+    #[test]
+    fn test_canonicalize() {
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [
+            (10, 30),
+            (30, 20),
+            (20, 10),
+            (20, 7),
+            (5, 6),
+            (6, 5),
+            (8, 8),
+        ] {
+            g.add_edge(u, v);
+        }
+        let (normal, old_ids) = g.canonicalize();
+        assert_eq!(old_ids, vec![7, 10, 20, 30]);
+        assert_eq!((normal.n, normal.m), (4, 4));
+        assert_eq!(
+            normal.adjs,
+            vec![vec![2], vec![2, 3], vec![0, 1, 3], vec![1, 2]]
+        );
+        let lcc = g.lcc();
+        assert_eq!((lcc.num_nodes(), lcc.num_edges()), (4, 4));
+    }
+
+    #[test]
+    fn test_merge_and_split_nodes() {
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [(1, 2), (1, 3), (2, 3), (2, 4), (5, 6)] {
+            g.add_edge(u, v);
+        }
+        g.merge_nodes(1, 2).unwrap();
+        assert!(!g.nodes.contains(&2));
+        let mut edges: Vec<_> = g.edges.iter().copied().collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(1, 3), (1, 4), (5, 6)]);
+        assert!(g.merge_nodes(1, 2).is_err());
+        assert!(g.merge_nodes(3, 3).is_err());
+
+        let fresh = g.split_node(1, &[4]).unwrap();
+        assert_eq!(fresh, 7);
+        assert!(g.edges.contains(&(1, 3)) && g.edges.contains(&(4, 7)));
+        assert!(!g.edges.contains(&(1, 4)));
+        assert_eq!((g.num_nodes(), g.num_edges()), (6, 3));
+        assert!(g.split_node(1, &[5]).is_err());
+    }
+
+    #[test]
+    fn test_from_file_malformed() {
+        let mut f = tempfile::tempfile().unwrap();
+        write!(f, "# comment\n1 2\n2 three\n").unwrap();
+        f.rewind().unwrap();
+        match GeneralUndiGraph::from_file("dirty", f) {
+            Err(GraphError::Parse { line, content, .. }) => {
+                assert_eq!((line, content.as_str()), (3, "2 three"))
+            }
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("malformed line accepted"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_konect_async_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = KonectOptions {
+            cache_dir: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let src = dir.path().join("out.toy");
+        std::fs::write(&src, "% sym\n1 2\n2 3\n").unwrap();
+        options.store("toy", &src).unwrap();
+        let config = ComputeConfig::serial();
+        let (a, b) = tokio::join!(
+            GeneralUndiGraph::from_konect_async("a", "toy", &options, &config),
+            GeneralUndiGraph::from_konect_async("b", "toy", &options, &config),
+        );
+        assert_eq!(a.unwrap().num_edges(), 2);
+        assert_eq!(b.unwrap().num_nodes(), 3);
+        // the blocking wrapper refuses to nest runtimes instead of panicking
+        assert!(GeneralUndiGraph::from_konect_cached("c", "toy", &options, &config).is_err());
+    }
+
+    #[test]
+    fn test_mirrors_fail_as_network_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = KonectOptions {
+            cache_dir: Some(dir.path().to_path_buf()),
+            // nothing listens on the discard port of the loopback address
+            mirrors: vec!["http://127.0.0.1:9".to_string(); 2],
+            ..Default::default()
+        };
+        let config = ComputeConfig::serial();
+        match GeneralUndiGraph::from_konect_cached("toy", "toy", &options, &config) {
+            Err(GraphError::Network { url, reason }) => {
+                assert_eq!(url, "toy");
+                assert!(reason.starts_with("every mirror failed"));
+                assert!(reason.contains("http://127.0.0.1:9/download.tsv.toy.tar.bz2"));
+            }
+            other => panic!("expected a network error, got {:?}", other.err()),
+        }
+    }
 
     #[test]
     fn test_disjoint_set() {
         let rf = File::open("subelj_euroroad.txt").unwrap();
         let mut wf = File::create("euro.txt").unwrap();
-        let g = GeneralUndiGraph::from_file("euro", rf).lcc();
+        let g = GeneralUndiGraph::from_file("euro", rf).unwrap().lcc();
         write!(wf, "{}", g).unwrap();
     }
 
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use super::normal_graph::NormalUndiGraph;
+
+pub const NUM_ORBITS: usize = 73;
+const MAX_GRAPHLET: usize = 5;
+
+fn pair_index(k: usize) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for i in 0..k {
+        for j in i + 1..k {
+            pairs.push((i, j));
+        }
+    }
+    pairs
+}
+
+fn permutations(k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    let mut perms = Vec::new();
+    for p in permutations(k - 1) {
+        for pos in 0..k {
+            let mut q = p.clone();
+            q.insert(pos, k - 1);
+            perms.push(q);
+        }
+    }
+    perms
+}
+
+fn permute(
+    mask: usize,
+    perm: &[usize],
+    pairs: &[(usize, usize)],
+    index: &HashMap<(usize, usize), usize>,
+) -> usize {
+    let mut out = 0;
+    for (b, &(i, j)) in pairs.iter().enumerate() {
+        if mask >> b & 1 == 1 {
+            let (a, c) = (perm[i].min(perm[j]), perm[i].max(perm[j]));
+            out |= 1 << index[&(a, c)];
+        }
+    }
+    out
+}
+
+fn connected(mask: usize, k: usize, pairs: &[(usize, usize)]) -> bool {
+    let mut seen = 1usize;
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (b, &(i, j)) in pairs.iter().enumerate() {
+            if mask >> b & 1 == 1 && (seen >> i & 1) != (seen >> j & 1) {
+                seen |= (1 << i) | (1 << j);
+                changed = true;
+            }
+        }
+    }
+    seen == (1 << k) - 1
+}
+
+// edge list and the orbit of each node
+type Graphlet = (&'static [(usize, usize)], &'static [usize]);
+
+// Pržulj's graphlets G0-G29
+const GRAPHLETS: [Graphlet; 30] = [
+    (&[(0, 1)], &[0, 0]),
+    (&[(0, 1), (1, 2)], &[1, 2, 1]),
+    (&[(0, 1), (0, 2), (1, 2)], &[3, 3, 3]),
+    (&[(0, 1), (1, 2), (2, 3)], &[4, 5, 5, 4]),
+    (&[(0, 1), (0, 2), (0, 3)], &[7, 6, 6, 6]),
+    (&[(0, 1), (0, 3), (1, 2), (2, 3)], &[8, 8, 8, 8]),
+    (&[(0, 1), (0, 2), (1, 2), (2, 3)], &[10, 10, 11, 9]),
+    (&[(0, 1), (0, 2), (0, 3), (1, 2), (2, 3)], &[13, 12, 13, 12]),
+    (
+        &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)],
+        &[14, 14, 14, 14],
+    ),
+    (&[(0, 1), (1, 2), (2, 3), (3, 4)], &[15, 16, 17, 16, 15]),
+    (&[(0, 1), (1, 2), (2, 3), (2, 4)], &[18, 20, 21, 19, 19]),
+    (&[(0, 1), (0, 2), (0, 3), (0, 4)], &[23, 22, 22, 22, 22]),
+    (
+        &[(0, 1), (1, 2), (2, 3), (2, 4), (3, 4)],
+        &[24, 27, 26, 25, 25],
+    ),
+    (
+        &[(0, 1), (1, 2), (1, 3), (2, 3), (3, 4)],
+        &[28, 30, 29, 30, 28],
+    ),
+    (
+        &[(0, 1), (0, 2), (0, 3), (0, 4), (1, 2)],
+        &[33, 32, 32, 31, 31],
+    ),
+    (
+        &[(0, 1), (0, 4), (1, 2), (2, 3), (3, 4)],
+        &[34, 34, 34, 34, 34],
+    ),
+    (
+        &[(0, 1), (1, 2), (1, 4), (2, 3), (3, 4)],
+        &[35, 38, 37, 36, 37],
+    ),
+    (
+        &[(0, 1), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4)],
+        &[39, 42, 41, 40, 40],
+    ),
+    (
+        &[(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (3, 4)],
+        &[44, 43, 43, 43, 43],
+    ),
+    (
+        &[(0, 1), (1, 2), (1, 3), (2, 3), (2, 4), (3, 4)],
+        &[45, 47, 48, 48, 46],
+    ),
+    (
+        &[(0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4)],
+        &[50, 50, 49, 49, 49],
+    ),
+    (
+        &[(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (3, 4)],
+        &[52, 53, 51, 51, 53],
+    ),
+    (
+        &[(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4)],
+        &[55, 55, 54, 54, 54],
+    ),
+    (
+        &[(0, 1), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)],
+        &[56, 58, 57, 57, 57],
+    ),
+    (
+        &[(0, 1), (0, 4), (1, 2), (1, 4), (2, 3), (2, 4), (3, 4)],
+        &[59, 60, 60, 59, 61],
+    ),
+    (
+        &[(0, 2), (0, 3), (0, 4), (1, 3), (1, 4), (2, 3), (2, 4)],
+        &[64, 62, 64, 63, 63],
+    ),
+    (
+        &[
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ],
+        &[66, 65, 66, 67, 67],
+    ),
+    (
+        &[
+            (0, 1),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ],
+        &[68, 68, 68, 68, 69],
+    ),
+    (
+        &[
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ],
+        &[70, 70, 71, 71, 71],
+    ),
+    (
+        &[
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (1, 2),
+            (1, 3),
+            (1, 4),
+            (2, 3),
+            (2, 4),
+            (3, 4),
+        ],
+        &[72, 72, 72, 72, 72],
+    ),
+];
+
+/// Maps every connected labelled graph on 2 to 5 nodes, given as a bitmask
+/// over node pairs, to the orbit of each of its positions, using Pržulj's
+/// numbering of graphlets G0-G29 and orbits 0-72 (as also used by ORCA).
+pub struct OrbitTable {
+    // tables[k][mask] lists the orbit of each position, empty if disconnected
+    tables: Vec<Vec<Vec<usize>>>,
+    index: Vec<HashMap<(usize, usize), usize>>,
+    pub num_orbits: usize,
+}
+
+impl OrbitTable {
+    pub fn new() -> Self {
+        let mut tables = vec![Vec::new(); MAX_GRAPHLET + 1];
+        let mut index = vec![HashMap::new(); MAX_GRAPHLET + 1];
+        let mut num_orbits = 0;
+        for k in 2..=MAX_GRAPHLET {
+            let pairs = pair_index(k);
+            index[k] = pairs.iter().enumerate().map(|(b, &p)| (p, b)).collect();
+            let perms = permutations(k);
+            let canon = |mask: usize| {
+                perms
+                    .iter()
+                    .map(|p| permute(mask, p, &pairs, &index[k]))
+                    .min()
+                    .unwrap()
+            };
+            // orbit of each position of the canonical form of every graphlet
+            let mut orbit_of: HashMap<usize, Vec<usize>> = HashMap::new();
+            for (edges, orbits) in GRAPHLETS.iter().filter(|(_, o)| o.len() == k) {
+                let g = edges.iter().fold(0, |g, e| g | 1 << index[k][e]);
+                let c = canon(g);
+                let p = perms
+                    .iter()
+                    .find(|p| permute(g, p, &pairs, &index[k]) == c)
+                    .unwrap();
+                let mut rep = vec![0; k];
+                for (i, &orbit) in orbits.iter().enumerate() {
+                    rep[p[i]] = orbit;
+                    num_orbits = num_orbits.max(orbit + 1);
+                }
+                orbit_of.insert(c, rep);
+            }
+            let mut table = vec![Vec::new(); 1 << pairs.len()];
+            for mask in (0..1usize << pairs.len()).filter(|&mask| connected(mask, k, &pairs)) {
+                let c = canon(mask);
+                let p = perms
+                    .iter()
+                    .find(|p| permute(mask, p, &pairs, &index[k]) == c)
+                    .unwrap();
+                table[mask] = (0..k).map(|i| orbit_of[&c][p[i]]).collect();
+            }
+            tables[k] = table;
+        }
+        Self {
+            tables,
+            index,
+            num_orbits,
+        }
+    }
+    fn orbits(&self, g: &NormalUndiGraph, nodes: &[usize]) -> &[usize] {
+        let k = nodes.len();
+        let mut mask = 0;
+        for i in 0..k {
+            for j in i + 1..k {
+                if g.adjs[nodes[i]].binary_search(&nodes[j]).is_ok() {
+                    mask |= 1 << self.index[k][&(i, j)];
+                }
+            }
+        }
+        &self.tables[k][mask]
+    }
+}
+
+impl Default for OrbitTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NormalUndiGraph {
+    /// Per-node counts of the 73 automorphism orbits of connected graphlets on
+    /// 2 to 5 nodes, see [`OrbitTable`] for the orbit numbering. Every connected
+    /// induced subgraph is enumerated once with the ESU algorithm.
+    pub fn graphlet_degree_vectors(&self) -> Vec<[u64; NUM_ORBITS]> {
+        let table = OrbitTable::new();
+        let mut gdv = vec![[0u64; NUM_ORBITS]; self.n];
+        let mut sub = Vec::with_capacity(MAX_GRAPHLET);
+        for v in 0..self.n {
+            sub.push(v);
+            let ext: Vec<usize> = self.adjs[v].iter().copied().filter(|&u| u > v).collect();
+            self.extend_esu(&table, &mut gdv, &mut sub, ext, v);
+            sub.pop();
+        }
+        gdv
+    }
+    fn extend_esu(
+        &self,
+        table: &OrbitTable,
+        gdv: &mut [[u64; NUM_ORBITS]],
+        sub: &mut Vec<usize>,
+        mut ext: Vec<usize>,
+        root: usize,
+    ) {
+        if sub.len() >= 2 {
+            for (&u, &orbit) in sub.iter().zip(table.orbits(self, sub)) {
+                gdv[u][orbit] += 1;
+            }
+        }
+        if sub.len() == MAX_GRAPHLET {
+            return;
+        }
+        while let Some(w) = ext.pop() {
+            let mut next_ext = ext.clone();
+            for &u in &self.adjs[w] {
+                if u > root
+                    && !sub.contains(&u)
+                    && !next_ext.contains(&u)
+                    && sub.iter().all(|&s| self.adjs[s].binary_search(&u).is_err())
+                {
+                    next_ext.push(u);
+                }
+            }
+            sub.push(w);
+            self.extend_esu(table, gdv, sub, next_ext, root);
+            sub.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_orbit_table() {
+        let table = OrbitTable::new();
+        assert_eq!(table.num_orbits, NUM_ORBITS);
+        // path 0-1-2: ends are orbit 1, the middle is orbit 2
+        let p3 = 1 << table.index[3][&(0, 1)] | 1 << table.index[3][&(1, 2)];
+        assert_eq!(table.tables[3][p3], vec![1, 2, 1]);
+        assert_eq!(table.tables[3][0b111], vec![3, 3, 3]);
+        assert_eq!(table.tables[4][0b111111], vec![14; 4]);
+        assert_eq!(table.tables[5][(1 << 10) - 1], vec![72; 5]);
+        // triangle 2-3-4 with the tail 0-1-2 is G12 with orbits 24-27
+        let tadpole = [(0, 1), (1, 2), (2, 3), (2, 4), (3, 4)]
+            .iter()
+            .fold(0, |g, e| g | 1 << table.index[5][e]);
+        assert_eq!(table.tables[5][tadpole], vec![24, 27, 26, 25, 25]);
+    }
+
+    #[test]
+    fn test_graphlet_degree_vectors() {
+        let k4 = NormalUndiGraph::from_apollo(0);
+        for gdv in k4.graphlet_degree_vectors() {
+            let mut expected = [0u64; NUM_ORBITS];
+            expected[0] = 3;
+            expected[3] = 3;
+            expected[14] = 1;
+            assert_eq!(gdv, expected);
+        }
+        // star with three leaves: centre is orbit 7 of the claw
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        let gdv = star.graphlet_degree_vectors();
+        assert_eq!((gdv[0][0], gdv[0][2], gdv[0][7]), (3, 3, 1));
+        assert_eq!((gdv[1][0], gdv[1][1], gdv[1][6]), (1, 2, 1));
+        // wheel with hub 0: orbit 69 for the hub, 68 for the rim
+        let wheel = NormalUndiGraph::from_edges(
+            "wheel".to_string(),
+            5,
+            &[
+                (0, 1),
+                (0, 2),
+                (0, 3),
+                (0, 4),
+                (1, 2),
+                (2, 3),
+                (3, 4),
+                (4, 1),
+            ],
+        );
+        let gdv = wheel.graphlet_degree_vectors();
+        assert_eq!(
+            (gdv[0][69], gdv[0][68], gdv[1][68], gdv[1][69]),
+            (1, 0, 1, 0)
+        );
+        let g = NormalUndiGraph::from_koch(2);
+        let gdv = g.graphlet_degree_vectors();
+        for (u, row) in gdv.iter().enumerate() {
+            assert_eq!(row[0] as usize, g.adjs[u].len());
+        }
+    }
+}
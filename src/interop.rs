@@ -0,0 +1,67 @@
+use petgraph::graph::{NodeIndex, UnGraph};
+
+use super::normal_graph::NormalUndiGraph;
+
+impl NormalUndiGraph {
+    /// Copy as a petgraph `UnGraph` whose node `NodeIndex::new(u)` is node
+    /// `u`, with each undirected edge added once.
+    pub fn to_petgraph(&self) -> UnGraph<(), ()> {
+        let mut g = UnGraph::with_capacity(self.n, self.m);
+        for _ in 0..self.n {
+            g.add_node(());
+        }
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &v in adj.iter().filter(|&&v| u < v) {
+                g.add_edge(NodeIndex::new(u), NodeIndex::new(v), ());
+            }
+        }
+        g
+    }
+    /// Reads the structure of a petgraph `UnGraph`, numbering nodes by their
+    /// index and ignoring weights. Self-loops are dropped and parallel edges
+    /// merged.
+    pub fn from_petgraph<N, E>(name: String, g: &UnGraph<N, E>) -> Self {
+        let edges: Vec<(usize, usize)> = g
+            .raw_edges()
+            .iter()
+            .map(|e| (e.source().index(), e.target().index()))
+            .collect();
+        Self::from_edges(name, g.node_count(), &edges)
+    }
+}
+
+impl From<&NormalUndiGraph> for UnGraph<(), ()> {
+    fn from(g: &NormalUndiGraph) -> Self {
+        g.to_petgraph()
+    }
+}
+
+impl<N, E> From<&UnGraph<N, E>> for NormalUndiGraph {
+    fn from(g: &UnGraph<N, E>) -> Self {
+        NormalUndiGraph::from_petgraph(String::new(), g)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_petgraph_round_trip() {
+        let g = NormalUndiGraph::from_koch(2);
+        let pg = UnGraph::from(&g);
+        assert_eq!((pg.node_count(), pg.edge_count()), (g.n, g.m));
+        assert_eq!(petgraph::algo::connected_components(&pg), 1);
+        let back = NormalUndiGraph::from_petgraph(g.name.clone(), &pg);
+        assert_eq!((back.n, back.m), (g.n, g.m));
+        assert_eq!(back.adjs, g.adjs);
+
+        let mut multi: UnGraph<&str, f64> = UnGraph::new_undirected();
+        let (a, b) = (multi.add_node("a"), multi.add_node("b"));
+        multi.add_edge(a, b, 1.0);
+        multi.add_edge(b, a, 2.0);
+        multi.add_edge(a, a, 3.0);
+        let simple = NormalUndiGraph::from(&multi);
+        assert_eq!((simple.n, simple.m), (2, 1));
+    }
+}
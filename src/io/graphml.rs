@@ -0,0 +1,314 @@
+// GraphML import and export, e.g. for Gephi or yEd. Node ids are written
+// as the numeric ids of the graph and edge weights as the `weight` key.
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::error::GraphError;
+use crate::general_graph::GeneralUndiGraph;
+use crate::weighted_graph::WeightedUndiGraph;
+
+const WEIGHT_KEY: &str = "weight";
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn write_open<W: Write>(w: &mut W, name: &str, weighted: bool) -> io::Result<()> {
+    writeln!(w, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(
+        w,
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">"
+    )?;
+    if weighted {
+        writeln!(
+            w,
+            "  <key id=\"{0}\" for=\"edge\" attr.name=\"{0}\" attr.type=\"double\"/>",
+            WEIGHT_KEY
+        )?;
+    }
+    writeln!(
+        w,
+        "  <graph id=\"{}\" edgedefault=\"undirected\">",
+        escape(name)
+    )
+}
+
+fn write_close<W: Write>(w: &mut W) -> io::Result<()> {
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")
+}
+
+type WeightedEdge = (usize, usize, Option<f64>);
+
+// The parts of a GraphML document kept by the readers, ids as written.
+struct Document {
+    name: String,
+    nodes: Vec<String>,
+    edges: Vec<(String, String, Option<f64>)>,
+}
+
+impl Document {
+    // Numeric ids are kept; otherwise every node is numbered by first
+    // appearance, declared nodes before edge endpoints.
+    fn node_ids<'a>(&'a self) -> (Vec<usize>, Vec<WeightedEdge>) {
+        let numeric = self
+            .nodes
+            .iter()
+            .chain(self.edges.iter().flat_map(|(s, t, _)| [s, t]))
+            .all(|id| id.parse::<usize>().is_ok());
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        let mut id_of = |id: &'a str| -> usize {
+            if numeric {
+                return id.parse().unwrap();
+            }
+            let next = index.len();
+            *index.entry(id).or_insert(next)
+        };
+        let nodes = self.nodes.iter().map(|id| id_of(id)).collect();
+        let edges = self
+            .edges
+            .iter()
+            .map(|(s, t, w)| (id_of(s), id_of(t), *w))
+            .collect();
+        (nodes, edges)
+    }
+    // Nodes numbered densely from 0, numeric ids by increasing value and
+    // other ids by first appearance, with the id written for every number.
+    fn dense_ids(&self) -> (Vec<String>, Vec<WeightedEdge>) {
+        let (nodes, edges) = self.node_ids();
+        let written = self
+            .nodes
+            .iter()
+            .chain(self.edges.iter().flat_map(|(s, t, _)| [s, t]));
+        let numbers = nodes
+            .iter()
+            .copied()
+            .chain(edges.iter().flat_map(|&(u, v, _)| [u, v]));
+        let mut ids: Vec<(usize, &String)> = numbers.zip(written).collect();
+        ids.sort_unstable_by_key(|&(u, _)| u);
+        ids.dedup_by_key(|&mut (u, _)| u);
+        let dense = |u: usize| ids.binary_search_by_key(&u, |&(x, _)| x).unwrap();
+        let edges = edges
+            .iter()
+            .map(|&(u, v, w)| (dense(u), dense(v), w))
+            .collect();
+        (ids.iter().map(|(_, id)| id.to_string()).collect(), edges)
+    }
+}
+
+fn parse<R: Read>(r: R) -> Result<Document, GraphError> {
+    let malformed = |what: String| GraphError::Other(format!("Malformed GraphML: {}", what));
+    let mut doc = Document {
+        name: "graph".to_string(),
+        nodes: Vec::new(),
+        edges: Vec::new(),
+    };
+    let mut weight_key: Option<String> = None;
+    // set while inside `<data>` of the weight key of the current edge
+    let mut in_weight = false;
+    for event in EventReader::new(r) {
+        match event.map_err(|e| malformed(e.to_string()))? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                let attr = |key: &str| {
+                    attributes
+                        .iter()
+                        .find(|a| a.name.local_name == key)
+                        .map(|a| a.value.clone())
+                };
+                match name.local_name.as_str() {
+                    "key" => {
+                        let is_weight = attr("attr.name").as_deref() == Some(WEIGHT_KEY)
+                            || attr("id").as_deref() == Some(WEIGHT_KEY);
+                        if is_weight && attr("for").as_deref() != Some("node") {
+                            weight_key = attr("id");
+                        }
+                    }
+                    "graph" => {
+                        if let Some(id) = attr("id") {
+                            doc.name = id;
+                        }
+                    }
+                    "node" => doc
+                        .nodes
+                        .push(attr("id").ok_or_else(|| malformed("node without id".to_string()))?),
+                    "edge" => {
+                        let (Some(s), Some(t)) = (attr("source"), attr("target")) else {
+                            return Err(malformed("edge without source or target".to_string()));
+                        };
+                        doc.edges.push((s, t, None));
+                    }
+                    "data" => {
+                        in_weight = weight_key.is_some() && attr("key") == weight_key;
+                    }
+                    _ => {}
+                }
+            }
+            XmlEvent::Characters(text) if in_weight => {
+                let w: f64 = text
+                    .trim()
+                    .parse()
+                    .map_err(|_| malformed(format!("bad weight '{}'", text.trim())))?;
+                if let Some(edge) = doc.edges.last_mut() {
+                    edge.2 = Some(w);
+                }
+            }
+            XmlEvent::EndElement { name } if name.local_name == "data" => in_weight = false,
+            _ => {}
+        }
+    }
+    Ok(doc)
+}
+
+impl GeneralUndiGraph {
+    /// Writes the graph as GraphML with its node ids, isolated nodes
+    /// included.
+    pub fn to_graphml<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_open(w, &self.name, false)?;
+        let mut nodes: Vec<&usize> = self.nodes.iter().collect();
+        nodes.sort_unstable();
+        for u in nodes {
+            writeln!(w, "    <node id=\"{}\"/>", u)?;
+        }
+        let mut edges: Vec<&(usize, usize)> = self.edges.iter().collect();
+        edges.sort_unstable();
+        for (u, v) in edges {
+            writeln!(w, "    <edge source=\"{}\" target=\"{}\"/>", u, v)?;
+        }
+        write_close(w)
+    }
+    /// Reads a GraphML graph named after its `<graph id>`. Numeric node ids
+    /// are kept, other ids are numbered by first appearance. Directed edges
+    /// are read as undirected and weights are ignored.
+    pub fn from_graphml<R: Read>(r: R) -> Result<Self, GraphError> {
+        let doc = parse(r)?;
+        let (nodes, edges) = doc.node_ids();
+        let mut g = Self::new(doc.name);
+        g.nodes.extend(nodes);
+        for (u, v, _) in edges {
+            g.nodes.insert(u);
+            g.nodes.insert(v);
+            g.add_edge(u, v);
+        }
+        Ok(g)
+    }
+}
+
+impl WeightedUndiGraph {
+    /// Writes the graph as GraphML with nodes `0..n` and a `weight` key.
+    pub fn to_graphml<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write_open(w, &self.name, true)?;
+        for u in 0..self.n {
+            writeln!(w, "    <node id=\"{}\"/>", u)?;
+        }
+        for (u, adj) in self.adjs.iter().enumerate() {
+            let i = adj.partition_point(|&(v, _)| v <= u);
+            for (v, weight) in &adj[i..] {
+                writeln!(
+                    w,
+                    "    <edge source=\"{}\" target=\"{}\"><data key=\"{}\">{}</data></edge>",
+                    u, v, WEIGHT_KEY, weight
+                )?;
+            }
+        }
+        write_close(w)
+    }
+    /// Reads a GraphML graph like `GeneralUndiGraph::from_graphml`, taking
+    /// edge weights from the key named `weight` (1 when absent) and summing
+    /// those of repeated edges. Nodes are numbered densely from 0, numeric
+    /// ids by increasing value and others by first appearance, see
+    /// `from_graphml_with_ids`.
+    pub fn from_graphml<R: Read>(r: R) -> Result<Self, GraphError> {
+        Ok(Self::from_graphml_with_ids(r)?.0)
+    }
+    /// `from_graphml` also returning the GraphML id of every node.
+    pub fn from_graphml_with_ids<R: Read>(r: R) -> Result<(Self, Vec<String>), GraphError> {
+        let doc = parse(r)?;
+        let (ids, edges) = doc.dense_ids();
+        let mut g = Self::new(doc.name);
+        g.n = ids.len();
+        g.adjs.resize_with(g.n, Vec::new);
+        for (u, v, w) in edges {
+            g.add_edge(u, v, w.unwrap_or(1.0));
+        }
+        Ok((g, ids))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphml_round_trip() {
+        let mut g = GeneralUndiGraph::new("a<b".to_string());
+        for (u, v) in [(10, 20), (20, 30), (30, 10)] {
+            g.add_edge(u, v);
+        }
+        g.nodes.insert(99);
+        let mut buf = Vec::new();
+        g.to_graphml(&mut buf).unwrap();
+        let read = GeneralUndiGraph::from_graphml(buf.as_slice()).unwrap();
+        assert_eq!(read.name, "a<b");
+        assert_eq!(read.nodes, g.nodes);
+        assert_eq!(read.edges, g.edges);
+
+        let mut w = WeightedUndiGraph::new("w".to_string());
+        w.add_edge(0, 1, 2.5);
+        w.add_edge(1, 2, 0.5);
+        let mut buf = Vec::new();
+        w.to_graphml(&mut buf).unwrap();
+        let read = WeightedUndiGraph::from_graphml(buf.as_slice()).unwrap();
+        assert_eq!((read.n, read.m), (3, 2));
+        assert_eq!(read.adjs, w.adjs);
+    }
+
+    #[test]
+    fn test_graphml_foreign() {
+        // as exported by other tools: string ids, a differently named weight key
+        let text = r#"<?xml version="1.0"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="d0" for="node" attr.name="label" attr.type="string"/>
+  <key id="d1" for="edge" attr.name="weight" attr.type="double"/>
+  <graph id="G" edgedefault="directed">
+    <node id="n0"><data key="d0">first</data></node>
+    <node id="n1"/>
+    <node id="n2"/>
+    <edge source="n0" target="n1"><data key="d1">3</data></edge>
+    <edge source="n2" target="n1"/>
+  </graph>
+</graphml>"#;
+        let w = WeightedUndiGraph::from_graphml(text.as_bytes()).unwrap();
+        assert_eq!(w.name, "G");
+        assert_eq!(w.adjs[1], vec![(0, 3.0), (2, 1.0)]);
+        let g = GeneralUndiGraph::from_graphml(text.as_bytes()).unwrap();
+        assert_eq!((g.num_nodes(), g.num_edges()), (3, 2));
+        assert!(GeneralUndiGraph::from_graphml("<graphml><edge/>".as_bytes()).is_err());
+
+        // sparse numeric ids are compacted in increasing order
+        let text = r#"<graphml><graph>
+    <node id="18446744073709551615"/>
+    <edge source="1000000000" target="18446744073709551615"/>
+    <edge source="7" target="1000000000"/>
+  </graph></graphml>"#;
+        let (w, ids) = WeightedUndiGraph::from_graphml_with_ids(text.as_bytes()).unwrap();
+        assert_eq!(ids, ["7", "1000000000", "18446744073709551615"]);
+        assert_eq!((w.n, w.m), (3, 2));
+        assert_eq!(w.adjs[1], vec![(0, 1.0), (2, 1.0)]);
+    }
+}
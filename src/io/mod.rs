@@ -0,0 +1,560 @@
+pub mod graphml;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Lines, Read};
+use std::path::Path;
+
+use super::canonical::{edge_checksum, Header, HEADER_PREFIX};
+use super::config::ComputeConfig;
+use super::error::GraphError;
+use super::general_graph::{GeneralUndiGraph, CHECK_INTERVAL};
+use super::weighted_graph::WeightedUndiGraph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphFormat {
+    // whitespace-separated `u v` lines with `#` comments, e.g. `.txt` or `.edges`
+    EdgeList,
+    // KONECT `out.*` files: `%` comments and optional weight/time columns
+    Konect,
+    // Matrix Market `.mtx` coordinate files: a `%%MatrixMarket` header, a
+    // `rows cols entries` size line and 1-based `i j [value]` entries
+    MatrixMarket,
+}
+
+impl GraphFormat {
+    /// Whether a file name looks like this format.
+    pub fn matches(&self, path: &Path) -> bool {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            return false;
+        };
+        match self {
+            GraphFormat::EdgeList => ["txt", "edges", "el", "tsv"]
+                .iter()
+                .any(|ext| path.extension().and_then(|e| e.to_str()) == Some(ext)),
+            GraphFormat::Konect => file_name.starts_with("out."),
+            GraphFormat::MatrixMarket => path.extension().and_then(|e| e.to_str()) == Some("mtx"),
+        }
+    }
+    fn is_comment(&self, line: &str) -> bool {
+        let line = line.trim_start();
+        line.is_empty()
+            || match self {
+                GraphFormat::EdgeList => line.starts_with('#') || line.starts_with('%'),
+                GraphFormat::Konect | GraphFormat::MatrixMarket => line.starts_with('%'),
+            }
+    }
+    // whether the first data line gives sizes rather than an edge
+    fn has_size_line(&self) -> bool {
+        *self == GraphFormat::MatrixMarket
+    }
+    // converts a node id as written in the file to a 0-based one
+    fn node_id(&self, x: usize) -> Option<usize> {
+        match self {
+            GraphFormat::MatrixMarket => x.checked_sub(1),
+            _ => Some(x),
+        }
+    }
+    // rejects Matrix Market files other than sparse coordinate matrices
+    fn check_header(&self, name: &str, line: &str) -> Result<(), GraphError> {
+        if *self == GraphFormat::MatrixMarket {
+            parse_mtx_header(name, line)?;
+        }
+        Ok(())
+    }
+}
+
+/// How `WeightedUndiGraph::read_matrix_market` treats a `general` matrix,
+/// which may hold different entries at `(i, j)` and `(j, i)`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Asymmetry {
+    // weight `(a_ij + a_ji) / 2` on the edge `{i, j}`
+    #[default]
+    Symmetrize,
+    // fail on the first entry without an equal mirrored entry
+    Reject,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MtxField {
+    Real,
+    Integer,
+    Pattern,
+}
+
+// field and whether only one triangle is stored, from the banner line
+fn parse_mtx_header(name: &str, line: &str) -> Result<(MtxField, bool), GraphError> {
+    let header = line.to_lowercase();
+    let tokens: Vec<&str> = header.split_whitespace().collect();
+    let unsupported = |what: &str| {
+        Err(GraphError::Other(format!(
+            "'{}' is not supported: {}: {}",
+            name, what, line
+        )))
+    };
+    if tokens.len() != 5 || tokens[0] != "%%matrixmarket" || tokens[1] != "matrix" {
+        return unsupported("not a Matrix Market matrix");
+    }
+    if tokens[2] != "coordinate" {
+        return unsupported("only the coordinate format is");
+    }
+    let field = match tokens[3] {
+        "real" => MtxField::Real,
+        "integer" => MtxField::Integer,
+        "pattern" => MtxField::Pattern,
+        "complex" => return unsupported("complex entries have no edge weight"),
+        _ => return unsupported("unknown field"),
+    };
+    let symmetric = match tokens[4] {
+        "general" => false,
+        "symmetric" => true,
+        "skew-symmetric" | "hermitian" => {
+            return unsupported("skew-symmetric and hermitian matrices are not graphs")
+        }
+        _ => return unsupported("unknown symmetry"),
+    };
+    Ok((field, symmetric))
+}
+
+// `i` is the 0-based line index
+fn malformed(name: &str, i: usize, line: &str) -> GraphError {
+    GraphError::Parse {
+        name: name.to_string(),
+        line: i + 1,
+        content: line.to_string(),
+    }
+}
+
+// Calls `f` on every edge of an unweighted edge list, with the progress and
+// cancellation handling of `GeneralUndiGraph::read_with`. Returns the
+// `canonical::Header` of an edge list written by `write_canonical`.
+pub(crate) fn parse_edges<R, F>(
+    name: &str,
+    r: R,
+    format: GraphFormat,
+    config: &ComputeConfig,
+    mut f: F,
+) -> Result<Option<Header>, GraphError>
+where
+    R: Read,
+    F: FnMut(usize, usize) -> Result<(), GraphError>,
+{
+    let mut size_line = format.has_size_line();
+    let mut header: Option<Header> = None;
+    for (i, line) in BufReader::new(r).lines().enumerate() {
+        if i % CHECK_INTERVAL == 0 {
+            config.check_cancelled(&format!("Parsing '{}'", name))?;
+            config.report("parse", i as u64, None);
+        }
+        let line = line.map_err(|e| {
+            GraphError::io(format!("Failed to read line {} of '{}'", i + 1, name), e)
+        })?;
+        if i == 0 {
+            format.check_header(name, &line)?;
+        }
+        if format == GraphFormat::EdgeList && line.starts_with(HEADER_PREFIX) {
+            header
+                .get_or_insert_with(Header::default)
+                .parse_line(&line)?;
+            continue;
+        }
+        if format.is_comment(&line) {
+            continue;
+        }
+        if size_line {
+            size_line = false;
+            continue;
+        }
+        let mut split = line.split_whitespace();
+        let mut next = || -> Result<usize, GraphError> {
+            split
+                .next()
+                .and_then(|x| x.parse().ok())
+                .and_then(|x| format.node_id(x))
+                .ok_or_else(|| malformed(name, i, &line))
+        };
+        let (u, v) = (next()?, next()?);
+        f(u, v)?;
+    }
+    Ok(header)
+}
+
+impl GeneralUndiGraph {
+    /// Parses an edge list, reporting the first malformed line as an error.
+    pub fn read<R: Read>(name: &str, r: R, format: GraphFormat) -> Result<Self, GraphError> {
+        Self::read_with(name, r, format, &ComputeConfig::serial())
+    }
+    /// `read` reporting the lines parsed so far and checking the
+    /// cancellation token of `config` every `CHECK_INTERVAL` lines.
+    pub fn read_with<R: Read>(
+        name: &str,
+        r: R,
+        format: GraphFormat,
+        config: &ComputeConfig,
+    ) -> Result<Self, GraphError> {
+        let mut g = Self::new(name.to_string());
+        let header = parse_edges(name, r, format, config, |u, v| {
+            g.add_edge(u, v);
+            Ok(())
+        })?;
+        if let Some(header) = header {
+            let checksum = edge_checksum(g.edges.iter().copied());
+            header.verify(name, g.num_nodes(), g.num_edges(), checksum)?;
+        }
+        Ok(g)
+    }
+    pub fn read_path<P: AsRef<Path>>(path: P, format: GraphFormat) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("graph")
+            .to_string();
+        let f = File::open(path)
+            .map_err(|e| GraphError::io(format!("Failed to open '{}'", path.display()), e))?;
+        Self::read(&name, f, format)
+    }
+}
+
+impl WeightedUndiGraph {
+    /// Parses a weighted edge list, taking weights from the third column
+    /// (1 when absent) and summing those of repeated edges. Nodes are
+    /// numbered by increasing id in the file. Matrix Market files go through
+    /// `read_matrix_market` with the default `Asymmetry`.
+    pub fn read<R: Read>(name: &str, r: R, format: GraphFormat) -> Result<Self, GraphError> {
+        if format == GraphFormat::MatrixMarket {
+            return Self::read_matrix_market(name, r, Asymmetry::default());
+        }
+        let mut edges: Vec<(usize, usize, f64)> = Vec::new();
+        for (i, line) in BufReader::new(r).lines().enumerate() {
+            let line = line.map_err(|e| {
+                GraphError::io(format!("Failed to read line {} of '{}'", i + 1, name), e)
+            })?;
+            if format.is_comment(&line) {
+                continue;
+            }
+            let mut split = line.split_whitespace();
+            let mut next = || -> Result<usize, GraphError> {
+                split
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| malformed(name, i, &line))
+            };
+            let (u, v) = (next()?, next()?);
+            let w = match split.next() {
+                Some(x) => x.parse().map_err(|_| malformed(name, i, &line))?,
+                None => 1.0,
+            };
+            edges.push((u, v, w));
+        }
+        let mut ids: Vec<usize> = edges.iter().flat_map(|&(u, v, _)| [u, v]).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let id = |u: usize| ids.binary_search(&u).unwrap();
+        let edges: Vec<(usize, usize, f64)> =
+            edges.iter().map(|&(u, v, w)| (id(u), id(v), w)).collect();
+        Ok(Self::from_edges(name.to_string(), ids.len(), &edges))
+    }
+    pub fn read_path<P: AsRef<Path>>(path: P, format: GraphFormat) -> Result<Self, GraphError> {
+        let path = path.as_ref();
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("graph")
+            .to_string();
+        let f = File::open(path)
+            .map_err(|e| GraphError::io(format!("Failed to open '{}'", path.display()), e))?;
+        Self::read(&name, f, format)
+    }
+    /// Parses a square Matrix Market coordinate matrix as a graph on its
+    /// rows, keeping 1-based row `i` as node `i - 1` and dropping the
+    /// diagonal. `real` and `integer` entries become weights and `pattern`
+    /// entries weigh 1. A `symmetric` file stores each edge once; a
+    /// `general` one is handled according to `asymmetry`.
+    pub fn read_matrix_market<R: Read>(
+        name: &str,
+        r: R,
+        asymmetry: Asymmetry,
+    ) -> Result<Self, GraphError> {
+        let mut lines = BufReader::new(r).lines().enumerate();
+        let mut next_line = || -> Result<Option<(usize, String)>, GraphError> {
+            for (i, line) in lines.by_ref() {
+                let line = line.map_err(|e| {
+                    GraphError::io(format!("Failed to read line {} of '{}'", i + 1, name), e)
+                })?;
+                if i == 0 || !GraphFormat::MatrixMarket.is_comment(&line) {
+                    return Ok(Some((i, line)));
+                }
+            }
+            Ok(None)
+        };
+        let (_, banner) = next_line()?.unwrap_or_default();
+        let (field, symmetric) = parse_mtx_header(name, &banner)?;
+        let Some((i, size)) = next_line()? else {
+            return Err(GraphError::Other(format!("'{}' has no size line", name)));
+        };
+        let dims: Vec<usize> = size
+            .split_whitespace()
+            .map(|x| x.parse().map_err(|_| malformed(name, i, &size)))
+            .collect::<Result<_, _>>()?;
+        let n = match dims[..] {
+            [rows, cols, _] if rows == cols => rows,
+            [_, _, _] => {
+                return Err(GraphError::Other(format!(
+                    "'{}' is not square: {}",
+                    name, size
+                )))
+            }
+            _ => return Err(malformed(name, i, &size)),
+        };
+        let mut entries: HashMap<(usize, usize), f64> = HashMap::new();
+        while let Some((i, line)) = next_line()? {
+            let mut split = line.split_whitespace();
+            let mut next = || -> Result<usize, GraphError> {
+                split
+                    .next()
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .filter(|&x| 1 <= x && x <= n)
+                    .map(|x| x - 1)
+                    .ok_or_else(|| malformed(name, i, &line))
+            };
+            let (u, v) = (next()?, next()?);
+            let w = match (field, split.next()) {
+                (MtxField::Pattern, _) => 1.0,
+                (MtxField::Integer, Some(x)) => {
+                    x.parse::<i64>().map_err(|_| malformed(name, i, &line))? as f64
+                }
+                (MtxField::Real, Some(x)) => x.parse().map_err(|_| malformed(name, i, &line))?,
+                (_, None) => return Err(malformed(name, i, &line)),
+            };
+            if u != v {
+                *entries.entry((u, v)).or_default() += w;
+            }
+        }
+        let mut edges: Vec<(usize, usize, f64)> = Vec::with_capacity(entries.len());
+        for (&(u, v), &w) in &entries {
+            if symmetric {
+                edges.push((u, v, w));
+                continue;
+            }
+            let mirror = entries.get(&(v, u)).copied();
+            if asymmetry == Asymmetry::Reject && mirror != Some(w) {
+                return Err(GraphError::Other(format!(
+                    "'{}' is not symmetric at entry ({}, {})",
+                    name,
+                    u + 1,
+                    v + 1
+                )));
+            }
+            // each mirrored pair is emitted once, from its upper entry
+            if u < v || mirror.is_none() {
+                edges.push((u, v, (w + mirror.unwrap_or(0.0)) / 2.0));
+            }
+        }
+        edges.sort_unstable_by_key(|&(u, v, _)| (u, v));
+        Ok(Self::from_edges(name.to_string(), n, &edges))
+    }
+}
+
+/// Edges and nodes present in only one of two edge lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphDiff {
+    // edges as `(u, v)` with `u < v`, in increasing order
+    pub added_edges: Vec<(usize, usize)>,
+    pub removed_edges: Vec<(usize, usize)>,
+    pub added_nodes: Vec<usize>,
+    pub removed_nodes: Vec<usize>,
+}
+
+// Edges of a sorted edge list read one at a time, normalised to `u < v`.
+struct SortedEdges {
+    name: String,
+    lines: Lines<BufReader<File>>,
+    line_no: usize,
+    last: Option<(usize, usize)>,
+}
+
+impl SortedEdges {
+    fn open(path: &Path) -> Result<Self, GraphError> {
+        let f = File::open(path)
+            .map_err(|e| GraphError::io(format!("Failed to open '{}'", path.display()), e))?;
+        Ok(Self {
+            name: path.display().to_string(),
+            lines: BufReader::new(f).lines(),
+            line_no: 0,
+            last: None,
+        })
+    }
+    // next edge, skipping comments, self-loops and repeats of the last edge
+    fn next_edge(&mut self) -> Result<Option<(usize, usize)>, GraphError> {
+        for line in self.lines.by_ref() {
+            let i = self.line_no;
+            self.line_no += 1;
+            let line = line.map_err(|e| {
+                GraphError::io(
+                    format!("Failed to read line {} of '{}'", i + 1, self.name),
+                    e,
+                )
+            })?;
+            if GraphFormat::EdgeList.is_comment(&line) {
+                continue;
+            }
+            let mut split = line.split_whitespace();
+            let mut next = || -> Result<usize, GraphError> {
+                split
+                    .next()
+                    .and_then(|x| x.parse().ok())
+                    .ok_or_else(|| malformed(&self.name, i, &line))
+            };
+            let (u, v) = (next()?, next()?);
+            let edge = (u.min(v), u.max(v));
+            if u == v || Some(edge) == self.last {
+                continue;
+            }
+            if Some(edge) < self.last {
+                return Err(GraphError::Other(format!(
+                    "Line {} of '{}' is out of order: {}",
+                    i + 1,
+                    self.name,
+                    line
+                )));
+            }
+            self.last = Some(edge);
+            return Ok(Some(edge));
+        }
+        Ok(None)
+    }
+}
+
+/// Compares two edge lists sorted by `(min(u, v), max(u, v))`, streaming
+/// both so that memory grows with the number of nodes and of differing
+/// edges, never with the edges the files share.
+pub fn diff_files<P: AsRef<Path>, Q: AsRef<Path>>(a: P, b: Q) -> Result<GraphDiff, GraphError> {
+    let (mut ra, mut rb) = (
+        SortedEdges::open(a.as_ref())?,
+        SortedEdges::open(b.as_ref())?,
+    );
+    let (mut nodes_a, mut nodes_b) = (HashSet::new(), HashSet::new());
+    let mut diff = GraphDiff::default();
+    let (mut ea, mut eb) = (ra.next_edge()?, rb.next_edge()?);
+    loop {
+        let order = match (ea, eb) {
+            (None, None) => break,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(x), Some(y)) => x.cmp(&y),
+        };
+        if order != Ordering::Greater {
+            let (u, v) = ea.unwrap();
+            nodes_a.extend([u, v]);
+            if order == Ordering::Less {
+                diff.removed_edges.push((u, v));
+            }
+            ea = ra.next_edge()?;
+        }
+        if order != Ordering::Less {
+            let (u, v) = eb.unwrap();
+            nodes_b.extend([u, v]);
+            if order == Ordering::Greater {
+                diff.added_edges.push((u, v));
+            }
+            eb = rb.next_edge()?;
+        }
+    }
+    diff.added_nodes = nodes_b.difference(&nodes_a).copied().collect();
+    diff.removed_nodes = nodes_a.difference(&nodes_b).copied().collect();
+    diff.added_nodes.sort_unstable();
+    diff.removed_nodes.sort_unstable();
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        let text = "% sym unweighted\n% 3 2 3\n1 2 1 100\n2 3 1 200\n";
+        let g = GeneralUndiGraph::read("k", text.as_bytes(), GraphFormat::Konect).unwrap();
+        assert_eq!((g.num_nodes(), g.num_edges()), (3, 2));
+        let text = "# comment\n0\t1\n\n1 2\n";
+        let g = GeneralUndiGraph::read("e", text.as_bytes(), GraphFormat::EdgeList).unwrap();
+        assert_eq!(g.num_edges(), 2);
+        match GeneralUndiGraph::read("bad", "0 x\n".as_bytes(), GraphFormat::EdgeList) {
+            Err(GraphError::Parse { line, .. }) => assert_eq!(line, 1),
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("malformed line accepted"),
+        }
+        let text = "% sym positive\n10 20 2.5\n20 30 1\n20 10 0.5\n30 40\n";
+        let w = WeightedUndiGraph::read("w", text.as_bytes(), GraphFormat::Konect).unwrap();
+        assert_eq!((w.n, w.m), (4, 3));
+        assert_eq!(w.weight(0, 1), Some(3.0));
+        assert_eq!(w.weight(2, 3), Some(1.0));
+        assert!(WeightedUndiGraph::read("w", "0 1 x\n".as_bytes(), GraphFormat::Konect).is_err());
+        assert!(GraphFormat::Konect.matches(Path::new("dir/out.subelj_euroroad")));
+        assert!(GraphFormat::EdgeList.matches(Path::new("a.edges")));
+        assert!(!GraphFormat::EdgeList.matches(Path::new("a.json")));
+    }
+
+    #[test]
+    fn test_read_matrix_market() {
+        let text = "%%MatrixMarket matrix coordinate pattern symmetric\n% comment\n\
+                    4 4 3\n2 1\n3 2\n4 1\n";
+        let g = GeneralUndiGraph::read("m", text.as_bytes(), GraphFormat::MatrixMarket).unwrap();
+        let mut edges: Vec<(usize, usize)> = g.edges.iter().copied().collect();
+        edges.sort_unstable();
+        assert_eq!(edges, vec![(0, 1), (0, 3), (1, 2)]);
+        let text = "%%MatrixMarket matrix coordinate real symmetric\n3 3 2\n2 1 0.5\n3 1 2\n";
+        let w = WeightedUndiGraph::read("w", text.as_bytes(), GraphFormat::MatrixMarket).unwrap();
+        assert_eq!(w.weight(0, 2), Some(2.0));
+        let dense = "%%MatrixMarket matrix array real general\n2 2\n1\n0\n0\n1\n";
+        assert!(GeneralUndiGraph::read("d", dense.as_bytes(), GraphFormat::MatrixMarket).is_err());
+        let zero = "%%MatrixMarket matrix coordinate pattern general\n2 2 1\n0 1\n";
+        assert!(GeneralUndiGraph::read("z", zero.as_bytes(), GraphFormat::MatrixMarket).is_err());
+        assert!(GraphFormat::MatrixMarket.matches(Path::new("bio-celegans.mtx")));
+    }
+
+    #[test]
+    fn test_matrix_market_symmetry() {
+        let general = "%%MatrixMarket matrix coordinate integer general\n\
+                       3 3 4\n1 2 4\n2 1 2\n2 3 5\n3 3 9\n";
+        let w =
+            WeightedUndiGraph::read_matrix_market("g", general.as_bytes(), Asymmetry::Symmetrize)
+                .unwrap();
+        assert_eq!((w.n, w.m), (3, 2));
+        assert_eq!(w.weight(0, 1), Some(3.0));
+        assert_eq!(w.weight(1, 2), Some(2.5));
+        assert!(
+            WeightedUndiGraph::read_matrix_market("g", general.as_bytes(), Asymmetry::Reject)
+                .is_err()
+        );
+        let mirrored = "%%MatrixMarket matrix coordinate real general\n2 2 2\n1 2 1.5\n2 1 1.5\n";
+        let w = WeightedUndiGraph::read_matrix_market("m", mirrored.as_bytes(), Asymmetry::Reject)
+            .unwrap();
+        assert_eq!(w.weight(0, 1), Some(1.5));
+        let complex = "%%MatrixMarket matrix coordinate complex hermitian\n2 2 1\n2 1 1 1\n";
+        match WeightedUndiGraph::read("c", complex.as_bytes(), GraphFormat::MatrixMarket) {
+            Err(e) => assert!(e.to_string().contains("complex")),
+            Ok(_) => panic!("complex matrix accepted"),
+        }
+        let wide = "%%MatrixMarket matrix coordinate pattern general\n2 3 1\n1 3\n";
+        assert!(WeightedUndiGraph::read("r", wide.as_bytes(), GraphFormat::MatrixMarket).is_err());
+    }
+
+    #[test]
+    fn test_diff_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, b) = (dir.path().join("a.txt"), dir.path().join("b.txt"));
+        std::fs::write(&a, "# v1\n0 1\n2 1\n1 2\n1 3\n3 4\n").unwrap();
+        std::fs::write(&b, "0 1\n1 3\n3 5\n5 6\n").unwrap();
+        let diff = diff_files(&a, &b).unwrap();
+        assert_eq!(diff.added_edges, vec![(3, 5), (5, 6)]);
+        assert_eq!(diff.removed_edges, vec![(1, 2), (3, 4)]);
+        assert_eq!(diff.added_nodes, vec![5, 6]);
+        assert_eq!(diff.removed_nodes, vec![2, 4]);
+        assert_eq!(diff_files(&a, &a).unwrap(), GraphDiff::default());
+        std::fs::write(&b, "1 3\n0 1\n").unwrap();
+        assert!(diff_files(&a, &b).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::general_graph::GeneralUndiGraph;
+
+/// A `GeneralUndiGraph` whose edge insertions and removals are journaled to
+/// disk as `+ u v` / `- u v` lines, so an interrupted construction job can
+/// reopen the journal and resume where it stopped.
+pub struct AppendLog {
+    graph: GeneralUndiGraph,
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+// Applies every complete line of `text` to `g` and returns the length of the
+// prefix covered by complete lines; a torn final line from a crash is ignored.
+fn replay_into(g: &mut GeneralUndiGraph, text: &str) -> Result<usize, String> {
+    let complete = text.rfind('\n').map_or(0, |i| i + 1);
+    for (i, line) in text[..complete].lines().enumerate() {
+        let malformed = || format!("Malformed journal line {}: {}", i + 1, line);
+        let mut split = line.split_whitespace();
+        let op = split.next().ok_or_else(malformed)?;
+        let mut next = || -> Result<usize, String> {
+            split
+                .next()
+                .and_then(|x| x.parse().ok())
+                .ok_or_else(malformed)
+        };
+        let (u, v) = (next()?, next()?);
+        match op {
+            "+" => g.add_edge(u, v),
+            "-" => {
+                g.remove_edge(u, v);
+            }
+            _ => return Err(malformed()),
+        }
+    }
+    Ok(complete)
+}
+
+impl AppendLog {
+    /// Opens the journal at `path`, replaying any existing entries.
+    pub fn open<P: AsRef<Path>>(name: &str, path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let mut f = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)
+            .or(Err(format!("Failed to open journal '{}'", path.display())))?;
+        let mut text = String::new();
+        f.read_to_string(&mut text)
+            .or(Err(format!("Failed to read journal '{}'", path.display())))?;
+        let mut graph = GeneralUndiGraph::new(name.to_string());
+        let complete = replay_into(&mut graph, &text)?;
+        if complete < text.len() {
+            f.set_len(complete as u64).or(Err(format!(
+                "Failed to truncate journal '{}'",
+                path.display()
+            )))?;
+        }
+        Ok(Self {
+            graph,
+            path,
+            writer: BufWriter::new(f),
+        })
+    }
+    /// Rebuilds the graph recorded in a journal without opening it for writing.
+    pub fn replay<P: AsRef<Path>>(name: &str, path: P) -> Result<GeneralUndiGraph, String> {
+        let path = path.as_ref();
+        let mut text = String::new();
+        File::open(path)
+            .and_then(|mut f| f.read_to_string(&mut text))
+            .or(Err(format!("Failed to read journal '{}'", path.display())))?;
+        let mut graph = GeneralUndiGraph::new(name.to_string());
+        replay_into(&mut graph, &text)?;
+        Ok(graph)
+    }
+    pub fn add_edge(&mut self, u: usize, v: usize) -> Result<(), String> {
+        self.append('+', u, v)?;
+        self.graph.add_edge(u, v);
+        Ok(())
+    }
+    pub fn remove_edge(&mut self, u: usize, v: usize) -> Result<bool, String> {
+        self.append('-', u, v)?;
+        Ok(self.graph.remove_edge(u, v))
+    }
+    fn append(&mut self, op: char, u: usize, v: usize) -> Result<(), String> {
+        writeln!(self.writer, "{} {} {}", op, u, v).or(Err(format!(
+            "Failed to append to journal '{}'",
+            self.path.display()
+        )))
+    }
+    /// Pushes buffered entries to the journal file.
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.writer.flush().or(Err(format!(
+            "Failed to flush journal '{}'",
+            self.path.display()
+        )))
+    }
+    pub fn graph(&self) -> &GeneralUndiGraph {
+        &self.graph
+    }
+    pub fn into_graph(mut self) -> Result<GeneralUndiGraph, String> {
+        self.flush()?;
+        Ok(self.graph)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("g.journal");
+        let mut log = AppendLog::open("g", &path).unwrap();
+        for (u, v) in [(0, 1), (1, 2), (2, 0), (2, 3)] {
+            log.add_edge(u, v).unwrap();
+        }
+        assert!(log.remove_edge(0, 2).unwrap());
+        assert!(!log.remove_edge(0, 3).unwrap());
+        log.flush().unwrap();
+        drop(log);
+
+        // simulate a crash in the middle of writing an entry
+        let mut f = OpenOptions::new().append(true).open(&path).unwrap();
+        write!(f, "+ 3 ").unwrap();
+        drop(f);
+
+        let mut log = AppendLog::open("g", &path).unwrap();
+        assert_eq!(log.graph().num_edges(), 3);
+        log.add_edge(3, 4).unwrap();
+        let g = log.into_graph().unwrap();
+        let replayed = AppendLog::replay("g", &path).unwrap();
+        assert_eq!(replayed.edges, g.edges);
+        assert_eq!(g.num_edges(), 4);
+        assert!(!g.edges.contains(&(0, 2)));
+    }
+
+    #[test]
+    fn test_malformed_journal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bad.journal");
+        std::fs::write(&path, "+ 0 1\n* 1 2\n").unwrap();
+        assert!(AppendLog::replay("bad", &path).is_err());
+        assert!(AppendLog::open("bad", &path).is_err());
+    }
+}
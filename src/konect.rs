@@ -0,0 +1,377 @@
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use super::config::ComputeConfig;
+use super::error::GraphError;
+use super::general_graph::{block_on, download_konect, entry_name, list_konect_entries};
+
+/// Where `GeneralUndiGraph::from_konect_cached` fetches KONECT archives from
+/// and keeps their extracted edge lists.
+#[derive(Clone, Debug, Default)]
+pub struct KonectOptions {
+    // None for `$XDG_CACHE_HOME/general_graph` or `~/.cache/general_graph`
+    pub cache_dir: Option<PathBuf>,
+    // download again even when a valid cached copy exists
+    pub force_refresh: bool,
+    // base URLs tried in order, empty for `$GENERAL_GRAPH_KONECT_MIRRORS`
+    // (comma-separated) or else `DEFAULT_MIRROR`
+    pub mirrors: Vec<String>,
+    // the `out.*` file to load from archives holding several networks,
+    // with or without the `out.` prefix; None when the archive has only one
+    pub out_file: Option<String>,
+    // parse the edge list straight from the downloaded archive in memory,
+    // writing neither a temp dir nor the cache; for read-only or slow disks
+    pub in_memory: bool,
+}
+
+pub const DEFAULT_MIRROR: &str = "http://konect.cc/files";
+pub const MIRRORS_ENV: &str = "GENERAL_GRAPH_KONECT_MIRRORS";
+
+fn default_cache_dir() -> PathBuf {
+    let base = match (env::var_os("XDG_CACHE_HOME"), env::var_os("HOME")) {
+        (Some(dir), _) => PathBuf::from(dir),
+        (None, Some(home)) => Path::new(&home).join(".cache"),
+        (None, None) => env::temp_dir(),
+    };
+    base.join("general_graph")
+}
+
+// 64-bit FNV-1a, enough to catch truncated or corrupted cache entries
+fn checksum<R: Read>(r: R) -> io::Result<u64> {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut reader = BufReader::new(r);
+    let mut buf = [0u8; 1 << 16];
+    loop {
+        let len = reader.read(&mut buf)?;
+        if len == 0 {
+            return Ok(hash);
+        }
+        for &b in &buf[..len] {
+            hash = (hash ^ b as u64).wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+impl KonectOptions {
+    pub fn cache_root(&self) -> PathBuf {
+        self.cache_dir.clone().unwrap_or_else(default_cache_dir)
+    }
+    /// The mirrors to try, see `mirrors`.
+    pub fn mirror_list(&self) -> Vec<String> {
+        if !self.mirrors.is_empty() {
+            return self.mirrors.clone();
+        }
+        let from_env: Vec<String> = env::var(MIRRORS_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .map(|url| url.trim().trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        if from_env.is_empty() {
+            vec![DEFAULT_MIRROR.to_string()]
+        } else {
+            from_env
+        }
+    }
+    /// Tarball URLs of `internal_name`, one per mirror.
+    pub fn tarball_urls(&self, internal_name: &str) -> Vec<String> {
+        self.mirror_list()
+            .iter()
+            .map(|base| {
+                format!(
+                    "{}/download.tsv.{}.tar.bz2",
+                    base.trim_end_matches('/'),
+                    internal_name
+                )
+            })
+            .collect()
+    }
+    // the edge list of `internal_name` and the file holding its checksum,
+    // kept apart for every selected `out_file`; names that could reach
+    // outside the cache directory are refused
+    fn entry(&self, internal_name: &str) -> Result<(PathBuf, PathBuf), GraphError> {
+        for name in std::iter::once(internal_name).chain(self.out_file.as_deref()) {
+            if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") {
+                return Err(GraphError::Other(format!(
+                    "Invalid KONECT name '{}' for a cache entry",
+                    name
+                )));
+            }
+        }
+        let root = self.cache_root();
+        let key = match &self.out_file {
+            Some(out_file) => format!("{}.{}", internal_name, out_file),
+            None => internal_name.to_string(),
+        };
+        Ok((
+            root.join(format!("{}.tsv", key)),
+            root.join(format!("{}.tsv.fnv", key)),
+        ))
+    }
+    /// Picks the edge list to load among the `out.*` files of an archive:
+    /// the one named by `out_file`, or else the only one. Fails listing the
+    /// candidates when the choice is missing or ambiguous.
+    pub fn select_entry<'a>(
+        &self,
+        internal_name: &str,
+        candidates: &'a [PathBuf],
+    ) -> Result<&'a Path, GraphError> {
+        let names: Vec<String> = candidates.iter().map(|path| entry_name(path)).collect();
+        let listing = || names.join(", ");
+        match &self.out_file {
+            Some(wanted) => {
+                let wanted = wanted.strip_prefix("out.").unwrap_or(wanted);
+                candidates
+                    .iter()
+                    .find(|path| self.is_out_file(path))
+                    .map(PathBuf::as_path)
+                    .ok_or_else(|| {
+                        GraphError::Other(format!(
+                            "No 'out.{}' in archive of '{}', candidates: {}",
+                            wanted,
+                            internal_name,
+                            listing()
+                        ))
+                    })
+            }
+            None => match candidates {
+                [path] => Ok(path.as_path()),
+                [] => Err(GraphError::Other(format!(
+                    "Failed to find valid konect file in archive of '{}'",
+                    internal_name
+                ))),
+                _ => Err(GraphError::Other(format!(
+                    "Archive of '{}' holds several networks, set `out_file` to one of: {}",
+                    internal_name,
+                    listing()
+                ))),
+            },
+        }
+    }
+    /// Whether `path` is the `out.*` file named by `out_file`.
+    pub(crate) fn is_out_file(&self, path: &Path) -> bool {
+        self.out_file.as_deref().is_some_and(|wanted| {
+            let wanted = wanted.strip_prefix("out.").unwrap_or(wanted);
+            entry_name(path).strip_prefix("out.") == Some(wanted)
+        })
+    }
+    /// Cached edge list of `internal_name`, None when missing, when its
+    /// checksum does not match or when `force_refresh` is set.
+    pub fn cached(&self, internal_name: &str) -> Option<PathBuf> {
+        if self.force_refresh {
+            return None;
+        }
+        let (path, sum_path) = self.entry(internal_name).ok()?;
+        let expected = fs::read_to_string(sum_path).ok()?;
+        let actual = checksum(File::open(&path).ok()?).ok()?;
+        (expected.trim() == format!("{:016x}", actual)).then_some(path)
+    }
+    /// Copies the edge list at `src` into the cache as `internal_name` and
+    /// records its checksum, returning the cached path.
+    pub fn store(&self, internal_name: &str, src: &Path) -> Result<PathBuf, GraphError> {
+        let (path, sum_path) = self.entry(internal_name)?;
+        let root = self.cache_root();
+        let io_err = |e| GraphError::io(format!("Failed to cache '{}'", internal_name), e);
+        fs::create_dir_all(&root).map_err(io_err)?;
+        // copy under a temporary name so a crash never leaves a torn entry
+        let tmp = path.with_extension("tsv.tmp");
+        fs::copy(src, &tmp).map_err(io_err)?;
+        let sum = checksum(File::open(&tmp).map_err(io_err)?).map_err(io_err)?;
+        fs::rename(&tmp, &path).map_err(io_err)?;
+        fs::write(sum_path, format!("{:016x}\n", sum)).map_err(io_err)?;
+        Ok(path)
+    }
+}
+
+/// Outcome of one dataset in `download_all`.
+#[derive(Debug)]
+pub struct DownloadOutcome {
+    pub internal_name: String,
+    // the cached edge list on success
+    pub result: Result<PathBuf, GraphError>,
+    // whether a valid cached copy made the download unnecessary
+    pub from_cache: bool,
+}
+
+/// Per-dataset results of `download_all`, in the order of the names given.
+#[derive(Debug, Default)]
+pub struct DownloadReport {
+    pub outcomes: Vec<DownloadOutcome>,
+}
+
+impl DownloadReport {
+    pub fn succeeded(&self) -> impl Iterator<Item = &DownloadOutcome> {
+        self.outcomes.iter().filter(|o| o.result.is_ok())
+    }
+    pub fn failed(&self) -> impl Iterator<Item = &DownloadOutcome> {
+        self.outcomes.iter().filter(|o| o.result.is_err())
+    }
+    pub fn all_succeeded(&self) -> bool {
+        self.failed().next().is_none()
+    }
+}
+
+/// Names of the `out.*` edge lists in the archive of `internal_name`, to
+/// choose `out_file` from. Downloads the archive without caching it.
+pub fn list_entries(
+    internal_name: &str,
+    options: &KonectOptions,
+) -> Result<Vec<String>, GraphError> {
+    block_on(list_konect_entries(
+        internal_name,
+        options,
+        &ComputeConfig::default(),
+    ))?
+}
+
+/// Fetches every dataset into the cache of `options`, carrying on past
+/// failures so that one missing dataset does not stop the others.
+pub fn download_all(names: &[&str], options: &KonectOptions) -> DownloadReport {
+    download_all_with(names, options, &ComputeConfig::default())
+}
+
+/// `download_all` downloading under the limits of `config`. Cancelling
+/// through `config` fails the remaining datasets.
+pub fn download_all_with(
+    names: &[&str],
+    options: &KonectOptions,
+    config: &ComputeConfig,
+) -> DownloadReport {
+    let downloads = async {
+        let mut outcomes = Vec::with_capacity(names.len());
+        for &internal_name in names {
+            let (result, from_cache) = match options.cached(internal_name) {
+                Some(path) => (Ok(path), true),
+                None => (download_konect(internal_name, options, config).await, false),
+            };
+            outcomes.push(DownloadOutcome {
+                internal_name: internal_name.to_string(),
+                result,
+                from_cache,
+            });
+        }
+        outcomes
+    };
+    let outcomes = match block_on(downloads) {
+        Ok(outcomes) => outcomes,
+        // no runtime, so nothing was attempted
+        Err(e) => names
+            .iter()
+            .map(|name| DownloadOutcome {
+                internal_name: name.to_string(),
+                result: Err(GraphError::Other(e.to_string())),
+                from_cache: false,
+            })
+            .collect(),
+    };
+    DownloadReport { outcomes }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = KonectOptions {
+            cache_dir: Some(dir.path().join("cache")),
+            ..Default::default()
+        };
+        assert_eq!(options.cached("toy"), None);
+        let src = dir.path().join("out.toy");
+        fs::write(&src, "% sym\n1 2\n2 3\n").unwrap();
+        let path = options.store("toy", &src).unwrap();
+        assert_eq!(options.cached("toy"), Some(path.clone()));
+        let refresh = KonectOptions {
+            force_refresh: true,
+            ..options.clone()
+        };
+        assert_eq!(refresh.cached("toy"), None);
+        let mirrored = KonectOptions {
+            mirrors: vec![
+                "http://a.example/".to_string(),
+                "http://b.example".to_string(),
+            ],
+            ..options.clone()
+        };
+        assert_eq!(
+            mirrored.tarball_urls("toy"),
+            vec![
+                "http://a.example/download.tsv.toy.tar.bz2",
+                "http://b.example/download.tsv.toy.tar.bz2"
+            ]
+        );
+        // a corrupted entry is ignored
+        fs::write(&path, "% sym\n1 2\n").unwrap();
+        assert_eq!(options.cached("toy"), None);
+        // names never leave the cache directory
+        assert!(options.store("../toy", &src).is_err());
+        assert!(options.store("a/b", &src).is_err());
+        assert_eq!(options.cached(".."), None);
+        let nested = KonectOptions {
+            out_file: Some("x/../../y".to_string()),
+            ..options.clone()
+        };
+        assert!(nested.store("toy", &src).is_err());
+        assert!(!dir.path().join("y.tsv").exists());
+    }
+
+    #[test]
+    fn test_select_entry() {
+        let candidates = vec![
+            PathBuf::from("/tmp/x/out.a_b"),
+            PathBuf::from("/tmp/x/out.a_c"),
+        ];
+        let options = KonectOptions::default();
+        let err = options.select_entry("a", &candidates).unwrap_err();
+        assert!(err.to_string().contains("out.a_b, out.a_c"));
+        assert_eq!(
+            options.select_entry("a", &candidates[..1]).unwrap(),
+            candidates[0]
+        );
+        for out_file in ["a_c", "out.a_c"] {
+            let options = KonectOptions {
+                out_file: Some(out_file.to_string()),
+                ..Default::default()
+            };
+            assert_eq!(
+                options.select_entry("a", &candidates).unwrap(),
+                candidates[1]
+            );
+        }
+        let missing = KonectOptions {
+            out_file: Some("a_d".to_string()),
+            ..Default::default()
+        };
+        assert!(missing.select_entry("a", &candidates).is_err());
+    }
+
+    #[test]
+    fn test_download_all_continues() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = KonectOptions {
+            cache_dir: Some(dir.path().to_path_buf()),
+            // nothing listens on the discard port of the loopback address,
+            // so the missing dataset fails without leaving the machine
+            mirrors: vec!["http://127.0.0.1:9".to_string()],
+            ..Default::default()
+        };
+        let src = dir.path().join("out.toy");
+        fs::write(&src, "% sym\n1 2\n").unwrap();
+        options.store("toy", &src).unwrap();
+        let report = download_all(&["no-such-dataset", "../toy", "toy"], &options);
+        assert_eq!(report.outcomes.len(), 3);
+        assert!(!report.all_succeeded());
+        let failed: Vec<&str> = report
+            .failed()
+            .map(|outcome| outcome.internal_name.as_str())
+            .collect();
+        assert_eq!(failed, ["no-such-dataset", "../toy"]);
+        let ok = report.succeeded().next().unwrap();
+        assert!(ok.from_cache);
+        assert_eq!(ok.internal_name, "toy");
+    }
+}
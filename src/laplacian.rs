@@ -0,0 +1,542 @@
+use nalgebra::DMatrix;
+
+use super::coarsen::{Coarsening, MatchingStrategy};
+use super::linalg::{conjugate_gradient, preconditioned_conjugate_gradient, CgOptions, CgSolution};
+use super::normal_graph::NormalUndiGraph;
+use super::weighted_graph::WeightedUndiGraph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Preconditioner {
+    Identity,
+    Jacobi,
+    // aggregation multigrid V-cycle over repeated heavy-edge coarsening
+    Multigrid,
+}
+
+pub struct LaplacianSolverOptions {
+    pub preconditioner: Preconditioner,
+    pub tol: f64,
+    pub max_iter: usize,
+    // levels at or below this size are solved densely
+    pub coarsest_size: usize,
+    // damped Jacobi sweeps before and after each coarse correction
+    pub smoothing_steps: usize,
+}
+
+impl Default for LaplacianSolverOptions {
+    fn default() -> Self {
+        Self {
+            preconditioner: Preconditioner::Multigrid,
+            tol: 1e-8,
+            max_iter: 1000,
+            coarsest_size: 100,
+            smoothing_steps: 2,
+        }
+    }
+}
+
+const JACOBI_DAMPING: f64 = 2.0 / 3.0;
+
+struct Level {
+    graph: WeightedUndiGraph,
+    diag: Vec<f64>,
+    // map to the next coarser level, None on the coarsest level
+    coarsening: Option<Coarsening>,
+}
+
+pub struct LaplacianSolver {
+    levels: Vec<Level>,
+    // pseudo-inverse of the coarsest Laplacian
+    coarse_pinv: DMatrix<f64>,
+    components: Vec<usize>,
+    num_components: usize,
+    options: LaplacianSolverOptions,
+}
+
+impl WeightedUndiGraph {
+    pub(crate) fn laplacian_apply(&self, x: &[f64], y: &mut [f64]) {
+        for (u, adj) in self.adjs.iter().enumerate() {
+            y[u] = adj.iter().map(|&(v, w)| w * (x[u] - x[v])).sum();
+        }
+    }
+    fn component_labels(&self) -> (Vec<usize>, usize) {
+        let mut label = vec![usize::MAX; self.n];
+        let mut cnt = 0;
+        for s in 0..self.n {
+            if label[s] != usize::MAX {
+                continue;
+            }
+            label[s] = cnt;
+            let mut stack = vec![s];
+            while let Some(u) = stack.pop() {
+                for &(v, _) in &self.adjs[u] {
+                    if label[v] == usize::MAX {
+                        label[v] = cnt;
+                        stack.push(v);
+                    }
+                }
+            }
+            cnt += 1;
+        }
+        (label, cnt)
+    }
+    /// Solves `L x = b` for the weighted Laplacian, see [`LaplacianSolver`].
+    pub fn solve_laplacian(&self, b: &[f64], options: LaplacianSolverOptions) -> CgSolution {
+        LaplacianSolver::new(self.clone(), options).solve(b)
+    }
+    /// Harmonic extension of `boundary_values`: the vector equal to them on
+    /// boundary nodes with `(L x)_u = 0` at every other node, i.e. the node
+    /// potentials of the network with the boundary held at fixed voltages.
+    /// Solved by Jacobi-preconditioned CG on the interior block of `L`. Fails
+    /// unless every interior component touches the boundary.
+    pub fn solve_dirichlet(&self, boundary_values: &[(usize, f64)]) -> Result<Vec<f64>, String> {
+        let mut x = vec![0.0; self.n];
+        let mut fixed = vec![false; self.n];
+        for &(u, value) in boundary_values {
+            if u >= self.n {
+                return Err(format!("Boundary node {} out of range", u));
+            }
+            if fixed[u] {
+                return Err(format!("Boundary node {} given twice", u));
+            }
+            fixed[u] = true;
+            x[u] = value;
+        }
+        // interior nodes not connected to the boundary are undetermined
+        let mut reached = fixed.clone();
+        let mut stack: Vec<usize> = (0..self.n).filter(|&u| fixed[u]).collect();
+        while let Some(u) = stack.pop() {
+            for &(v, _) in &self.adjs[u] {
+                if !reached[v] {
+                    reached[v] = true;
+                    stack.push(v);
+                }
+            }
+        }
+        if let Some(u) = reached.iter().position(|&r| !r) {
+            return Err(format!("Node {} is not connected to the boundary", u));
+        }
+        let interior: Vec<usize> = (0..self.n).filter(|&u| !fixed[u]).collect();
+        if interior.is_empty() {
+            return Ok(x);
+        }
+        let mut pos = vec![usize::MAX; self.n];
+        for (i, &u) in interior.iter().enumerate() {
+            pos[u] = i;
+        }
+        let diag: Vec<f64> = interior
+            .iter()
+            .map(|&u| self.adjs[u].iter().map(|&(_, w)| w).sum())
+            .collect();
+        let b: Vec<f64> = interior
+            .iter()
+            .map(|&u| {
+                self.adjs[u]
+                    .iter()
+                    .filter(|&&(v, _)| fixed[v])
+                    .map(|&(v, w)| w * x[v])
+                    .sum()
+            })
+            .collect();
+        let apply = |y: &[f64], out: &mut [f64]| {
+            for (i, &u) in interior.iter().enumerate() {
+                let off: f64 = self.adjs[u]
+                    .iter()
+                    .filter(|&&(v, _)| !fixed[v])
+                    .map(|&(v, w)| w * y[pos[v]])
+                    .sum();
+                out[i] = diag[i] * y[i] - off;
+            }
+        };
+        let solution = conjugate_gradient(apply, &b, Some(&diag), &CgOptions::default());
+        if !solution.converged {
+            return Err(format!(
+                "Dirichlet solve did not converge, residual {:e}",
+                solution.residual
+            ));
+        }
+        for (&u, value) in interior.iter().zip(solution.x) {
+            x[u] = value;
+        }
+        Ok(x)
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn solve_laplacian(&self, b: &[f64], options: LaplacianSolverOptions) -> CgSolution {
+        WeightedUndiGraph::from_normal(self).solve_laplacian(b, options)
+    }
+    /// See [`WeightedUndiGraph::solve_dirichlet`].
+    pub fn solve_dirichlet(&self, boundary_values: &[(usize, f64)]) -> Result<Vec<f64>, String> {
+        WeightedUndiGraph::from_normal(self).solve_dirichlet(boundary_values)
+    }
+    /// Effective resistance of every pair, sharing one solver; see
+    /// [`LaplacianSolver::effective_resistances`].
+    pub fn effective_resistances(&self, pairs: &[(usize, usize)]) -> Vec<f64> {
+        LaplacianSolver::new(WeightedUndiGraph::from_normal(self), Default::default())
+            .effective_resistances(pairs)
+    }
+    pub fn effective_resistance(&self, u: usize, v: usize) -> f64 {
+        self.effective_resistances(&[(u, v)])[0]
+    }
+    /// Dense Moore-Penrose pseudo-inverse `L^+` of the Laplacian by SVD, in
+    /// O(n^3); meant for graphs of up to a few thousand nodes.
+    pub fn laplacian_pinv(&self) -> DMatrix<f64> {
+        let (diag, adj) = self.diag_adj();
+        let laplacian = DMatrix::from_diagonal(&diag) - adj;
+        laplacian.pseudo_inverse(PINV_EPS).unwrap()
+    }
+    /// Kirchhoff index, the sum of effective resistances over unordered
+    /// pairs, as `n tr(L^+)`; infinite for disconnected graphs. The trace
+    /// comes from `laplacian_pinv` up to `DENSE_LIMIT` nodes and from one
+    /// Laplacian solve per node beyond.
+    pub fn kirchhoff_index(&self) -> f64 {
+        if self.n <= DENSE_LIMIT {
+            self.kirchhoff_index_dense()
+        } else {
+            self.kirchhoff_index_iterative()
+        }
+    }
+    fn kirchhoff_index_dense(&self) -> f64 {
+        if self.num_components() > 1 {
+            return f64::INFINITY;
+        }
+        self.n as f64 * self.laplacian_pinv().trace()
+    }
+    fn kirchhoff_index_iterative(&self) -> f64 {
+        if self.num_components() > 1 {
+            return f64::INFINITY;
+        }
+        let solver = LaplacianSolver::new(WeightedUndiGraph::from_normal(self), Default::default());
+        let trace: f64 = (0..self.n)
+            .map(|s| {
+                let mut b = vec![0.0; self.n];
+                b[s] = 1.0;
+                solver.solve(&b).x[s]
+            })
+            .sum();
+        self.n as f64 * trace
+    }
+}
+
+// node count up to which `kirchhoff_index` inverts the Laplacian densely
+pub(crate) const DENSE_LIMIT: usize = 2000;
+// singular values below this count as the zero eigenvalues of `L`
+const PINV_EPS: f64 = 1e-9;
+
+impl LaplacianSolver {
+    /// Builds the multigrid hierarchy when requested, by heavy-edge coarsening
+    /// until `coarsest_size` is reached or coarsening stalls.
+    pub fn new(graph: WeightedUndiGraph, options: LaplacianSolverOptions) -> Self {
+        let (components, num_components) = graph.component_labels();
+        let mut levels = vec![Level {
+            diag: graph.strengths(),
+            graph,
+            coarsening: None,
+        }];
+        if options.preconditioner == Preconditioner::Multigrid {
+            loop {
+                let last = levels.last_mut().unwrap();
+                if last.graph.n <= options.coarsest_size {
+                    break;
+                }
+                let coarsening = last.graph.coarsen(MatchingStrategy::HeavyEdge);
+                // stop once matching removes less than a tenth of the nodes
+                if coarsening.graph.n * 10 > last.graph.n * 9 {
+                    break;
+                }
+                let graph = coarsening.graph.clone();
+                last.coarsening = Some(coarsening);
+                levels.push(Level {
+                    diag: graph.strengths(),
+                    graph,
+                    coarsening: None,
+                });
+            }
+        }
+        let coarse_pinv = if options.preconditioner == Preconditioner::Multigrid {
+            let g = &levels.last().unwrap().graph;
+            let lap = DMatrix::from_fn(g.n, g.n, |i, j| {
+                if i == j {
+                    g.adjs[i].iter().map(|&(_, w)| w).sum()
+                } else {
+                    -g.weight(i, j).unwrap_or(0.0)
+                }
+            });
+            lap.pseudo_inverse(1e-10).unwrap()
+        } else {
+            DMatrix::zeros(0, 0)
+        };
+        Self {
+            levels,
+            coarse_pinv,
+            components,
+            num_components,
+            options,
+        }
+    }
+    pub fn num_levels(&self) -> usize {
+        self.levels.len()
+    }
+    fn smooth(&self, level: &Level, r: &[f64], z: &mut [f64]) {
+        let mut lz = vec![0.0; z.len()];
+        for _ in 0..self.options.smoothing_steps {
+            level.graph.laplacian_apply(z, &mut lz);
+            for i in 0..z.len() {
+                if level.diag[i] > 0.0 {
+                    z[i] += JACOBI_DAMPING * (r[i] - lz[i]) / level.diag[i];
+                }
+            }
+        }
+    }
+    fn v_cycle(&self, depth: usize, r: &[f64]) -> Vec<f64> {
+        let level = &self.levels[depth];
+        let Some(coarsening) = &level.coarsening else {
+            let x = &self.coarse_pinv * nalgebra::DVector::from_column_slice(r);
+            return x.as_slice().to_vec();
+        };
+        let mut z = vec![0.0; r.len()];
+        self.smooth(level, r, &mut z);
+        let mut lz = vec![0.0; r.len()];
+        level.graph.laplacian_apply(&z, &mut lz);
+        let residual: Vec<f64> = r.iter().zip(&lz).map(|(a, b)| a - b).collect();
+        let correction =
+            coarsening.prolong(&self.v_cycle(depth + 1, &coarsening.restrict(&residual)));
+        z.iter_mut().zip(correction).for_each(|(x, c)| *x += c);
+        self.smooth(level, r, &mut z);
+        z
+    }
+    // Removes the mean of `x` on every component, the kernel of the Laplacian.
+    fn project(&self, x: &mut [f64]) {
+        let mut sums = vec![0.0; self.num_components];
+        let mut cnts = vec![0usize; self.num_components];
+        for (&c, v) in self.components.iter().zip(x.iter()) {
+            sums[c] += v;
+            cnts[c] += 1;
+        }
+        for (&c, v) in self.components.iter().zip(x.iter_mut()) {
+            *v -= sums[c] / cnts[c] as f64;
+        }
+    }
+    /// Preconditioned CG on `L x = b`. The right-hand side is first projected
+    /// onto the range of `L` (zero sum on every component) and the solution is
+    /// returned with zero mean on every component.
+    pub fn solve(&self, b: &[f64]) -> CgSolution {
+        let fine = &self.levels[0];
+        let mut rhs = b.to_vec();
+        self.project(&mut rhs);
+        let apply = |x: &[f64], y: &mut [f64]| fine.graph.laplacian_apply(x, y);
+        let precondition = |r: &[f64], z: &mut [f64]| match self.options.preconditioner {
+            Preconditioner::Identity => z.copy_from_slice(r),
+            Preconditioner::Jacobi => {
+                for ((z, r), d) in z.iter_mut().zip(r).zip(&fine.diag) {
+                    *z = if *d > 0.0 { r / d } else { *r };
+                }
+            }
+            Preconditioner::Multigrid => {
+                z.copy_from_slice(&self.v_cycle(0, r));
+                self.project(z);
+            }
+        };
+        let cg = CgOptions {
+            tol: self.options.tol,
+            max_iter: self.options.max_iter,
+        };
+        let mut sol = preconditioned_conjugate_gradient(apply, precondition, &rhs, &cg);
+        self.project(&mut sol.x);
+        sol
+    }
+    /// Effective resistance `(e_s - e_t)^T L^+ (e_s - e_t)` of every pair,
+    /// infinite across components. Solves once per pair or once per
+    /// distinct endpoint, whichever is fewer, reusing the hierarchy: with
+    /// the columns `L^+ e_s` at hand every pair among those endpoints costs
+    /// O(1).
+    pub fn effective_resistances(&self, pairs: &[(usize, usize)]) -> Vec<f64> {
+        let n = self.components.len();
+        let connected =
+            |&(s, t): &(usize, usize)| s != t && self.components[s] == self.components[t];
+        let mut nodes: Vec<usize> = pairs
+            .iter()
+            .filter(|p| connected(p))
+            .flat_map(|&(s, t)| [s, t])
+            .collect();
+        nodes.sort_unstable();
+        nodes.dedup();
+        let mut res: Vec<f64> = pairs
+            .iter()
+            .map(|&(s, t)| if s == t { 0.0 } else { f64::INFINITY })
+            .collect();
+        if pairs.iter().filter(|p| connected(p)).count() <= nodes.len() {
+            for (i, p) in pairs.iter().enumerate().filter(|(_, p)| connected(p)) {
+                let mut b = vec![0.0; n];
+                b[p.0] = 1.0;
+                b[p.1] = -1.0;
+                let x = self.solve(&b).x;
+                res[i] = x[p.0] - x[p.1];
+            }
+            return res;
+        }
+        // diagonal entries of L^+ and the entry of every pair, filled in as
+        // the column of its first endpoint is computed
+        let mut diag = vec![0.0; n];
+        let mut cross = vec![0.0; pairs.len()];
+        let mut by_first: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for (i, p) in pairs.iter().enumerate().filter(|(_, p)| connected(p)) {
+            by_first[p.0].push(i);
+        }
+        for &s in &nodes {
+            let mut b = vec![0.0; n];
+            b[s] = 1.0;
+            let x = self.solve(&b).x;
+            diag[s] = x[s];
+            for &i in &by_first[s] {
+                cross[i] = x[pairs[i].1];
+            }
+        }
+        for (i, p) in pairs.iter().enumerate().filter(|(_, p)| connected(p)) {
+            res[i] = diag[p.0] + diag[p.1] - 2.0 * cross[i];
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn residual(g: &NormalUndiGraph, x: &[f64], b: &[f64]) -> f64 {
+        let w = WeightedUndiGraph::from_normal(g);
+        let mut lx = vec![0.0; g.n];
+        w.laplacian_apply(x, &mut lx);
+        lx.iter()
+            .zip(b)
+            .map(|(a, b)| (a - b).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    #[test]
+    fn test_solve_laplacian() {
+        let g = NormalUndiGraph::from_apollo(5);
+        let mut b = vec![0.0; g.n];
+        b[0] = 1.0;
+        b[g.n - 1] = -1.0;
+        let mut iterations = Vec::new();
+        for preconditioner in [Preconditioner::Jacobi, Preconditioner::Multigrid] {
+            let sol = g.solve_laplacian(
+                &b,
+                LaplacianSolverOptions {
+                    preconditioner,
+                    coarsest_size: 20,
+                    ..Default::default()
+                },
+            );
+            assert!(sol.converged);
+            assert!(residual(&g, &sol.x, &b) < 1e-6);
+            assert!(sol.x.iter().sum::<f64>().abs() < 1e-8);
+            iterations.push(sol.iterations);
+        }
+        assert!(iterations[1] < iterations[0]);
+    }
+
+    #[test]
+    fn test_solve_dirichlet() {
+        // the potential falls linearly along a path held at 1 and 0
+        let path =
+            NormalUndiGraph::from_edges("P5".to_string(), 5, &[(0, 1), (1, 2), (2, 3), (3, 4)]);
+        let x = path.solve_dirichlet(&[(0, 1.0), (4, 0.0)]).unwrap();
+        for (a, b) in x.iter().zip([1.0, 0.75, 0.5, 0.25, 0.0]) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        let g = NormalUndiGraph::from_apollo(4);
+        let x = g.solve_dirichlet(&[(0, 2.0), (1, -1.0), (2, 0.5)]).unwrap();
+        let w = WeightedUndiGraph::from_normal(&g);
+        let mut lx = vec![0.0; g.n];
+        w.laplacian_apply(&x, &mut lx);
+        assert_eq!(&x[..3], &[2.0, -1.0, 0.5]);
+        assert!(lx[3..].iter().all(|v| v.abs() < 1e-6));
+        // the maximum principle holds
+        assert!(x.iter().all(|&v| (-1.0..=2.0).contains(&v)));
+        let split = NormalUndiGraph::from_edges("2K2".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert!(split.solve_dirichlet(&[(0, 1.0)]).is_err());
+        assert!(split.solve_dirichlet(&[(0, 1.0), (0, 2.0)]).is_err());
+    }
+
+    #[test]
+    fn test_kirchhoff_index() {
+        // K_n has resistance 2 / n on every pair
+        let k4 = NormalUndiGraph::from_apollo(0);
+        assert!((k4.effective_resistance(0, 3) - 0.5).abs() < 1e-6);
+        assert!((k4.kirchhoff_index() - 3.0).abs() < 1e-9);
+        // P_n has Kirchhoff index (n^3 - n) / 6, the Wiener index of a tree
+        let n = 30;
+        let edges: Vec<(usize, usize)> = (1..n).map(|u| (u - 1, u)).collect();
+        let path = NormalUndiGraph::from_edges("P30".to_string(), n, &edges);
+        let expected = (n * n * n - n) as f64 / 6.0;
+        assert!((path.kirchhoff_index_dense() - expected).abs() < 1e-6);
+        assert!((path.kirchhoff_index_iterative() - expected).abs() < 1e-4);
+        assert!((path.effective_resistance(0, n - 1) - (n - 1) as f64).abs() < 1e-5);
+
+        let g = NormalUndiGraph::from_apollo(3);
+        let pinv = g.laplacian_pinv();
+        let (u, v) = (1, g.n - 1);
+        let dense = pinv[(u, u)] + pinv[(v, v)] - 2.0 * pinv[(u, v)];
+        assert!((g.effective_resistance(u, v) - dense).abs() < 1e-6);
+        let rel = (g.kirchhoff_index_iterative() - g.kirchhoff_index_dense()).abs()
+            / g.kirchhoff_index_dense();
+        assert!(rel < 1e-6);
+        let two = NormalUndiGraph::from_edges("2K2".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert_eq!(two.kirchhoff_index(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_solve_laplacian_disconnected() {
+        // two disjoint triangles; b is projected per component
+        let g = NormalUndiGraph::from_edges(
+            "2C3".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)],
+        );
+        let solver = LaplacianSolver::new(
+            WeightedUndiGraph::from_normal(&g),
+            LaplacianSolverOptions {
+                coarsest_size: 2,
+                ..Default::default()
+            },
+        );
+        assert!(solver.num_levels() > 1);
+        let sol = solver.solve(&[1.0, 0.0, -1.0, 2.0, 2.0, 2.0]);
+        assert!(sol.converged);
+        assert!(residual(&g, &sol.x, &[1.0, 0.0, -1.0, 0.0, 0.0, 0.0]) < 1e-6);
+    }
+
+    #[test]
+    fn test_effective_resistances() {
+        // 4-cycle with a pendant node 4 and an isolated node 5
+        let g = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (3, 4)],
+        );
+        // fewer pairs than endpoints: one solve per pair
+        let r = g.effective_resistances(&[(0, 2), (1, 4)]);
+        assert!((r[0] - 1.0).abs() < 1e-6);
+        assert!((r[1] - 2.0).abs() < 1e-6);
+        // many pairs over few endpoints: one solve per endpoint
+        let pairs = [
+            (0, 1),
+            (0, 2),
+            (0, 4),
+            (1, 2),
+            (2, 4),
+            (4, 0),
+            (5, 0),
+            (3, 3),
+        ];
+        let r = g.effective_resistances(&pairs);
+        let expected = [0.75, 1.0, 1.75, 0.75, 1.75, 1.75, f64::INFINITY, 0.0];
+        for (a, b) in r.iter().zip(expected) {
+            assert!(a == &b || (a - b).abs() < 1e-6, "{} {}", a, b);
+        }
+    }
+}
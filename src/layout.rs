@@ -0,0 +1,260 @@
+use nalgebra as na;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::io::Write;
+
+use super::normal_graph::NormalUndiGraph;
+use super::render::RenderOptions;
+use super::spectral::SpectrumMatrix;
+
+pub struct ForceLayoutOptions {
+    pub iterations: usize,
+    // Barnes-Hut opening angle; 0 falls back to exact O(n^2) repulsion
+    pub theta: f64,
+    pub seed: u64,
+}
+
+impl Default for ForceLayoutOptions {
+    fn default() -> Self {
+        Self {
+            iterations: 300,
+            theta: 0.8,
+            seed: 0,
+        }
+    }
+}
+
+struct QuadNode {
+    x0: f64,
+    y0: f64,
+    size: f64,
+    mass: f64,
+    cx: f64,
+    cy: f64,
+    body: Option<usize>,
+    children: Option<[usize; 4]>,
+}
+
+struct QuadTree {
+    nodes: Vec<QuadNode>,
+}
+
+impl QuadTree {
+    fn new(pos: &[(f64, f64)]) -> Self {
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &(x, y) in pos {
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+        let size = (x1 - x0).max(y1 - y0).max(1e-9) * 1.0001;
+        let mut tree = Self {
+            nodes: vec![QuadNode::empty(x0, y0, size)],
+        };
+        for (i, &(x, y)) in pos.iter().enumerate() {
+            tree.insert(0, i, x, y, pos);
+        }
+        tree
+    }
+    fn insert(&mut self, idx: usize, body: usize, x: f64, y: f64, pos: &[(f64, f64)]) {
+        let node = &mut self.nodes[idx];
+        node.cx = (node.cx * node.mass + x) / (node.mass + 1.0);
+        node.cy = (node.cy * node.mass + y) / (node.mass + 1.0);
+        node.mass += 1.0;
+        if node.mass == 1.0 {
+            node.body = Some(body);
+            return;
+        }
+        // coincident points are merged into the leaf once cells get tiny
+        if node.size < 1e-9 {
+            return;
+        }
+        if node.children.is_none() {
+            let (x0, y0, half) = (node.x0, node.y0, node.size / 2.0);
+            let base = self.nodes.len();
+            for k in 0..4 {
+                let (dx, dy) = ((k & 1) as f64, (k >> 1) as f64);
+                self.nodes
+                    .push(QuadNode::empty(x0 + dx * half, y0 + dy * half, half));
+            }
+            self.nodes[idx].children = Some([base, base + 1, base + 2, base + 3]);
+            if let Some(old) = self.nodes[idx].body.take() {
+                let (ox, oy) = pos[old];
+                let child = self.child_for(idx, ox, oy);
+                self.insert(child, old, ox, oy, pos);
+            }
+        }
+        let child = self.child_for(idx, x, y);
+        self.insert(child, body, x, y, pos);
+    }
+    fn child_for(&self, idx: usize, x: f64, y: f64) -> usize {
+        let node = &self.nodes[idx];
+        let half = node.size / 2.0;
+        let k = (x >= node.x0 + half) as usize + 2 * (y >= node.y0 + half) as usize;
+        node.children.unwrap()[k]
+    }
+    fn repulsion(&self, i: usize, x: f64, y: f64, k2: f64, theta: f64) -> (f64, f64) {
+        let mut force = (0.0, 0.0);
+        let mut stack = vec![0usize];
+        while let Some(idx) = stack.pop() {
+            let node = &self.nodes[idx];
+            if node.mass == 0.0 || (node.body == Some(i) && node.children.is_none()) {
+                continue;
+            }
+            let (dx, dy) = (x - node.cx, y - node.cy);
+            let d2 = (dx * dx + dy * dy).max(1e-12);
+            let d = d2.sqrt();
+            match node.children {
+                Some(children) if node.size / d >= theta => stack.extend(children),
+                _ => {
+                    let f = node.mass * k2 / d2;
+                    force.0 += dx * f;
+                    force.1 += dy * f;
+                }
+            }
+        }
+        force
+    }
+}
+
+impl QuadNode {
+    fn empty(x0: f64, y0: f64, size: f64) -> Self {
+        Self {
+            x0,
+            y0,
+            size,
+            mass: 0.0,
+            cx: 0.0,
+            cy: 0.0,
+            body: None,
+            children: None,
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    /// Fruchterman-Reingold layout in the unit square, with Barnes-Hut
+    /// approximated repulsion.
+    pub fn fruchterman_reingold(&self, options: &ForceLayoutOptions) -> Vec<(f64, f64)> {
+        let n = self.n;
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let mut pos: Vec<(f64, f64)> = (0..n).map(|_| (rng.gen(), rng.gen())).collect();
+        if n < 2 {
+            return pos;
+        }
+        let k = (1.0 / n as f64).sqrt();
+        let k2 = k * k;
+        let mut disp = vec![(0.0, 0.0); n];
+        for it in 0..options.iterations {
+            let temp = 0.1 * (1.0 - it as f64 / options.iterations as f64);
+            if options.theta > 0.0 {
+                let tree = QuadTree::new(&pos);
+                for (i, &(x, y)) in pos.iter().enumerate() {
+                    disp[i] = tree.repulsion(i, x, y, k2, options.theta);
+                }
+            } else {
+                for i in 0..n {
+                    disp[i] = (0.0, 0.0);
+                    for j in 0..n {
+                        if i != j {
+                            let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                            let d2 = (dx * dx + dy * dy).max(1e-12);
+                            disp[i].0 += dx * k2 / d2;
+                            disp[i].1 += dy * k2 / d2;
+                        }
+                    }
+                }
+            }
+            for (u, adj) in self.adjs.iter().enumerate() {
+                for &v in adj {
+                    let (dx, dy) = (pos[u].0 - pos[v].0, pos[u].1 - pos[v].1);
+                    let d = (dx * dx + dy * dy).sqrt();
+                    disp[u].0 -= dx * d / k;
+                    disp[u].1 -= dy * d / k;
+                }
+            }
+            for (p, &(dx, dy)) in pos.iter_mut().zip(&disp) {
+                let len = (dx * dx + dy * dy).sqrt().max(1e-12);
+                let step = len.min(temp);
+                p.0 += dx / len * step;
+                p.1 += dy / len * step;
+            }
+        }
+        pos
+    }
+    /// Coordinates from the Laplacian eigenvectors of the second and third
+    /// smallest eigenvalues.
+    pub fn spectral_layout(&self) -> Vec<(f64, f64)> {
+        if self.n < 3 {
+            return (0..self.n).map(|u| (u as f64, 0.0)).collect();
+        }
+        let eigen = na::SymmetricEigen::new(self.spectrum_matrix(SpectrumMatrix::Laplacian));
+        let mut order: Vec<usize> = (0..self.n).collect();
+        order.sort_unstable_by(|&a, &b| eigen.eigenvalues[a].total_cmp(&eigen.eigenvalues[b]));
+        let (x, y) = (
+            eigen.eigenvectors.column(order[1]),
+            eigen.eigenvectors.column(order[2]),
+        );
+        (0..self.n).map(|u| (x[u], y[u])).collect()
+    }
+    pub fn write_svg<W: Write>(&self, coords: &[(f64, f64)], w: &mut W) -> std::io::Result<()> {
+        self.render_svg_to(
+            w,
+            &RenderOptions {
+                coords: Some(coords),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge_length_ratio(g: &NormalUndiGraph, pos: &[(f64, f64)]) -> f64 {
+        let dist = |u: usize, v: usize| {
+            ((pos[u].0 - pos[v].0).powi(2) + (pos[u].1 - pos[v].1).powi(2)).sqrt()
+        };
+        let mut edge_sum = 0.0;
+        for (u, adj) in g.adjs.iter().enumerate() {
+            for &v in adj {
+                edge_sum += dist(u, v);
+            }
+        }
+        let mut all_sum = 0.0;
+        for u in 0..g.n {
+            for v in 0..g.n {
+                all_sum += dist(u, v);
+            }
+        }
+        (edge_sum / (2 * g.m) as f64) / (all_sum / (g.n * g.n) as f64)
+    }
+
+    #[test]
+    fn test_fruchterman_reingold() {
+        let g = NormalUndiGraph::from_koch(2);
+        let bh = g.fruchterman_reingold(&ForceLayoutOptions::default());
+        let exact = g.fruchterman_reingold(&ForceLayoutOptions {
+            theta: 0.0,
+            ..Default::default()
+        });
+        assert_eq!(bh.len(), g.n);
+        assert!(bh.iter().all(|(x, y)| x.is_finite() && y.is_finite()));
+        assert!(edge_length_ratio(&g, &bh) < 0.5);
+        assert!(edge_length_ratio(&g, &exact) < 0.5);
+    }
+
+    #[test]
+    fn test_spectral_layout_svg() {
+        let g = NormalUndiGraph::from_pseudofractal(2);
+        let pos = g.spectral_layout();
+        assert_eq!(pos.len(), g.n);
+        let mut buf: Vec<u8> = Vec::new();
+        g.write_svg(&pos, &mut buf).unwrap();
+        let svg = String::from_utf8(buf).unwrap();
+        assert_eq!(svg.matches("<line").count(), g.m);
+        assert_eq!(svg.matches("<circle").count(), g.n);
+    }
+}
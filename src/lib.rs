@@ -1,7 +1,71 @@
+pub mod algorithms;
+pub mod alignment;
+pub mod archive;
+pub mod assortativity;
+pub mod automorphism;
+pub mod binary;
+pub mod bottleneck;
+pub mod builder;
+pub mod canonical;
+pub mod centrality;
+pub mod checkpoint;
+pub mod classification;
+pub mod closed_form;
+pub mod clustering;
+pub mod coarsen;
+pub mod combinatorial;
+pub mod components;
+pub mod config;
+pub mod connectivity;
+pub mod cores;
+pub mod csr;
+pub mod cycles;
+pub mod directed_graph;
 pub mod dsu;
+pub mod eccentricity;
+pub mod error;
+pub mod features;
 pub mod general_graph;
+pub mod graphlets;
+#[cfg(feature = "petgraph")]
+pub mod interop;
+pub mod io;
+pub mod journal;
+pub mod konect;
+pub mod laplacian;
+pub mod layout;
+pub mod linalg;
+pub mod link_prediction;
+pub mod matrix_functions;
+pub mod memory;
 pub mod normal_graph;
+pub mod oracle;
+pub mod progress;
+pub mod random_walk;
+pub mod ranking;
+pub mod relabel;
+pub mod render;
+pub mod reports;
+pub mod sampling;
+pub mod signal;
+pub mod similarity;
+pub mod spanning_tree;
+pub mod spatial;
+pub mod spectral;
+pub mod stats;
+pub mod testing;
+pub mod tours;
+pub mod traversal;
+pub mod trim;
+pub mod weighted_graph;
 
+pub use builder::GraphBuilder;
+pub use config::ComputeConfig;
+pub use csr::CsrGraph;
+pub use directed_graph::{GeneralDiGraph, NormalDiGraph};
 pub use dsu::DSU;
+pub use error::GraphError;
 pub use general_graph::GeneralUndiGraph;
 pub use normal_graph::NormalUndiGraph;
+pub use progress::{CancellationToken, ProgressFn};
+pub use weighted_graph::WeightedUndiGraph;
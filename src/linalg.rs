@@ -0,0 +1,428 @@
+use nalgebra::{DMatrix, Dyn, SymmetricEigen};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+pub struct CgOptions {
+    // stop once the residual norm drops below tol * |b|
+    pub tol: f64,
+    pub max_iter: usize,
+}
+
+impl Default for CgOptions {
+    fn default() -> Self {
+        Self {
+            tol: 1e-10,
+            max_iter: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CgSolution {
+    pub x: Vec<f64>,
+    pub iterations: usize,
+    pub residual: f64,
+    pub converged: bool,
+}
+
+pub(crate) fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Conjugate gradient for a symmetric positive definite operator given as
+/// `apply(x, y)` writing `y = A x`. `diag`, if given, is the diagonal of `A`
+/// used as a Jacobi preconditioner.
+pub fn conjugate_gradient<F>(
+    apply: F,
+    b: &[f64],
+    diag: Option<&[f64]>,
+    options: &CgOptions,
+) -> CgSolution
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let precondition = |r: &[f64], z: &mut [f64]| match diag {
+        Some(d) => {
+            for ((z, r), d) in z.iter_mut().zip(r).zip(d) {
+                *z = if *d != 0.0 { r / d } else { *r };
+            }
+        }
+        None => z.copy_from_slice(r),
+    };
+    preconditioned_conjugate_gradient(apply, precondition, b, options)
+}
+
+/// Conjugate gradient with an arbitrary symmetric positive definite
+/// preconditioner `precondition(r, z)` writing `z = M^{-1} r`.
+pub fn preconditioned_conjugate_gradient<F, P>(
+    apply: F,
+    mut precondition: P,
+    b: &[f64],
+    options: &CgOptions,
+) -> CgSolution
+where
+    F: Fn(&[f64], &mut [f64]),
+    P: FnMut(&[f64], &mut [f64]),
+{
+    let n = b.len();
+    let threshold = options.tol * dot(b, b).sqrt();
+    let mut x = vec![0.0; n];
+    let mut r = b.to_vec();
+    let mut z = vec![0.0; n];
+    let mut ap = vec![0.0; n];
+    precondition(&r, &mut z);
+    let mut p = z.clone();
+    let mut rz = dot(&r, &z);
+    let mut residual = dot(&r, &r).sqrt();
+    let mut iterations = 0;
+    while residual > threshold && iterations < options.max_iter {
+        apply(&p, &mut ap);
+        let pap = dot(&p, &ap);
+        if pap <= 0.0 {
+            break;
+        }
+        let alpha = rz / pap;
+        for i in 0..n {
+            x[i] += alpha * p[i];
+            r[i] -= alpha * ap[i];
+        }
+        residual = dot(&r, &r).sqrt();
+        iterations += 1;
+        precondition(&r, &mut z);
+        let rz_next = dot(&r, &z);
+        let beta = rz_next / rz;
+        rz = rz_next;
+        for i in 0..n {
+            p[i] = z[i] + beta * p[i];
+        }
+    }
+    CgSolution {
+        x,
+        iterations,
+        converged: residual <= threshold,
+        residual,
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Lanczos {
+    // diagonal and off-diagonal of the tridiagonal matrix T
+    pub alpha: Vec<f64>,
+    pub beta: Vec<f64>,
+    // orthonormal Krylov basis, one vector per step
+    pub basis: Vec<Vec<f64>>,
+    pub start_norm: f64,
+}
+
+/// `steps` iterations of the Lanczos process on a symmetric operator from
+/// `v0`, with full reorthogonalization. Stops early once the Krylov space
+/// becomes invariant.
+pub fn lanczos<F>(apply: F, v0: &[f64], steps: usize) -> Lanczos
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    lanczos_deflated(apply, v0, steps, &[])
+}
+
+// `lanczos` kept orthogonal to the orthonormal vectors `locked` as well,
+// which must span an invariant subspace
+fn lanczos_deflated<F>(apply: F, v0: &[f64], steps: usize, locked: &[Vec<f64>]) -> Lanczos
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let n = v0.len();
+    let start_norm = dot(v0, v0).sqrt();
+    let mut alpha = Vec::with_capacity(steps);
+    let mut beta: Vec<f64> = Vec::with_capacity(steps);
+    let mut basis: Vec<Vec<f64>> = Vec::with_capacity(steps);
+    if start_norm == 0.0 {
+        return Lanczos {
+            alpha,
+            beta,
+            basis,
+            start_norm,
+        };
+    }
+    let mut q: Vec<f64> = v0.iter().map(|x| x / start_norm).collect();
+    let mut w = vec![0.0; n];
+    for _ in 0..steps {
+        apply(&q, &mut w);
+        let a = dot(&q, &w);
+        basis.push(q);
+        // twice, as one pass leaves noise behind once the space is close to
+        // invariant
+        for _ in 0..2 {
+            for b in locked.iter().chain(&basis) {
+                let c = dot(b, &w);
+                w.iter_mut().zip(b).for_each(|(x, y)| *x -= c * y);
+            }
+        }
+        alpha.push(a);
+        let norm = dot(&w, &w).sqrt();
+        if norm < 1e-12 * (1.0 + a.abs()) || basis.len() + locked.len() >= n {
+            break;
+        }
+        beta.push(norm);
+        q = w.iter().map(|x| x / norm).collect();
+    }
+    beta.truncate(alpha.len().saturating_sub(1));
+    Lanczos {
+        alpha,
+        beta,
+        basis,
+        start_norm,
+    }
+}
+
+/// Settings of `extreme_eigenpairs`.
+pub struct EigenOptions {
+    // Lanczos steps between restarts
+    pub steps: usize,
+    pub max_restarts: usize,
+    // stop once the residual norm drops below tol * max(1, |λ|)
+    pub tol: f64,
+    pub seed: u64,
+}
+
+impl Default for EigenOptions {
+    fn default() -> Self {
+        Self {
+            steps: 80,
+            max_restarts: 500,
+            tol: 1e-8,
+            seed: 0,
+        }
+    }
+}
+
+/// The `k` largest (or smallest) eigenpairs of a symmetric operator on
+/// `R^n`, most extreme first, by explicitly restarted Lanczos. Pairs are
+/// found one at a time and locked, later runs staying orthogonal to them,
+/// so repeated eigenvalues come out with their multiplicity. Fails when a
+/// pair is still above the residual tolerance after `max_restarts`.
+pub fn extreme_eigenpairs<F>(
+    apply: F,
+    n: usize,
+    k: usize,
+    largest: bool,
+    options: &EigenOptions,
+) -> Result<Vec<(f64, Vec<f64>)>, String>
+where
+    F: Fn(&[f64], &mut [f64]),
+{
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut locked: Vec<Vec<f64>> = Vec::new();
+    let mut pairs = Vec::with_capacity(k);
+    while pairs.len() < k.min(n) {
+        let mut v: Vec<f64> = (0..n).map(|_| rng.gen_range(-1.0..1.0)).collect();
+        let mut best = (0.0, Vec::new());
+        let mut residual = 0.0;
+        for _ in 0..=options.max_restarts {
+            orthogonalize(&mut v, &locked);
+            let run = lanczos_deflated(&apply, &v, options.steps, &locked);
+            if run.alpha.is_empty() {
+                break;
+            }
+            let eig = run.eigen();
+            let m = run.alpha.len();
+            let i = (0..m)
+                .max_by(|&i, &j| {
+                    let (a, b) = (eig.eigenvalues[i], eig.eigenvalues[j]);
+                    if largest {
+                        a.total_cmp(&b)
+                    } else {
+                        b.total_cmp(&a)
+                    }
+                })
+                .unwrap();
+            let theta = eig.eigenvalues[i];
+            let mut x = vec![0.0; n];
+            for (j, q) in run.basis.iter().enumerate() {
+                let c = eig.eigenvectors[(j, i)];
+                x.iter_mut().zip(q).for_each(|(a, b)| *a += c * b);
+            }
+            // with an invariant Krylov space the Ritz pair is exact
+            residual = if m < options.steps {
+                0.0
+            } else {
+                let mut ax = vec![0.0; n];
+                apply(&x, &mut ax);
+                ax.iter()
+                    .zip(&x)
+                    .map(|(a, b)| (a - theta * b).powi(2))
+                    .sum::<f64>()
+                    .sqrt()
+            };
+            best = (theta, x.clone());
+            if residual <= options.tol * theta.abs().max(1.0) {
+                break;
+            }
+            v = x;
+        }
+        let (theta, mut x) = best;
+        if x.is_empty() {
+            break;
+        }
+        if residual > options.tol * theta.abs().max(1.0) {
+            return Err(format!(
+                "Lanczos did not converge for eigenpair {} after {} restarts (residual {:e})",
+                pairs.len() + 1,
+                options.max_restarts,
+                residual
+            ));
+        }
+        orthogonalize(&mut x, &locked);
+        let norm = dot(&x, &x).sqrt();
+        x.iter_mut().for_each(|a| *a /= norm);
+        locked.push(x.clone());
+        pairs.push((theta, x));
+    }
+    Ok(pairs)
+}
+
+fn orthogonalize(v: &mut [f64], against: &[Vec<f64>]) {
+    for b in against {
+        let c = dot(b, v);
+        v.iter_mut().zip(b).for_each(|(x, y)| *x -= c * y);
+    }
+}
+
+impl Lanczos {
+    fn eigen(&self) -> SymmetricEigen<f64, Dyn> {
+        let k = self.alpha.len();
+        let t = DMatrix::from_fn(k, k, |i, j| {
+            if i == j {
+                self.alpha[i]
+            } else if i == j + 1 {
+                self.beta[j]
+            } else if j == i + 1 {
+                self.beta[i]
+            } else {
+                0.0
+            }
+        });
+        t.symmetric_eigen()
+    }
+    /// Gauss quadrature estimate of `v0^T f(A) v0`.
+    pub fn quadrature<F: Fn(f64) -> f64>(&self, f: F) -> f64 {
+        if self.alpha.is_empty() {
+            return 0.0;
+        }
+        let eig = self.eigen();
+        let total: f64 = (0..self.alpha.len())
+            .map(|i| eig.eigenvectors[(0, i)].powi(2) * f(eig.eigenvalues[i]))
+            .sum();
+        total * self.start_norm * self.start_norm
+    }
+    /// Krylov approximation `|v0| Q f(T) e_1` of `f(A) v0`.
+    pub fn function_action<F: Fn(f64) -> f64>(&self, f: F) -> Vec<f64> {
+        let n = self.basis.first().map_or(0, |q| q.len());
+        let mut out = vec![0.0; n];
+        if self.alpha.is_empty() {
+            return out;
+        }
+        let eig = self.eigen();
+        let k = self.alpha.len();
+        // coefficients f(T) e_1 in the Krylov basis
+        let coef: Vec<f64> = (0..k)
+            .map(|j| {
+                (0..k)
+                    .map(|i| {
+                        eig.eigenvectors[(j, i)] * f(eig.eigenvalues[i]) * eig.eigenvectors[(0, i)]
+                    })
+                    .sum::<f64>()
+                    * self.start_norm
+            })
+            .collect();
+        for (c, q) in coef.iter().zip(&self.basis) {
+            out.iter_mut().zip(q).for_each(|(x, y)| *x += c * y);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::DVector;
+
+    #[test]
+    fn test_conjugate_gradient() {
+        let a = DMatrix::from_row_slice(3, 3, &[4.0, 1.0, 0.0, 1.0, 3.0, 1.0, 0.0, 1.0, 5.0]);
+        let b = [1.0, 2.0, 3.0];
+        let apply = |x: &[f64], y: &mut [f64]| {
+            y.copy_from_slice((&a * DVector::from_column_slice(x)).as_slice())
+        };
+        let expected = a
+            .clone()
+            .lu()
+            .solve(&DVector::from_column_slice(&b))
+            .unwrap();
+        for diag in [None, Some(&[4.0, 3.0, 5.0][..])] {
+            let sol = conjugate_gradient(apply, &b, diag, &CgOptions::default());
+            assert!(sol.converged);
+            assert!(sol.iterations <= 3);
+            for (x, e) in sol.x.iter().zip(expected.iter()) {
+                assert!((x - e).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lanczos() {
+        let a = DMatrix::from_row_slice(3, 3, &[2.0, 1.0, 0.0, 1.0, 2.0, 1.0, 0.0, 1.0, 2.0]);
+        let apply = |x: &[f64], y: &mut [f64]| {
+            y.copy_from_slice((&a * DVector::from_column_slice(x)).as_slice())
+        };
+        let v = [1.0, 0.0, 2.0];
+        let run = lanczos(apply, &v, 10);
+        assert_eq!(run.alpha.len(), 3);
+        let exp_a = a.clone().exp();
+        let expected = &exp_a * DVector::from_column_slice(&v);
+        for (x, e) in run.function_action(f64::exp).iter().zip(expected.iter()) {
+            assert!((x - e).abs() < 1e-9);
+        }
+        let quad = expected.dot(&DVector::from_column_slice(&v));
+        assert!((run.quadrature(f64::exp) - quad).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_extreme_eigenpairs() {
+        // diag(3, 3, 1, 0, 0, -2): repeated extremes keep their multiplicity
+        let d = [3.0, 1.0, 0.0, 3.0, -2.0, 0.0];
+        let apply = |x: &[f64], y: &mut [f64]| {
+            y.iter_mut()
+                .zip(x)
+                .zip(&d)
+                .for_each(|((y, x), d)| *y = d * x)
+        };
+        let options = EigenOptions {
+            steps: 3,
+            ..Default::default()
+        };
+        let top: Vec<f64> = extreme_eigenpairs(apply, 6, 3, true, &options)
+            .unwrap()
+            .into_iter()
+            .map(|(x, _)| x)
+            .collect();
+        let bottom: Vec<f64> = extreme_eigenpairs(apply, 6, 3, false, &options)
+            .unwrap()
+            .into_iter()
+            .map(|(x, _)| x)
+            .collect();
+        for (got, want) in top
+            .iter()
+            .chain(&bottom)
+            .zip([3.0, 3.0, 1.0, -2.0, 0.0, 0.0])
+        {
+            assert!((got - want).abs() < 1e-7);
+        }
+        // a two-step Krylov space without restarts cannot resolve the top pair
+        let hurried = EigenOptions {
+            steps: 2,
+            max_restarts: 0,
+            ..Default::default()
+        };
+        assert!(extreme_eigenpairs(apply, 6, 1, true, &hurried).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+use nalgebra::DMatrix;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+
+use super::dsu::DSU;
+use super::linalg::{conjugate_gradient, extreme_eigenpairs, CgOptions, EigenOptions};
+use super::normal_graph::NormalUndiGraph;
+
+pub const DEFAULT_SIMRANK_MAX_NODES: usize = 2000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LinkScore {
+    CommonNeighbors,
+    Jaccard,
+    AdamicAdar,
+    ResourceAllocation,
+}
+
+pub struct EdgeSplit {
+    pub train: NormalUndiGraph,
+    // held-out edges, removed from the training graph
+    pub test_pos: Vec<(usize, usize)>,
+    // non-edges of the original graph, as many as there are positives
+    pub test_neg: Vec<(usize, usize)>,
+}
+
+/// Probability that a random positive pair outscores a random negative pair,
+/// counting ties as one half (the Mann-Whitney statistic).
+pub fn auc(pos: &[f64], neg: &[f64]) -> f64 {
+    if pos.is_empty() || neg.is_empty() {
+        return 0.5;
+    }
+    let mut sorted = neg.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let mut total = 0.0;
+    for &s in pos {
+        let below = sorted.partition_point(|&x| x < s);
+        let ties = sorted.partition_point(|&x| x <= s) - below;
+        total += below as f64 + 0.5 * ties as f64;
+    }
+    total / (pos.len() * neg.len()) as f64
+}
+
+impl NormalUndiGraph {
+    fn common_neighbors(&self, u: usize, v: usize) -> Vec<usize> {
+        let (a, b) = (&self.adjs[u], &self.adjs[v]);
+        let (mut i, mut j) = (0, 0);
+        let mut common = Vec::new();
+        while i < a.len() && j < b.len() {
+            match a[i].cmp(&b[j]) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    common.push(a[i]);
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        common
+    }
+    /// Similarity of `u` and `v` by the neighbourhood index `kind`; a node
+    /// paired with itself scores 0, as it is never a candidate link.
+    pub fn link_score(&self, u: usize, v: usize, kind: LinkScore) -> f64 {
+        if u == v {
+            return 0.0;
+        }
+        let common = self.common_neighbors(u, v);
+        match kind {
+            LinkScore::CommonNeighbors => common.len() as f64,
+            LinkScore::Jaccard => {
+                let union = self.adjs[u].len() + self.adjs[v].len() - common.len();
+                if union == 0 {
+                    0.0
+                } else {
+                    common.len() as f64 / union as f64
+                }
+            }
+            // common neighbours have degree at least 2, so the logarithm is positive
+            LinkScore::AdamicAdar => common
+                .iter()
+                .map(|&w| 1.0 / (self.adjs[w].len() as f64).ln())
+                .sum(),
+            LinkScore::ResourceAllocation => common
+                .iter()
+                .map(|&w| 1.0 / self.adjs[w].len() as f64)
+                .sum(),
+        }
+    }
+    pub fn link_scores(&self, pairs: &[(usize, usize)], kind: LinkScore) -> Vec<f64> {
+        pairs
+            .iter()
+            .map(|&(u, v)| self.link_score(u, v, kind))
+            .collect()
+    }
+    /// All non-adjacent pairs `u < v` whose endpoints both have degree at most
+    /// `max_degree`. Quadratic in the number of eligible nodes.
+    pub fn candidate_non_edges(&self, max_degree: usize) -> Vec<(usize, usize)> {
+        let eligible: Vec<usize> = (0..self.n)
+            .filter(|&u| self.adjs[u].len() <= max_degree)
+            .collect();
+        let mut pairs = Vec::new();
+        for (i, &u) in eligible.iter().enumerate() {
+            for &v in &eligible[i + 1..] {
+                if self.adjs[u].binary_search(&v).is_err() {
+                    pairs.push((u, v));
+                }
+            }
+        }
+        pairs
+    }
+    /// Holds out a random `test_frac` of the edges together with an equal
+    /// number of sampled non-edges. With `keep_connected`, the edges of a
+    /// random spanning forest are never held out, so the training graph keeps
+    /// the components of the original graph and fewer edges may be held out.
+    pub fn split_edges(&self, test_frac: f64, keep_connected: bool, seed: u64) -> EdgeSplit {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges: Vec<(usize, usize)> = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            edges.extend(adj.iter().filter(|&&v| u < v).map(|&v| (u, v)));
+        }
+        edges.shuffle(&mut rng);
+        let target = ((test_frac.clamp(0.0, 1.0) * self.m as f64).round() as usize).min(self.m);
+        let mut train = Vec::with_capacity(self.m);
+        let mut test_pos = Vec::with_capacity(target);
+        if keep_connected {
+            let mut dsu: DSU<usize> = DSU::new();
+            let mut rest = Vec::new();
+            for (u, v) in edges {
+                if dsu.union(u, v) {
+                    train.push((u, v));
+                } else {
+                    rest.push((u, v));
+                }
+            }
+            edges = rest;
+        }
+        for (i, e) in edges.into_iter().enumerate() {
+            if i < target {
+                test_pos.push(e);
+            } else {
+                train.push(e);
+            }
+        }
+        test_pos.sort_unstable();
+        let max_neg = (self.n * self.n.saturating_sub(1) / 2 - self.m).min(test_pos.len());
+        let mut seen = HashSet::with_capacity(max_neg);
+        while seen.len() < max_neg {
+            let (u, v) = (rng.gen_range(0..self.n), rng.gen_range(0..self.n));
+            if u != v && self.adjs[u].binary_search(&v).is_err() {
+                seen.insert((u.min(v), u.max(v)));
+            }
+        }
+        let mut test_neg: Vec<(usize, usize)> = seen.into_iter().collect();
+        test_neg.sort_unstable();
+        EdgeSplit {
+            train: NormalUndiGraph::from_edges(format!("{}-train", self.name), self.n, &train),
+            test_pos,
+            test_neg,
+        }
+    }
+    /// Dense SimRank by `iterations` rounds of `S = c W^T S W` with the
+    /// diagonal reset to 1, where `W` is the column-normalized adjacency.
+    /// Quadratic memory, hence the node cap; see `simrank_pair` for large graphs.
+    pub fn simrank(
+        &self,
+        c: f64,
+        iterations: usize,
+        max_nodes: usize,
+    ) -> Result<DMatrix<f64>, String> {
+        if self.n > max_nodes {
+            return Err(format!(
+                "Graph '{}' has {} nodes, exceeding the dense SimRank cap of {}",
+                self.name, self.n, max_nodes
+            ));
+        }
+        let w = self.normalized_adjacency();
+        let mut s = DMatrix::identity(self.n, self.n);
+        for _ in 0..iterations {
+            s = w.transpose() * &s * &w * c;
+            s.fill_diagonal(1.0);
+        }
+        Ok(s)
+    }
+    /// SimRank of a single pair, as the expected `c^k` over the first meeting
+    /// time `k <= iterations` of two random walks started at `u` and `v`. Only
+    /// the pairs reachable from `(u, v)` are ever stored.
+    pub fn simrank_pair(&self, u: usize, v: usize, c: f64, iterations: usize) -> f64 {
+        if u == v {
+            return 1.0;
+        }
+        let mut dist: HashMap<(usize, usize), f64> = HashMap::from([((u.min(v), u.max(v)), 1.0)]);
+        let mut score = 0.0;
+        let mut decay = 1.0;
+        for _ in 0..iterations {
+            decay *= c;
+            let mut next: HashMap<(usize, usize), f64> = HashMap::with_capacity(dist.len());
+            for ((a, b), p) in dist {
+                let (da, db) = (self.adjs[a].len(), self.adjs[b].len());
+                if da == 0 || db == 0 {
+                    continue;
+                }
+                let q = p / (da * db) as f64;
+                for &x in &self.adjs[a] {
+                    for &y in &self.adjs[b] {
+                        if x == y {
+                            score += decay * q;
+                        } else {
+                            *next.entry((x.min(y), x.max(y))).or_insert(0.0) += q;
+                        }
+                    }
+                }
+            }
+            dist = next;
+        }
+        score
+    }
+    // Katz sums converge only for `0 < beta < 1 / lambda_max`; `d_max`
+    // bounds `lambda_max` from above, so Lanczos runs only when that bound
+    // is not enough.
+    fn check_katz_beta(&self, beta: f64) -> Result<(), String> {
+        if !(beta.is_finite() && beta > 0.0) {
+            return Err(format!(
+                "Katz beta must be positive and finite, got {}",
+                beta
+            ));
+        }
+        let d_max = self.adjs.iter().map(|adj| adj.len()).max().unwrap_or(0);
+        if beta * (d_max as f64) < 1.0 {
+            return Ok(());
+        }
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        let lambda_max = extreme_eigenpairs(apply, self.n, 1, true, &EigenOptions::default())?
+            .first()
+            .map_or(0.0, |&(lambda, _)| lambda);
+        if beta * lambda_max >= 1.0 {
+            return Err(format!(
+                "Katz beta = {} is not below 1 / lambda_max = {} on graph '{}'",
+                beta,
+                1.0 / lambda_max,
+                self.name
+            ));
+        }
+        Ok(())
+    }
+    // `(I - beta A)^{-1} e_v - e_v` by CG, for a checked `beta`.
+    fn katz_solve(&self, v: usize, beta: f64) -> Result<Vec<f64>, String> {
+        let mut b = vec![0.0; self.n];
+        b[v] = 1.0;
+        let apply = |x: &[f64], y: &mut [f64]| {
+            for (u, adj) in self.adjs.iter().enumerate() {
+                y[u] = x[u] - beta * adj.iter().map(|&w| x[w]).sum::<f64>();
+            }
+        };
+        let sol = conjugate_gradient(apply, &b, None, &CgOptions::default());
+        if !sol.converged {
+            return Err(format!(
+                "Katz solve on graph '{}' did not converge for beta = {}",
+                self.name, beta
+            ));
+        }
+        let mut x = sol.x;
+        x[v] -= 1.0;
+        Ok(x)
+    }
+    /// Katz scores `sum_k beta^k (A^k)_{uv}` of `v` against every node, from
+    /// one CG solve of `(I - beta A) x = e_v`. Fails unless
+    /// `0 < beta < 1 / lambda_max`.
+    pub fn katz_column(&self, v: usize, beta: f64) -> Result<Vec<f64>, String> {
+        self.check_katz_beta(beta)?;
+        self.katz_solve(v, beta)
+    }
+    pub fn katz_index(&self, u: usize, v: usize, beta: f64) -> Result<f64, String> {
+        Ok(self.katz_column(v, beta)?[u])
+    }
+    /// Katz scores of many pairs, with one solve per distinct second endpoint.
+    pub fn katz_scores(&self, pairs: &[(usize, usize)], beta: f64) -> Result<Vec<f64>, String> {
+        self.check_katz_beta(beta)?;
+        let mut columns: HashMap<usize, Vec<f64>> = HashMap::new();
+        let mut scores = Vec::with_capacity(pairs.len());
+        for &(u, v) in pairs {
+            let column = match columns.entry(v) {
+                Entry::Occupied(e) => e.into_mut(),
+                Entry::Vacant(e) => e.insert(self.katz_solve(v, beta)?),
+            };
+            scores.push(column[u]);
+        }
+        Ok(scores)
+    }
+    /// AUC of `kind` at separating held-out edges from non-edges.
+    pub fn link_auc(
+        &self,
+        test_pos: &[(usize, usize)],
+        test_neg: &[(usize, usize)],
+        kind: LinkScore,
+    ) -> f64 {
+        auc(
+            &self.link_scores(test_pos, kind),
+            &self.link_scores(test_neg, kind),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_score() {
+        // 0 and 1 share neighbours 2 (degree 2) and 3 (degree 3)
+        let g = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            5,
+            &[(0, 2), (1, 2), (0, 3), (1, 3), (3, 4), (1, 4)],
+        );
+        assert_eq!(g.link_score(0, 1, LinkScore::CommonNeighbors), 2.0);
+        assert_eq!(g.link_score(0, 1, LinkScore::Jaccard), 2.0 / 3.0);
+        let aa = 1.0 / 2f64.ln() + 1.0 / 3f64.ln();
+        assert!((g.link_score(0, 1, LinkScore::AdamicAdar) - aa).abs() < 1e-12);
+        assert!((g.link_score(0, 1, LinkScore::ResourceAllocation) - 5.0 / 6.0).abs() < 1e-12);
+        assert_eq!(
+            g.link_scores(&[(0, 1), (2, 4)], LinkScore::CommonNeighbors),
+            vec![2.0, 1.0]
+        );
+        assert_eq!(g.candidate_non_edges(2), vec![(0, 4), (2, 4)]);
+        // the leaves of node 0 have degree 1, where Adamic-Adar is undefined
+        let star = NormalUndiGraph::from_edges("star".to_string(), 3, &[(0, 1), (0, 2)]);
+        assert_eq!(star.link_score(0, 0, LinkScore::AdamicAdar), 0.0);
+        assert_eq!(star.link_score(1, 1, LinkScore::CommonNeighbors), 0.0);
+    }
+
+    #[test]
+    fn test_split_edges() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let split = g.split_edges(0.2, true, 5);
+        assert_eq!(split.test_pos.len(), (0.2 * g.m as f64).round() as usize);
+        assert_eq!(split.test_neg.len(), split.test_pos.len());
+        assert_eq!(split.train.m + split.test_pos.len(), g.m);
+        assert!(split
+            .train
+            .distances_from(0)
+            .iter()
+            .all(|&d| d != usize::MAX));
+        for &(u, v) in &split.test_pos {
+            assert!(g.adjs[u].binary_search(&v).is_ok());
+            assert!(split.train.adjs[u].binary_search(&v).is_err());
+        }
+        assert!(split
+            .test_neg
+            .iter()
+            .all(|&(u, v)| u < v && g.adjs[u].binary_search(&v).is_err()));
+        // triangle closure is a strong signal on Apollonian networks
+        let auc = split.train.link_auc(
+            &split.test_pos,
+            &split.test_neg,
+            LinkScore::ResourceAllocation,
+        );
+        assert!(auc > 0.7);
+        // a tree has no removable edges when connectivity is kept
+        let path = NormalUndiGraph::from_edges("P3".to_string(), 3, &[(0, 1), (1, 2)]);
+        assert!(path.split_edges(0.5, true, 0).test_pos.is_empty());
+        assert_eq!(path.split_edges(0.5, false, 0).test_pos.len(), 1);
+    }
+
+    #[test]
+    fn test_simrank() {
+        let g = NormalUndiGraph::from_koch(1);
+        let s = g.simrank(0.8, 6, DEFAULT_SIMRANK_MAX_NODES).unwrap();
+        for u in 0..g.n {
+            assert_eq!(s[(u, u)], 1.0);
+            for v in 0..g.n {
+                assert!((s[(u, v)] - s[(v, u)]).abs() < 1e-12);
+                assert!((s[(u, v)] - g.simrank_pair(u, v, 0.8, 6)).abs() < 1e-12);
+            }
+        }
+        // two leaves of a star meet after one step
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        assert!((star.simrank_pair(1, 2, 0.8, 10) - 0.8).abs() < 1e-12);
+        assert!(g.simrank(0.8, 1, 3).is_err());
+    }
+
+    #[test]
+    fn test_katz() {
+        let g = NormalUndiGraph::from_apollo(1);
+        let beta = 0.1;
+        let a = DMatrix::from_fn(g.n, g.n, |u, v| {
+            g.adjs[u].binary_search(&v).is_ok() as u8 as f64
+        });
+        let inv = (DMatrix::identity(g.n, g.n) - a * beta)
+            .try_inverse()
+            .unwrap();
+        let pairs: Vec<(usize, usize)> = vec![(0, 1), (4, 5), (5, 4), (2, 6)];
+        let scores = g.katz_scores(&pairs, beta).unwrap();
+        for (&(u, v), s) in pairs.iter().zip(&scores) {
+            assert!((inv[(u, v)] - s).abs() < 1e-8);
+            assert!((g.katz_index(u, v, beta).unwrap() - s).abs() < 1e-12);
+        }
+        assert!((scores[1] - scores[2]).abs() < 1e-8);
+        assert!(g.katz_index(0, 1, 1.0).is_err());
+        // the star K1,4 has lambda_max = 2 < d_max = 4
+        let star =
+            NormalUndiGraph::from_edges("s".to_string(), 5, &[(0, 1), (0, 2), (0, 3), (0, 4)]);
+        assert!(star.katz_column(0, 0.45).is_ok());
+        assert!(star.katz_column(0, 0.55).is_err());
+        assert!(star.katz_scores(&[(1, 2)], -0.1).is_err());
+        assert!(star.katz_column(0, f64::NAN).is_err());
+    }
+
+    #[test]
+    fn test_auc() {
+        assert_eq!(auc(&[3.0, 4.0], &[1.0, 2.0]), 1.0);
+        assert_eq!(auc(&[1.0], &[2.0]), 0.0);
+        assert_eq!(auc(&[1.0, 2.0], &[1.0, 2.0]), 0.5);
+    }
+}
@@ -0,0 +1,255 @@
+use nalgebra::DMatrix;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::linalg::lanczos;
+use super::normal_graph::NormalUndiGraph;
+use super::spectral::SpectrumMatrix;
+
+pub struct MatrixFunctionOptions {
+    // graphs up to this size use a dense eigendecomposition
+    pub dense_max_nodes: usize,
+    pub lanczos_steps: usize,
+    // Rademacher probes for stochastic trace estimates
+    pub probes: usize,
+    pub seed: u64,
+}
+
+impl Default for MatrixFunctionOptions {
+    fn default() -> Self {
+        Self {
+            dense_max_nodes: 2000,
+            lanczos_steps: 30,
+            probes: 100,
+            seed: 0,
+        }
+    }
+}
+
+/// Dense eigendecomposition `A = Q diag(lambda) Q^T` kept for reading any
+/// number of entries of `exp(A)` in O(n) each.
+pub struct Communicability {
+    eigenvectors: DMatrix<f64>,
+    exp_eigenvalues: Vec<f64>,
+}
+
+impl Communicability {
+    /// `exp(A)_{uv}`.
+    pub fn get(&self, u: usize, v: usize) -> f64 {
+        self.exp_eigenvalues
+            .iter()
+            .enumerate()
+            .map(|(i, w)| self.eigenvectors[(u, i)] * self.eigenvectors[(v, i)] * w)
+            .sum()
+    }
+    /// Column `exp(A) e_v`.
+    pub fn column(&self, v: usize) -> Vec<f64> {
+        (0..self.eigenvectors.nrows())
+            .map(|u| self.get(u, v))
+            .collect()
+    }
+    /// `sqrt(G_uu + G_vv - 2 G_uv)` with `G = exp(A)`.
+    pub fn distance(&self, u: usize, v: usize) -> f64 {
+        (self.get(u, u) + self.get(v, v) - 2.0 * self.get(u, v))
+            .max(0.0)
+            .sqrt()
+    }
+}
+
+impl NormalUndiGraph {
+    /// One dense eigendecomposition for batches of `communicability` and
+    /// `communicability_distance` queries.
+    pub fn communicability_matrix(&self) -> Communicability {
+        let eig = self
+            .spectrum_matrix(SpectrumMatrix::Adjacency)
+            .symmetric_eigen();
+        Communicability {
+            exp_eigenvalues: eig.eigenvalues.iter().map(|l| l.exp()).collect(),
+            eigenvectors: eig.eigenvectors,
+        }
+    }
+    pub(crate) fn adjacency_apply(&self, x: &[f64], y: &mut [f64]) {
+        for (u, adj) in self.adjs.iter().enumerate() {
+            y[u] = adj.iter().map(|&v| x[v]).sum();
+        }
+    }
+    /// Subgraph centrality `exp(A)_{uu}`, the weighted count of closed walks
+    /// through each node. Large graphs use Lanczos quadrature started at `e_u`.
+    pub fn subgraph_centrality(&self, options: &MatrixFunctionOptions) -> Vec<f64> {
+        if self.n <= options.dense_max_nodes {
+            let eig = self
+                .spectrum_matrix(SpectrumMatrix::Adjacency)
+                .symmetric_eigen();
+            return (0..self.n)
+                .map(|u| {
+                    eig.eigenvalues
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &l)| eig.eigenvectors[(u, i)].powi(2) * l.exp())
+                        .sum()
+                })
+                .collect();
+        }
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        let mut e = vec![0.0; self.n];
+        (0..self.n)
+            .map(|u| {
+                e[u] = 1.0;
+                let run = lanczos(apply, &e, options.lanczos_steps);
+                e[u] = 0.0;
+                run.quadrature(f64::exp)
+            })
+            .collect()
+    }
+    /// Column `exp(A) e_v`, by a dense eigendecomposition or a Krylov
+    /// approximation of the matrix exponential action.
+    pub fn communicability_column(&self, v: usize, options: &MatrixFunctionOptions) -> Vec<f64> {
+        if self.n <= options.dense_max_nodes {
+            return self.communicability_matrix().column(v);
+        }
+        let mut e = vec![0.0; self.n];
+        e[v] = 1.0;
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        lanczos(apply, &e, options.lanczos_steps).function_action(f64::exp)
+    }
+    /// Communicability `exp(A)_{uv}`, the walks between `u` and `v` weighted
+    /// by `1 / k!` for length `k`.
+    pub fn communicability(&self, u: usize, v: usize, options: &MatrixFunctionOptions) -> f64 {
+        if self.n <= options.dense_max_nodes {
+            return self.communicability_matrix().get(u, v);
+        }
+        self.communicability_column(v, options)[u]
+    }
+    /// Communicability distance `sqrt(G_uu + G_vv - 2 G_uv)` with `G = exp(A)`,
+    /// from one eigendecomposition on small graphs; see
+    /// `communicability_matrix` for many pairs.
+    pub fn communicability_distance(
+        &self,
+        u: usize,
+        v: usize,
+        options: &MatrixFunctionOptions,
+    ) -> f64 {
+        if self.n <= options.dense_max_nodes {
+            return self.communicability_matrix().distance(u, v);
+        }
+        let cu = self.communicability_column(u, options);
+        let cv = self.communicability_column(v, options);
+        (cu[u] + cv[v] - 2.0 * cu[v]).max(0.0).sqrt()
+    }
+    /// Total communicability `exp(A) 1` of every node, from a single Krylov
+    /// solve on large graphs.
+    pub fn total_communicability(&self, options: &MatrixFunctionOptions) -> Vec<f64> {
+        if self.n <= options.dense_max_nodes {
+            let eig = self
+                .spectrum_matrix(SpectrumMatrix::Adjacency)
+                .symmetric_eigen();
+            let weights: Vec<f64> = eig
+                .eigenvalues
+                .iter()
+                .enumerate()
+                .map(|(i, &l)| eig.eigenvectors.column(i).sum() * l.exp())
+                .collect();
+            return (0..self.n)
+                .map(|u| {
+                    weights
+                        .iter()
+                        .enumerate()
+                        .map(|(i, w)| eig.eigenvectors[(u, i)] * w)
+                        .sum()
+                })
+                .collect();
+        }
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        lanczos(apply, &vec![1.0; self.n], options.lanczos_steps).function_action(f64::exp)
+    }
+    /// Estrada index `tr exp(A) = sum_i exp(lambda_i)`. Large graphs use
+    /// stochastic Lanczos quadrature with Rademacher probes.
+    pub fn estrada_index(&self, options: &MatrixFunctionOptions) -> f64 {
+        if self.n <= options.dense_max_nodes {
+            return self
+                .spectrum_matrix(SpectrumMatrix::Adjacency)
+                .symmetric_eigenvalues()
+                .iter()
+                .map(|l| l.exp())
+                .sum();
+        }
+        let mut rng = StdRng::seed_from_u64(options.seed);
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        let total: f64 = (0..options.probes)
+            .map(|_| {
+                let z: Vec<f64> = (0..self.n)
+                    .map(|_| if rng.gen() { 1.0 } else { -1.0 })
+                    .collect();
+                lanczos(apply, &z, options.lanczos_steps).quadrature(f64::exp)
+            })
+            .sum();
+        total / options.probes.max(1) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estrada_index() {
+        // spectrum of K4 is {3, -1, -1, -1}
+        let k4 = NormalUndiGraph::from_apollo(0);
+        let expected = 3f64.exp() + 3.0 * (-1f64).exp();
+        assert!((k4.estrada_index(&Default::default()) - expected).abs() < 1e-9);
+        let sc = k4.subgraph_centrality(&Default::default());
+        assert!(sc.iter().all(|&x| (x - expected / 4.0).abs() < 1e-9));
+
+        let g = NormalUndiGraph::from_apollo(3);
+        let dense = g.estrada_index(&Default::default());
+        let options = MatrixFunctionOptions {
+            dense_max_nodes: 0,
+            probes: 200,
+            ..Default::default()
+        };
+        assert!((g.estrada_index(&options) - dense).abs() / dense < 0.1);
+    }
+
+    #[test]
+    fn test_subgraph_centrality_lanczos() {
+        let g = NormalUndiGraph::from_koch(2);
+        let dense = g.subgraph_centrality(&Default::default());
+        let lanczos = g.subgraph_centrality(&MatrixFunctionOptions {
+            dense_max_nodes: 0,
+            ..Default::default()
+        });
+        for (a, b) in dense.iter().zip(&lanczos) {
+            assert!((a - b).abs() / a < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_communicability() {
+        let g = NormalUndiGraph::from_koch(2);
+        let dense = MatrixFunctionOptions::default();
+        let krylov = MatrixFunctionOptions {
+            dense_max_nodes: 0,
+            ..Default::default()
+        };
+        let sc = g.subgraph_centrality(&dense);
+        for (u, v) in [(0, 1), (3, 17), (5, 5)] {
+            let a = g.communicability(u, v, &dense);
+            assert!((a - g.communicability(v, u, &krylov)).abs() / a < 1e-8);
+        }
+        assert!((g.communicability(5, 5, &dense) - sc[5]).abs() < 1e-9);
+        assert_eq!(g.communicability_distance(4, 4, &dense), 0.0);
+        let d = g.communicability_distance(0, 9, &dense);
+        let expected = (sc[0] + sc[9] - 2.0 * g.communicability(0, 9, &dense)).sqrt();
+        assert!((d - expected).abs() < 1e-9);
+        let total = g.total_communicability(&dense);
+        let row_sum: f64 = g.communicability_column(2, &dense).iter().sum();
+        assert!((total[2] - row_sum).abs() < 1e-9);
+        for (a, b) in total.iter().zip(g.total_communicability(&krylov)) {
+            assert!((a - b).abs() / a < 1e-8);
+        }
+        let matrix = g.communicability_matrix();
+        assert!((matrix.get(0, 9) - g.communicability(9, 0, &krylov)).abs() < 1e-8);
+        let far = g.communicability_distance(0, 9, &krylov);
+        assert!((matrix.distance(0, 9) - far).abs() < 1e-6);
+    }
+}
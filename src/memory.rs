@@ -0,0 +1,136 @@
+use std::fmt;
+use std::mem::size_of;
+
+use super::general_graph::GeneralUndiGraph;
+use super::normal_graph::NormalUndiGraph;
+use super::weighted_graph::WeightedUndiGraph;
+
+/// Approximate heap bytes held by a graph, split by what they store.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemReport {
+    pub nodes: usize,
+    pub edges: usize,
+    pub adjacency: usize,
+}
+
+impl MemReport {
+    pub fn total(&self) -> usize {
+        self.nodes + self.edges + self.adjacency
+    }
+    /// Footprint of a `GeneralUndiGraph` with `n` nodes and `m` edges.
+    pub fn estimate_general(n: usize, m: usize) -> Self {
+        Self {
+            nodes: hash_set_bytes::<usize>(n),
+            edges: hash_set_bytes::<(usize, usize)>(m),
+            adjacency: 0,
+        }
+    }
+    /// Footprint of a `NormalUndiGraph` with `n` nodes and `m` edges if every
+    /// adjacency list is allocated exactly, a lower bound for built graphs.
+    pub fn estimate_normal(n: usize, m: usize) -> Self {
+        Self {
+            nodes: 0,
+            edges: 2 * m * size_of::<usize>(),
+            adjacency: n * size_of::<Vec<usize>>(),
+        }
+    }
+    pub fn estimate_weighted(n: usize, m: usize) -> Self {
+        Self {
+            nodes: 0,
+            edges: 2 * m * size_of::<(usize, f64)>(),
+            adjacency: n * size_of::<Vec<(usize, f64)>>(),
+        }
+    }
+}
+
+impl fmt::Display for MemReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "nodes: {} B, edges: {} B, adjacency: {} B, total: {} B",
+            self.nodes,
+            self.edges,
+            self.adjacency,
+            self.total()
+        )
+    }
+}
+
+// Bytes of a SwissTable holding `capacity` entries: a power-of-two bucket
+// count at most 7/8 full, one control byte per bucket plus one group.
+fn hash_set_bytes<T>(capacity: usize) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+    let buckets = if capacity < 8 {
+        (capacity + 1).next_power_of_two().max(4)
+    } else {
+        (capacity * 8 / 7).next_power_of_two()
+    };
+    buckets * size_of::<T>() + buckets + 16
+}
+
+impl GeneralUndiGraph {
+    pub fn mem_footprint(&self) -> MemReport {
+        MemReport {
+            nodes: hash_set_bytes::<usize>(self.nodes.capacity()),
+            edges: hash_set_bytes::<(usize, usize)>(self.edges.capacity()),
+            adjacency: 0,
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn mem_footprint(&self) -> MemReport {
+        MemReport {
+            nodes: 0,
+            edges: self
+                .adjs
+                .iter()
+                .map(|adj| adj.capacity() * size_of::<usize>())
+                .sum(),
+            adjacency: self.adjs.capacity() * size_of::<Vec<usize>>(),
+        }
+    }
+}
+
+impl WeightedUndiGraph {
+    pub fn mem_footprint(&self) -> MemReport {
+        MemReport {
+            nodes: 0,
+            edges: self
+                .adjs
+                .iter()
+                .map(|adj| adj.capacity() * size_of::<(usize, f64)>())
+                .sum(),
+            adjacency: self.adjs.capacity() * size_of::<Vec<(usize, f64)>>(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mem_footprint() {
+        let g = NormalUndiGraph::from_pseudofractal(4);
+        let report = g.mem_footprint();
+        assert!(report.edges >= 2 * g.m * size_of::<usize>());
+        assert_eq!(report.adjacency, g.n * size_of::<Vec<usize>>());
+        assert!(report.total() >= MemReport::estimate_normal(g.n, g.m).total());
+
+        let mut general = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in g.edge_list() {
+            general.add_edge(u, v);
+        }
+        let report = general.mem_footprint();
+        let estimate = MemReport::estimate_general(g.n, g.m);
+        assert!(report.edges >= estimate.edges && report.nodes >= estimate.nodes);
+        assert!(report.total() > MemReport::estimate_normal(g.n, g.m).total());
+
+        let w = WeightedUndiGraph::from_normal(&g);
+        assert!(w.mem_footprint().edges >= MemReport::estimate_weighted(g.n, g.m).edges);
+        assert_eq!(MemReport::default().total(), 0);
+    }
+}
@@ -1,11 +1,22 @@
 #![allow(unused_imports)]
 use nalgebra as na;
-use std::collections::HashMap;
+use nalgebra_sparse::CsrMatrix;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
 
+use super::config::ComputeConfig;
 use super::general_graph::GeneralUndiGraph;
+use super::relabel::sorted_ids;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawUndiGraph"))]
 pub struct NormalUndiGraph {
     pub name: String,
     pub n: usize,
@@ -13,6 +24,52 @@ pub struct NormalUndiGraph {
     pub adjs: Vec<Vec<usize>>,
 }
 
+// Deserialized fields, checked before they become a `NormalUndiGraph` so
+// that no algorithm meets out-of-range, unsorted or one-way adjacency.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct RawUndiGraph {
+    name: String,
+    n: usize,
+    m: usize,
+    adjs: Vec<Vec<usize>>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawUndiGraph> for NormalUndiGraph {
+    type Error = String;
+
+    fn try_from(raw: RawUndiGraph) -> Result<Self, String> {
+        let RawUndiGraph { name, n, m, adjs } = raw;
+        if adjs.len() != n {
+            return Err(format!("{} adjacency lists for {} nodes", adjs.len(), n));
+        }
+        for (u, adj) in adjs.iter().enumerate() {
+            if adj.iter().any(|&v| v >= n || v == u) {
+                return Err(format!(
+                    "neighbour of node {} out of range or a self-loop",
+                    u
+                ));
+            }
+            if adj.windows(2).any(|w| w[0] >= w[1]) {
+                return Err(format!("neighbours of node {} not strictly sorted", u));
+            }
+            if let Some(&v) = adj.iter().find(|&&v| adjs[v].binary_search(&u).is_err()) {
+                return Err(format!("edge ({}, {}) missing its reverse", u, v));
+            }
+        }
+        let degree_sum: usize = adjs.iter().map(Vec::len).sum();
+        if degree_sum != 2 * m {
+            return Err(format!(
+                "m is {} but the lists hold {} edges",
+                m,
+                degree_sum / 2
+            ));
+        }
+        Ok(Self { name, n, m, adjs })
+    }
+}
+
 impl fmt::Display for NormalUndiGraph {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -31,6 +88,25 @@ impl fmt::Display for NormalUndiGraph {
 }
 
 impl NormalUndiGraph {
+    pub fn degrees(&self) -> Vec<usize> {
+        self.adjs.iter().map(|adj| adj.len()).collect()
+    }
+    /// BFS hop counts from `source`, `usize::MAX` for unreachable nodes.
+    pub fn distances_from(&self, source: usize) -> Vec<usize> {
+        let mut dist = vec![usize::MAX; self.n];
+        let mut queue = VecDeque::from([source]);
+        dist[source] = 0;
+        while let Some(u) = queue.pop_front() {
+            for &v in &self.adjs[u] {
+                if dist[v] == usize::MAX {
+                    dist[v] = dist[u] + 1;
+                    queue.push_back(v);
+                }
+            }
+        }
+        dist
+    }
+    #[allow(clippy::needless_return)]
     pub fn diag_adj(&self) -> (na::DVector<f64>, na::DMatrix<f64>) {
         let diag_vec: na::DVector<f64> =
             na::DVector::from_iterator(self.n, self.adjs.iter().map(|adj| adj.len() as f64));
@@ -44,7 +120,64 @@ impl NormalUndiGraph {
         }
         return (diag_vec, adj_mat);
     }
+    /// Adjacency matrix in CSR form, storing only the `2m` non-zeros.
+    pub fn sparse_adj(&self) -> CsrMatrix<f64> {
+        let csr = self.to_csr();
+        let values = vec![1.0; csr.neighbors.len()];
+        CsrMatrix::try_from_csr_data(self.n, self.n, csr.offsets, csr.neighbors, values).unwrap()
+    }
+    /// Laplacian `D - A` in CSR form.
+    pub fn sparse_laplacian(&self) -> CsrMatrix<f64> {
+        let mut offsets = Vec::with_capacity(self.n + 1);
+        let mut indices = Vec::with_capacity(2 * self.m + self.n);
+        let mut values = Vec::with_capacity(2 * self.m + self.n);
+        offsets.push(0);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            let i = adj.partition_point(|&v| v < u);
+            indices.extend_from_slice(&adj[..i]);
+            indices.push(u);
+            indices.extend_from_slice(&adj[i..]);
+            values.extend(std::iter::repeat_n(-1.0, i));
+            values.push(adj.len() as f64);
+            values.extend(std::iter::repeat_n(-1.0, adj.len() - i));
+            offsets.push(indices.len());
+        }
+        CsrMatrix::try_from_csr_data(self.n, self.n, offsets, indices, values).unwrap()
+    }
+    pub fn from_edges(name: String, n: usize, edges: &[(usize, usize)]) -> Self {
+        let mut adjs: Vec<Vec<usize>> = vec![Vec::new(); n];
+        for &(u, v) in edges {
+            if u != v {
+                adjs[u].push(v);
+                adjs[v].push(u);
+            }
+        }
+        let mut m = 0;
+        for adj in adjs.iter_mut() {
+            adj.sort_unstable();
+            adj.dedup();
+            m += adj.len();
+        }
+        Self {
+            name,
+            n,
+            m: m / 2,
+            adjs,
+        }
+    }
+    /// Compact relabeling of `g`: ids keep their order and gaps are closed.
+    /// Runs on all cores with the `parallel` feature, else on one thread.
     pub fn from_general(g: &GeneralUndiGraph) -> Self {
+        #[cfg(feature = "parallel")]
+        let config = ComputeConfig::default();
+        #[cfg(not(feature = "parallel"))]
+        let config = ComputeConfig::serial();
+        Self::from_general_with(g, &config)
+    }
+    /// `from_general` with the renumbering, degree counting and filling of
+    /// the edges and the sorting of the adjacency lists spread over the
+    /// threads of `config`.
+    pub fn from_general_with(g: &GeneralUndiGraph, config: &ComputeConfig) -> Self {
         let n = g.num_nodes();
         if n == 0 {
             return Self {
@@ -54,43 +187,67 @@ impl NormalUndiGraph {
                 adjs: Vec::new(),
             };
         }
-        let mut degs = vec![0usize; n];
-        let mut o2n: HashMap<usize, usize> = HashMap::new();
-        let renumber = g.nodes.iter().max().unwrap() + 1 != n;
+        let old_ids = sorted_ids(g);
+        let renumber = old_ids[n - 1] + 1 != n;
+        let mut edges: Vec<(usize, usize)> = g.edges.iter().copied().collect();
+        let block = edges
+            .len()
+            .div_ceil(config.reduction_chunks(edges.len()))
+            .max(1);
+        // renumbered in place, one chunk of edges per task
         if renumber {
-            for &(u, v) in &g.edges {
-                let tot = o2n.len();
-                let &mut new_u = o2n.entry(u).or_insert(tot);
-                let tot = o2n.len();
-                let &mut new_v = o2n.entry(v).or_insert(tot);
-                degs[new_u] += 1;
-                degs[new_v] += 1;
-            }
-        } else {
-            for &(u, v) in &g.edges {
-                degs[u] += 1;
-                degs[v] += 1;
-            }
-        }
-        let mut adjs: Vec<Vec<usize>> = Vec::with_capacity(n);
-        for u in 0..n {
-            adjs.push(Vec::with_capacity(degs[u]));
+            let chunks: Vec<Mutex<&mut [(usize, usize)]>> =
+                edges.chunks_mut(block).map(Mutex::new).collect();
+            config.par_map(chunks.len(), |c| {
+                for (u, v) in chunks[c].lock().unwrap().iter_mut() {
+                    *u = old_ids.binary_search(u).unwrap();
+                    *v = old_ids.binary_search(v).unwrap();
+                }
+            });
         }
-        if renumber {
-            for &(u, v) in &g.edges {
-                let (new_u, new_v) = (o2n[&u], o2n[&v]);
-                adjs[new_u].push(new_v);
-                adjs[new_v].push(new_u);
-            }
-        } else {
-            for &(u, v) in &g.edges {
-                adjs[u].push(v);
-                adjs[v].push(u);
+        let chunks: Vec<&[(usize, usize)]> = edges.chunks(block).collect();
+        // degrees per chunk, turned into each chunk's first slot per node
+        // by a prefix sum over nodes and then chunks
+        let mut slots = config.par_map(chunks.len(), |c| {
+            let mut count = vec![0usize; n];
+            for &(u, v) in chunks[c] {
+                count[u] += 1;
+                count[v] += 1;
             }
-        }
+            count
+        });
+        let mut offsets = vec![0usize; n + 1];
         for u in 0..n {
-            adjs[u].sort_unstable();
+            let mut slot = offsets[u];
+            for count in slots.iter_mut() {
+                let k = count[u];
+                count[u] = slot;
+                slot += k;
+            }
+            offsets[u + 1] = slot;
         }
+        // both directions of every edge bucketed by tail, CSR style; every
+        // chunk writes its own slots
+        let heads: Vec<AtomicUsize> = (0..offsets[n]).map(|_| AtomicUsize::new(0)).collect();
+        let slots: Vec<Mutex<Vec<usize>>> = slots.into_iter().map(Mutex::new).collect();
+        config.par_map(chunks.len(), |c| {
+            let mut next = slots[c].lock().unwrap();
+            for &(u, v) in chunks[c] {
+                heads[next[u]].store(v, Ordering::Relaxed);
+                next[u] += 1;
+                heads[next[v]].store(u, Ordering::Relaxed);
+                next[v] += 1;
+            }
+        });
+        drop(edges);
+        let adjs = config.par_map(n, |u| {
+            let mut adj: Vec<usize> = heads[offsets[u]..offsets[u + 1]]
+                .iter()
+                .map(|head| head.load(Ordering::Relaxed))
+                .collect();
+            adj.sort_unstable();
+            adj
+        });
         Self {
             name: g.name.clone(),
             n,
@@ -187,6 +344,451 @@ impl NormalUndiGraph {
     pub fn from_pseudofractal(g: usize) -> Self {
         Self::_from_pseudo_ext(1, g, format!("Pseudofractal_{}", g))
     }
+    fn clique_edges(n_cliques: usize, clique_size: usize) -> Vec<(usize, usize)> {
+        let mut edges = Vec::with_capacity(n_cliques * clique_size * clique_size / 2);
+        for c in 0..n_cliques {
+            let base = c * clique_size;
+            for u in base..base + clique_size {
+                for v in u + 1..base + clique_size {
+                    edges.push((u, v));
+                }
+            }
+        }
+        edges
+    }
+    /// G(n, p) random graph, skipping over absent pairs with geometric jumps
+    /// (Batagelj and Brandes) in O(n + m).
+    pub fn from_erdos_renyi(n: usize, p: f64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges = Vec::new();
+        if p >= 1.0 {
+            for u in 0..n {
+                edges.extend((u + 1..n).map(|v| (u, v)));
+            }
+        } else if p > 0.0 {
+            let log_q = (-p).ln_1p();
+            // pairs (u, v) with v < u enumerated row by row
+            let (mut u, mut v) = (1usize, 0usize);
+            loop {
+                let r: f64 = 1.0 - rng.gen::<f64>();
+                v = v.saturating_add((r.ln() / log_q).floor() as usize);
+                while u < n && v >= u {
+                    v -= u;
+                    u += 1;
+                }
+                if u >= n {
+                    break;
+                }
+                edges.push((v, u));
+                v += 1;
+            }
+        }
+        Self::from_edges(format!("ErdosRenyi_{}_{}", n, p), n, &edges)
+    }
+    /// Watts-Strogatz small world: a ring where every node is linked to its
+    /// `k / 2` nearest nodes on each side, then each edge `(u, u + j)` rewired
+    /// with probability `beta` to `(u, w)` for a uniformly random `w`,
+    /// avoiding self-loops and parallel edges.
+    pub fn from_watts_strogatz(n: usize, k: usize, beta: f64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut present: HashSet<(usize, usize)> = HashSet::new();
+        for u in 0..n {
+            for j in 1..=k / 2 {
+                let v = (u + j) % n;
+                if u != v {
+                    present.insert((u.min(v), u.max(v)));
+                }
+            }
+        }
+        for j in 1..=k / 2 {
+            for u in 0..n {
+                let v = (u + j) % n;
+                if u == v || rng.gen::<f64>() >= beta {
+                    continue;
+                }
+                let w = rng.gen_range(0..n);
+                if w == u || present.contains(&(u.min(w), u.max(w))) {
+                    continue;
+                }
+                if present.remove(&(u.min(v), u.max(v))) {
+                    present.insert((u.min(w), u.max(w)));
+                }
+            }
+        }
+        let mut edges: Vec<(usize, usize)> = present.into_iter().collect();
+        edges.sort_unstable();
+        Self::from_edges(format!("WattsStrogatz_{}_{}_{}", n, k, beta), n, &edges)
+    }
+    /// Chung-Lu graph in which `u` and `v` are adjacent with probability
+    /// `min(w_u w_v / S, 1)`, `S` the total weight, so node `u` has expected
+    /// degree about `w_u`. Runs in O(n + m) by the skipping algorithm of
+    /// Miller and Hagberg over nodes sorted by decreasing weight.
+    pub fn from_chung_lu(weights: &[f64], seed: u64) -> Self {
+        let n = weights.len();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|&a, &b| weights[b].total_cmp(&weights[a]));
+        let w: Vec<f64> = order.iter().map(|&u| weights[u]).collect();
+        let total: f64 = w.iter().sum();
+        let mut edges = Vec::new();
+        if total > 0.0 {
+            for u in 0..n {
+                let mut v = u + 1;
+                let mut p = if v < n {
+                    (w[u] * w[v] / total).min(1.0)
+                } else {
+                    0.0
+                };
+                while v < n && p > 0.0 {
+                    if p < 1.0 {
+                        let r: f64 = 1.0 - rng.gen::<f64>();
+                        v = v.saturating_add((r.ln() / (-p).ln_1p()).floor() as usize);
+                    }
+                    if v < n {
+                        let q = (w[u] * w[v] / total).min(1.0);
+                        if rng.gen::<f64>() < q / p {
+                            edges.push((order[u], order[v]));
+                        }
+                        p = q;
+                        v += 1;
+                    }
+                }
+            }
+        }
+        Self::from_edges(format!("ChungLu_{}", n), n, &edges)
+    }
+    /// Kleinberg's navigable small world on an `n` by `n` grid: node
+    /// `(i, j)` has id `i * n + j` and is linked to every node within lattice
+    /// distance `k`, plus `q` long-range links to nodes `v` drawn with
+    /// probability proportional to `d(u, v)^-r`. A long-range contact is
+    /// found by drawing its distance `d` with weight `4d d^-r`, the size of
+    /// the shell on an unbounded lattice, and then a uniform point of that
+    /// shell, redrawing until it lies on the grid.
+    pub fn from_kleinberg(n: usize, k: usize, q: usize, r: f64, seed: u64) -> Result<Self, String> {
+        if !r.is_finite() {
+            return Err(format!("Kleinberg exponent must be finite, got {}", r));
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let side = n as isize;
+        let id = |i: isize, j: isize| {
+            (i >= 0 && i < side && j >= 0 && j < side).then(|| (i * side + j) as usize)
+        };
+        let radius = k.min(2 * n) as isize;
+        let mut edges = Vec::new();
+        for i in 0..side {
+            for j in 0..side {
+                let u = id(i, j).unwrap();
+                for di in -radius..=radius {
+                    let reach = radius - di.abs();
+                    for dj in -reach..=reach {
+                        if let Some(v) = id(i + di, j + dj).filter(|&v| v > u) {
+                            edges.push((u, v));
+                        }
+                    }
+                }
+                if q == 0 || n < 2 {
+                    continue;
+                }
+                // the farthest grid corner bounds the distance, so every
+                // drawn shell holds at least one grid node with some chance
+                let d_max = i.max(side - 1 - i) + j.max(side - 1 - j);
+                let log_weights: Vec<f64> =
+                    (1..=d_max).map(|d| (1.0 - r) * (d as f64).ln()).collect();
+                let top = log_weights
+                    .iter()
+                    .copied()
+                    .fold(f64::NEG_INFINITY, f64::max);
+                let shells =
+                    WeightedIndex::new(log_weights.iter().map(|w| (w - top).exp())).unwrap();
+                for _ in 0..q {
+                    let v = loop {
+                        let d = shells.sample(&mut rng) as isize + 1;
+                        let t = rng.gen_range(0..4 * d);
+                        let s = t % d;
+                        let (di, dj) = match t / d {
+                            0 => (d - s, s),
+                            1 => (-s, d - s),
+                            2 => (s - d, -s),
+                            _ => (s, s - d),
+                        };
+                        if let Some(v) = id(i + di, j + dj) {
+                            break v;
+                        }
+                    };
+                    edges.push((u, v));
+                }
+            }
+        }
+        Ok(Self::from_edges(
+            format!("Kleinberg_{}_{}_{}_{}", n, k, q, r),
+            n * n,
+            &edges,
+        ))
+    }
+    /// Forest-fire model of Leskovec et al.: every new node links to a random
+    /// ambassador and then recursively to a geometric number of the
+    /// unvisited out-links (mean `p_forward / (1 - p_forward)`) and in-links
+    /// (mean `p_backward / (1 - p_backward)`) of each node it links to,
+    /// where out-links are the ones a node created on arrival. Both
+    /// probabilities must lie in `[0, 1)`.
+    pub fn from_forest_fire(
+        n: usize,
+        p_forward: f64,
+        p_backward: f64,
+        seed: u64,
+    ) -> Result<Self, String> {
+        for (what, p) in [("p_forward", p_forward), ("p_backward", p_backward)] {
+            if !(0.0..1.0).contains(&p) {
+                return Err(format!(
+                    "Forest-fire {} must lie in [0, 1), got {}",
+                    what, p
+                ));
+            }
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut out_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut in_links: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut burned = vec![usize::MAX; n];
+        // geometric count, stopped at the `cap` unvisited links
+        let geometric = |p: f64, cap: usize, rng: &mut StdRng| {
+            let mut count = 0;
+            while count < cap && rng.gen::<f64>() < p {
+                count += 1;
+            }
+            count
+        };
+        for v in 1..n {
+            let ambassador = rng.gen_range(0..v);
+            burned[ambassador] = v;
+            let mut queue = VecDeque::from([ambassador]);
+            while let Some(u) = queue.pop_front() {
+                out_links[v].push(u);
+                for (links, p) in [(&out_links[u], p_forward), (&in_links[u], p_backward)] {
+                    let mut fresh: Vec<usize> =
+                        links.iter().copied().filter(|&w| burned[w] != v).collect();
+                    let count = geometric(p, fresh.len(), &mut rng);
+                    let (chosen, _) = fresh.partial_shuffle(&mut rng, count);
+                    for &w in chosen.iter() {
+                        burned[w] = v;
+                        queue.push_back(w);
+                    }
+                }
+            }
+            for &u in &out_links[v] {
+                in_links[u].push(v);
+            }
+        }
+        let edges: Vec<(usize, usize)> = out_links
+            .iter()
+            .enumerate()
+            .flat_map(|(v, links)| links.iter().map(move |&u| (u, v)))
+            .collect();
+        Ok(Self::from_edges(
+            format!("ForestFire_{}_{}_{}", n, p_forward, p_backward),
+            n,
+            &edges,
+        ))
+    }
+    // Grows from `m` isolated nodes by attaching every new node to `m`
+    // distinct targets drawn from a list holding each node once per incident
+    // edge. After a preferential pick, the next target is instead a random
+    // neighbour of it with probability `p_triad` (Holme-Kim triad step).
+    fn from_preferential_attachment(
+        n: usize,
+        m: usize,
+        p_triad: f64,
+        seed: u64,
+        name: String,
+    ) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut adjs: Vec<Vec<usize>> = vec![Vec::new(); n];
+        let mut repeated: Vec<usize> = Vec::with_capacity(2 * m * n);
+        for v in m..n {
+            let mut targets: Vec<usize> = Vec::with_capacity(m);
+            if v == m {
+                targets.extend(0..m);
+            }
+            let mut last_pick: Option<usize> = None;
+            while targets.len() < m {
+                let triad = last_pick
+                    .filter(|_| rng.gen::<f64>() < p_triad)
+                    .and_then(|u| {
+                        let fresh: Vec<usize> = adjs[u]
+                            .iter()
+                            .copied()
+                            .filter(|w| !targets.contains(w))
+                            .collect();
+                        fresh.choose(&mut rng).copied()
+                    });
+                let w = match triad {
+                    Some(w) => w,
+                    None => {
+                        let w = *repeated.choose(&mut rng).unwrap();
+                        if targets.contains(&w) {
+                            continue;
+                        }
+                        last_pick = Some(w);
+                        w
+                    }
+                };
+                targets.push(w);
+            }
+            for &u in &targets {
+                adjs[u].push(v);
+                adjs[v].push(u);
+                repeated.push(u);
+                repeated.push(v);
+            }
+        }
+        for adj in adjs.iter_mut() {
+            adj.sort_unstable();
+        }
+        Self {
+            name,
+            n,
+            m: repeated.len() / 2,
+            adjs,
+        }
+    }
+    /// Barabasi-Albert graph: every new node links to `m` distinct existing
+    /// nodes chosen with probability proportional to their degree.
+    pub fn from_barabasi_albert(n: usize, m: usize, seed: u64) -> Self {
+        Self::from_preferential_attachment(n, m, 0.0, seed, format!("BarabasiAlbert_{}_{}", n, m))
+    }
+    /// Holme-Kim model: preferential attachment with `m` links per new node,
+    /// each link after the first being a triad-closing link with probability
+    /// `p_triad`, which tunes the clustering of a scale-free graph.
+    pub fn from_holme_kim(n: usize, m: usize, p_triad: f64, seed: u64) -> Self {
+        Self::from_preferential_attachment(
+            n,
+            m,
+            p_triad,
+            seed,
+            format!("HolmeKim_{}_{}_{}", n, m, p_triad),
+        )
+    }
+    /// Stochastic block model in the Poisson form of Karrer and Newman: the
+    /// degree-corrected model of [`Self::from_dcsbm`] with equal propensities,
+    /// so every edge endpoint is uniform within its block.
+    pub fn from_sbm(blocks: &[usize], omega: &[Vec<f64>], seed: u64) -> Result<Self, String> {
+        let mut g = Self::from_dcsbm(blocks, &vec![1.0; blocks.len()], omega, seed)?;
+        g.name = format!("SBM_{}_{}", blocks.len(), omega.len());
+        Ok(g)
+    }
+    /// Degree-corrected stochastic block model of Karrer and Newman. Node `u`
+    /// lies in block `blocks[u]`; `theta` is normalized to sum to one within
+    /// every block, and the number of edges between blocks `r != s` (inside
+    /// block `r`) is Poisson with mean `omega[r][s]` (`omega[r][r] / 2`), each
+    /// endpoint drawn proportionally to `theta`. Parallel edges and
+    /// self-loops are dropped.
+    pub fn from_dcsbm(
+        blocks: &[usize],
+        theta: &[f64],
+        omega: &[Vec<f64>],
+        seed: u64,
+    ) -> Result<Self, String> {
+        let n = blocks.len();
+        let k = omega.len();
+        if theta.len() != n {
+            return Err(format!(
+                "Expected {} degree propensities, got {}",
+                n,
+                theta.len()
+            ));
+        }
+        if omega.iter().any(|row| row.len() != k) {
+            return Err("Block matrix omega is not square".to_string());
+        }
+        if let Some(&r) = blocks.iter().find(|&&r| r >= k) {
+            return Err(format!("Block {} out of range for {} blocks", r, k));
+        }
+        let mut members: Vec<Vec<usize>> = vec![Vec::new(); k];
+        for (u, &r) in blocks.iter().enumerate() {
+            members[r].push(u);
+        }
+        let mut samplers = Vec::with_capacity(k);
+        for nodes in &members {
+            let sampler = if nodes.is_empty() {
+                None
+            } else {
+                Some(WeightedIndex::new(nodes.iter().map(|&u| theta[u])).or(Err(
+                    "Degree propensities must be non-negative and not all zero in a block",
+                ))?)
+            };
+            samplers.push(sampler);
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges = Vec::new();
+        for r in 0..k {
+            for s in r..k {
+                let (Some(sr), Some(ss)) = (&samplers[r], &samplers[s]) else {
+                    continue;
+                };
+                let mean = if r == s {
+                    omega[r][r] / 2.0
+                } else {
+                    omega[r][s]
+                };
+                // Poisson count as the arrivals of a unit-rate process in [0, mean]
+                let mut clock = -(1.0 - rng.gen::<f64>()).ln();
+                while clock < mean {
+                    let u = members[r][sr.sample(&mut rng)];
+                    let v = members[s][ss.sample(&mut rng)];
+                    edges.push((u, v));
+                    clock -= (1.0 - rng.gen::<f64>()).ln();
+                }
+            }
+        }
+        Ok(Self::from_edges(format!("DCSBM_{}_{}", n, k), n, &edges))
+    }
+    /// `n_cliques` cliques of `clique_size` nodes, the last node of each
+    /// clique linked to the first node of the next one around a ring.
+    pub fn from_ring_of_cliques(n_cliques: usize, clique_size: usize) -> Self {
+        let n = n_cliques * clique_size;
+        let mut edges = Self::clique_edges(n_cliques, clique_size);
+        if n_cliques > 1 && clique_size > 0 {
+            for c in 0..n_cliques {
+                edges.push(((c + 1) * clique_size - 1, (c + 1) * clique_size % n));
+            }
+        }
+        Self::from_edges(
+            format!("RingOfCliques_{}_{}", n_cliques, clique_size),
+            n,
+            &edges,
+        )
+    }
+    /// Disjoint cliques in which every edge `(u, v)` is rewired with
+    /// probability `p_rewire` to `(u, x)` for a uniformly random node `x` not
+    /// yet adjacent to `u`.
+    pub fn from_relaxed_caveman(
+        n_cliques: usize,
+        clique_size: usize,
+        p_rewire: f64,
+        seed: u64,
+    ) -> Self {
+        let n = n_cliques * clique_size;
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut edges = Self::clique_edges(n_cliques, clique_size);
+        let mut present: HashSet<(usize, usize)> = edges.iter().copied().collect();
+        for edge in edges.iter_mut() {
+            let (u, v) = *edge;
+            if rng.gen::<f64>() >= p_rewire {
+                continue;
+            }
+            let x = rng.gen_range(0..n);
+            if x == u || present.contains(&(u.min(x), u.max(x))) {
+                continue;
+            }
+            present.remove(&(u, v));
+            present.insert((u.min(x), u.max(x)));
+            *edge = (u.min(x), u.max(x));
+        }
+        Self::from_edges(
+            format!("RelaxedCaveman_{}_{}_{}", n_cliques, clique_size, p_rewire),
+            n,
+            &edges,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -195,6 +797,41 @@ mod tests {
 
     use super::*;
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let g = NormalUndiGraph::from_koch(2);
+        let json = serde_json::to_string(&g).unwrap();
+        let back: NormalUndiGraph = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            (back.name, back.n, back.m, back.adjs),
+            (g.name, g.n, g.m, g.adjs)
+        );
+        let bad = [
+            r#"{"name":"","n":3,"m":1,"adjs":[[1],[0]]}"#,
+            r#"{"name":"","n":2,"m":1,"adjs":[[2],[0]]}"#,
+            r#"{"name":"","n":3,"m":2,"adjs":[[2,1],[0],[0]]}"#,
+            r#"{"name":"","n":3,"m":1,"adjs":[[1],[0,2],[]]}"#,
+            r#"{"name":"","n":2,"m":2,"adjs":[[1],[0]]}"#,
+        ];
+        for json in bad {
+            assert!(serde_json::from_str::<NormalUndiGraph>(json).is_err());
+        }
+        let mut general = GeneralUndiGraph::new("g".to_string());
+        general.add_edge(3, 1);
+        let back: GeneralUndiGraph =
+            serde_json::from_str(&serde_json::to_string(&general).unwrap()).unwrap();
+        assert_eq!((back.nodes, back.edges), (general.nodes, general.edges));
+        let bad = [
+            r#"{"name":"","nodes":[1],"edges":[[1,3]]}"#,
+            r#"{"name":"","nodes":[1,3],"edges":[[3,1]]}"#,
+            r#"{"name":"","nodes":[1],"edges":[[1,1]]}"#,
+        ];
+        for json in bad {
+            assert!(serde_json::from_str::<GeneralUndiGraph>(json).is_err());
+        }
+    }
+
     #[test]
     fn test_pseudo_ext() {
         let mut wf = File::create("pseudoext_2_4.txt").unwrap();
@@ -216,6 +853,190 @@ mod tests {
         write!(wf, "{}", g).unwrap();
     }
 
+    #[test]
+    fn test_ring_of_cliques() {
+        let g = NormalUndiGraph::from_ring_of_cliques(4, 5);
+        assert_eq!((g.n, g.m), (20, 4 * 10 + 4));
+        assert_eq!(g.num_components(), 1);
+        assert_eq!(g.adjs[4], vec![0, 1, 2, 3, 5]);
+        assert_eq!(g.adjs[0], vec![1, 2, 3, 4, 19]);
+    }
+
+    #[test]
+    fn test_relaxed_caveman() {
+        let g = NormalUndiGraph::from_relaxed_caveman(5, 4, 0.0, 1);
+        assert_eq!((g.m, g.num_components()), (30, 5));
+        let g = NormalUndiGraph::from_relaxed_caveman(5, 4, 0.3, 1);
+        assert_eq!((g.n, g.m), (20, 30));
+        assert!(g.num_components() < 5);
+        let same = NormalUndiGraph::from_relaxed_caveman(5, 4, 0.3, 1);
+        assert_eq!(g.adjs, same.adjs);
+    }
+
+    #[test]
+    fn test_erdos_renyi() {
+        let g = NormalUndiGraph::from_erdos_renyi(2000, 0.005, 3);
+        let expected = 0.005 * 2000.0 * 1999.0 / 2.0;
+        assert!((g.m as f64 - expected).abs() < 0.05 * expected);
+        assert_eq!(
+            NormalUndiGraph::from_erdos_renyi(2000, 0.005, 3).adjs,
+            g.adjs
+        );
+        assert_eq!(NormalUndiGraph::from_erdos_renyi(10, 1.0, 0).m, 45);
+        assert_eq!(NormalUndiGraph::from_erdos_renyi(10, 0.0, 0).m, 0);
+        // 1 - p rounds to 1, which must not turn every pair into an edge
+        assert_eq!(NormalUndiGraph::from_erdos_renyi(100, 1e-20, 0).m, 0);
+        let sparse = NormalUndiGraph::from_erdos_renyi(100_000, 1e-9, 0);
+        assert!(sparse.m < 20);
+    }
+
+    #[test]
+    fn test_watts_strogatz() {
+        let ring = NormalUndiGraph::from_watts_strogatz(100, 4, 0.0, 1);
+        assert_eq!(ring.m, 200);
+        assert!(ring.degrees().iter().all(|&d| d == 4));
+        assert_eq!(ring.adjs[0], vec![1, 2, 98, 99]);
+        let g = NormalUndiGraph::from_watts_strogatz(100, 4, 0.2, 1);
+        assert_eq!(g.m, 200);
+        assert!(g.adjs != ring.adjs);
+        assert!(g.average_clustering() < ring.average_clustering());
+    }
+
+    #[test]
+    fn test_chung_lu() {
+        let weights: Vec<f64> = (0..2000)
+            .map(|u| if u % 10 == 0 { 40.0 } else { 4.0 })
+            .collect();
+        let g = NormalUndiGraph::from_chung_lu(&weights, 7);
+        let expected = weights.iter().sum::<f64>() / 2.0;
+        assert!((g.m as f64 - expected).abs() < 0.05 * expected);
+        let degrees = g.degrees();
+        let hub_mean = (0..2000).step_by(10).map(|u| degrees[u]).sum::<usize>() as f64 / 200.0;
+        assert!((hub_mean - 40.0).abs() < 4.0);
+        assert_eq!(NormalUndiGraph::from_chung_lu(&weights, 7).adjs, g.adjs);
+        assert_eq!(NormalUndiGraph::from_chung_lu(&[0.0; 5], 7).m, 0);
+        // edge probabilities below the f64 resolution of 1 - p
+        assert_eq!(NormalUndiGraph::from_chung_lu(&[1e-20; 50], 7).m, 0);
+    }
+
+    #[test]
+    fn test_kleinberg() {
+        let lattice = NormalUndiGraph::from_kleinberg(5, 1, 0, 2.0, 0).unwrap();
+        assert_eq!((lattice.n, lattice.m), (25, 2 * 5 * 4));
+        assert_eq!(lattice.adjs[6], vec![1, 5, 7, 11]);
+        let wide = NormalUndiGraph::from_kleinberg(5, 2, 0, 2.0, 0).unwrap();
+        assert_eq!(wide.adjs[0], vec![1, 2, 5, 6, 10]);
+        let lattice = NormalUndiGraph::from_kleinberg(10, 1, 0, 2.0, 0).unwrap();
+        let g = NormalUndiGraph::from_kleinberg(10, 1, 1, 2.0, 3).unwrap();
+        assert!(g.m > lattice.m && g.m <= lattice.m + 100);
+        for (u, adj) in lattice.adjs.iter().enumerate() {
+            for v in adj {
+                assert!(g.adjs[u].binary_search(v).is_ok());
+            }
+        }
+        let far = NormalUndiGraph::from_kleinberg(10, 1, 1, 0.0, 3).unwrap();
+        assert!(far.eccentricities().unwrap()[0] < 18);
+        // a steep exponent keeps contacts next door, a negative one far away
+        let near = NormalUndiGraph::from_kleinberg(10, 0, 1, 50.0, 3).unwrap();
+        assert!(near.adjs.iter().enumerate().all(|(u, adj)| adj
+            .iter()
+            .all(|&v| (u / 10).abs_diff(v / 10) + (u % 10).abs_diff(v % 10) == 1)));
+        let distant = NormalUndiGraph::from_kleinberg(3, 0, 1, -1000.0, 3).unwrap();
+        assert!(distant.adjs[4].iter().all(|&v| [0, 2, 6, 8].contains(&v)));
+        assert!(NormalUndiGraph::from_kleinberg(10, 1, 1, f64::NAN, 3).is_err());
+    }
+
+    #[test]
+    fn test_forest_fire() {
+        let tree = NormalUndiGraph::from_forest_fire(100, 0.0, 0.0, 2).unwrap();
+        assert_eq!((tree.m, tree.num_components()), (99, 1));
+        let g = NormalUndiGraph::from_forest_fire(1000, 0.37, 0.32, 2).unwrap();
+        assert_eq!(g.num_components(), 1);
+        // densification: the later half of the nodes brings more edges
+        let early = NormalUndiGraph::from_forest_fire(500, 0.37, 0.32, 2).unwrap();
+        assert!(g.m - early.m > early.m);
+        assert_eq!(
+            NormalUndiGraph::from_forest_fire(1000, 0.37, 0.32, 2)
+                .unwrap()
+                .adjs,
+            g.adjs
+        );
+        for p in [1.0, -0.1, f64::NAN] {
+            assert!(NormalUndiGraph::from_forest_fire(10, p, 0.3, 2).is_err());
+            assert!(NormalUndiGraph::from_forest_fire(10, 0.3, p, 2).is_err());
+        }
+    }
+
+    #[test]
+    fn test_barabasi_albert() {
+        let g = NormalUndiGraph::from_barabasi_albert(5000, 2, 11);
+        assert_eq!((g.n, g.m), (5000, 2 * 4998));
+        assert_eq!(g.num_components(), 1);
+        let degrees = g.degrees();
+        assert!(degrees.iter().all(|&d| d >= 2));
+        // heavy tail: the largest hub far exceeds the mean degree of 4
+        assert!(*degrees.iter().max().unwrap() > 60);
+        assert_eq!(
+            NormalUndiGraph::from_barabasi_albert(5000, 2, 11).adjs,
+            g.adjs
+        );
+    }
+
+    #[test]
+    fn test_holme_kim() {
+        let ba = NormalUndiGraph::from_holme_kim(2000, 3, 0.0, 4);
+        assert_eq!((ba.n, ba.m), (2000, 3 * 1997));
+        assert_eq!(ba.num_components(), 1);
+        let hk = NormalUndiGraph::from_holme_kim(2000, 3, 0.8, 4);
+        assert_eq!(hk.m, ba.m);
+        assert!(hk.average_clustering() > 3.0 * ba.average_clustering());
+        assert!(*hk.degrees().iter().max().unwrap() > 40);
+    }
+
+    #[test]
+    fn test_dcsbm() {
+        let blocks: Vec<usize> = (0..1000).map(|u| u / 500).collect();
+        let theta: Vec<f64> = (0..1000)
+            .map(|u| if u % 50 == 0 { 20.0 } else { 1.0 })
+            .collect();
+        let omega = vec![vec![4000.0, 200.0], vec![200.0, 4000.0]];
+        let g = NormalUndiGraph::from_dcsbm(&blocks, &theta, &omega, 9).unwrap();
+        let crossing = g
+            .edge_list()
+            .iter()
+            .filter(|&&(u, v)| blocks[u] != blocks[v])
+            .count();
+        assert!(crossing > 150 && crossing < 250);
+        assert!(g.m > 3500 && g.m < 4300);
+        let degrees = g.degrees();
+        assert!(degrees[0] > 5 * degrees[1]);
+        assert!(NormalUndiGraph::from_dcsbm(&blocks, &theta[1..], &omega, 9).is_err());
+        assert!(NormalUndiGraph::from_dcsbm(&[0, 2], &[1.0, 1.0], &omega, 9).is_err());
+
+        // without degree correction, degrees stay Poisson around 2 m / n
+        let plain = NormalUndiGraph::from_sbm(&blocks, &omega, 9).unwrap();
+        assert!(plain.m > 3500 && plain.m < 4300);
+        assert!(plain.degrees().iter().all(|&d| d < 25));
+        assert!(NormalUndiGraph::from_sbm(&[0, 2], &omega, 9).is_err());
+    }
+
+    #[test]
+    fn test_sparse_matrices() {
+        use super::super::spectral::SpectrumMatrix;
+        let g = NormalUndiGraph::from_apollo(3);
+        let adj = g.sparse_adj();
+        assert_eq!(adj.nnz(), 2 * g.m);
+        assert_eq!(na::DMatrix::from(&adj), g.diag_adj().1);
+        let lap = g.sparse_laplacian();
+        assert_eq!(lap.nnz(), 2 * g.m + g.n);
+        assert_eq!(
+            na::DMatrix::from(&lap),
+            g.spectrum_matrix(SpectrumMatrix::Laplacian)
+        );
+        let x = na::DVector::from_element(g.n, 1.0);
+        assert!((&lap * &x).iter().all(|&y| y == 0.0));
+    }
+
     #[test]
     fn test_konect_euro() {
         use super::super::general_graph::GeneralUndiGraph;
@@ -234,7 +1055,7 @@ mod tests {
         let mut wf = File::create(path).unwrap();
         write!(wf, "0 1\n0 2\n0 3\n1 3\n").unwrap();
         let rf = File::open(path).unwrap();
-        let g = GeneralUndiGraph::from_file("test_diag_adj", rf);
+        let g = GeneralUndiGraph::from_file("test_diag_adj", rf).unwrap();
         std::fs::remove_file(path).unwrap();
         let g = NormalUndiGraph::from_general(&g);
         let (diag, adj) = g.diag_adj();
@@ -242,4 +1063,27 @@ mod tests {
         let mut wf = File::create("test_diag_adj_output.txt").unwrap();
         writeln!(wf, "lap:\n{}", lap).unwrap();
     }
+
+    #[test]
+    fn test_from_general_with() {
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for i in 0..500 {
+            g.add_edge(3 * i, 3 * ((i * 13 + 7) % 500));
+            g.add_edge(3 * i, 3 * ((i + 1) % 500));
+        }
+        let config = ComputeConfig {
+            num_threads: 3,
+            ..ComputeConfig::default()
+        };
+        let (serial, threaded) = (
+            NormalUndiGraph::from_general_with(&g, &ComputeConfig::serial()),
+            NormalUndiGraph::from_general_with(&g, &config),
+        );
+        assert_eq!((threaded.n, threaded.m), (serial.n, serial.m));
+        assert_eq!(threaded.adjs, serial.adjs);
+        assert_eq!(serial.adjs[1], vec![0, 2, 20, 38]);
+        assert_eq!(NormalUndiGraph::from_general(&g).adjs, serial.adjs);
+        let empty = GeneralUndiGraph::new("e".to_string());
+        assert_eq!(NormalUndiGraph::from_general_with(&empty, &config).n, 0);
+    }
 }
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, Write};
+
+use super::normal_graph::NormalUndiGraph;
+use super::sampling::sample_nodes_weighted_without_replacement;
+
+pub struct DistanceOracle {
+    pub landmarks: Vec<usize>,
+    // dists[u * landmarks.len() + i] is the BFS distance from landmarks[i] to u
+    dists: Vec<usize>,
+}
+
+impl DistanceOracle {
+    /// Picks `n_landmarks` distinct landmarks with probability proportional to
+    /// degree and stores a BFS distance vector for each of them.
+    pub fn build(g: &NormalUndiGraph, n_landmarks: usize, seed: u64) -> Self {
+        let degs: Vec<f64> = g.adjs.iter().map(|adj| adj.len() as f64).collect();
+        let landmarks =
+            sample_nodes_weighted_without_replacement(&degs, n_landmarks, seed).unwrap_or_default();
+        Self::from_landmarks(g, landmarks)
+    }
+    pub fn from_landmarks(g: &NormalUndiGraph, landmarks: Vec<usize>) -> Self {
+        let k = landmarks.len();
+        let mut dists = vec![usize::MAX; g.n * k];
+        for (i, &l) in landmarks.iter().enumerate() {
+            for (u, d) in g.distances_from(l).into_iter().enumerate() {
+                dists[u * k + i] = d;
+            }
+        }
+        Self { landmarks, dists }
+    }
+    fn row(&self, u: usize) -> &[usize] {
+        let k = self.landmarks.len();
+        &self.dists[u * k..(u + 1) * k]
+    }
+    /// Upper bound `min_l d(u, l) + d(l, v)`, or `None` if no landmark reaches both.
+    pub fn approx_distance(&self, u: usize, v: usize) -> Option<usize> {
+        if u == v {
+            return Some(0);
+        }
+        self.row(u)
+            .iter()
+            .zip(self.row(v))
+            .filter(|(&du, &dv)| du != usize::MAX && dv != usize::MAX)
+            .map(|(du, dv)| du + dv)
+            .min()
+    }
+    /// Triangle-inequality lower bound `max_l |d(u, l) - d(l, v)|`.
+    pub fn lower_bound(&self, u: usize, v: usize) -> usize {
+        self.row(u)
+            .iter()
+            .zip(self.row(v))
+            .filter(|(&du, &dv)| du != usize::MAX && dv != usize::MAX)
+            .map(|(&du, &dv)| du.abs_diff(dv))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Exact 2-hop distance labels built by pruned BFS (Akiba, Iwata, Yoshida 2013).
+/// Each label entry is `(rank, dist)`, with hubs ranked by decreasing degree.
+pub struct PrunedLandmarkLabeling {
+    labels: Vec<Vec<(usize, usize)>>,
+}
+
+impl PrunedLandmarkLabeling {
+    pub fn build(g: &NormalUndiGraph) -> Self {
+        let mut order: Vec<usize> = (0..g.n).collect();
+        order.sort_by_key(|&u| (std::cmp::Reverse(g.adjs[u].len()), u));
+        let mut labels: Vec<Vec<(usize, usize)>> = vec![Vec::new(); g.n];
+        let mut root_label = vec![usize::MAX; g.n];
+        let mut dist = vec![usize::MAX; g.n];
+        let mut visited: Vec<usize> = Vec::new();
+        let mut queue = VecDeque::new();
+        for (rank, &root) in order.iter().enumerate() {
+            for &(r, d) in &labels[root] {
+                root_label[r] = d;
+            }
+            dist[root] = 0;
+            visited.push(root);
+            queue.push_back(root);
+            while let Some(u) = queue.pop_front() {
+                let d = dist[u];
+                let covered = labels[u]
+                    .iter()
+                    .any(|&(r, du)| root_label[r] != usize::MAX && root_label[r] + du <= d);
+                if covered {
+                    continue;
+                }
+                labels[u].push((rank, d));
+                for &v in &g.adjs[u] {
+                    if dist[v] == usize::MAX {
+                        dist[v] = d + 1;
+                        visited.push(v);
+                        queue.push_back(v);
+                    }
+                }
+            }
+            for u in visited.drain(..) {
+                dist[u] = usize::MAX;
+            }
+            for &(r, _) in &labels[root] {
+                root_label[r] = usize::MAX;
+            }
+        }
+        Self { labels }
+    }
+    pub fn num_nodes(&self) -> usize {
+        self.labels.len()
+    }
+    pub fn num_entries(&self) -> usize {
+        self.labels.iter().map(|label| label.len()).sum()
+    }
+    pub fn query(&self, u: usize, v: usize) -> Option<usize> {
+        let (lu, lv) = (&self.labels[u], &self.labels[v]);
+        let (mut i, mut j) = (0, 0);
+        let mut best: Option<usize> = None;
+        while i < lu.len() && j < lv.len() {
+            match lu[i].0.cmp(&lv[j].0) {
+                std::cmp::Ordering::Less => i += 1,
+                std::cmp::Ordering::Greater => j += 1,
+                std::cmp::Ordering::Equal => {
+                    let d = lu[i].1 + lv[j].1;
+                    best = Some(best.map_or(d, |b| b.min(d)));
+                    i += 1;
+                    j += 1;
+                }
+            }
+        }
+        best
+    }
+    pub fn save<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        writeln!(w, "# PrunedLandmarkLabeling")?;
+        writeln!(
+            w,
+            "# Nodes: {} Entries: {}",
+            self.num_nodes(),
+            self.num_entries()
+        )?;
+        for label in &self.labels {
+            let entries: Vec<String> = label.iter().map(|(r, d)| format!("{}:{}", r, d)).collect();
+            writeln!(w, "{}", entries.join("\t"))?;
+        }
+        Ok(())
+    }
+    pub fn load<R: BufRead>(r: R) -> Result<Self, String> {
+        let mut labels = Vec::new();
+        for line in r.lines() {
+            let line = line.or(Err("Failed to read label line"))?;
+            if line.starts_with('#') {
+                continue;
+            }
+            let mut label = Vec::new();
+            for entry in line.split('\t').filter(|e| !e.is_empty()) {
+                let (r, d) = entry
+                    .split_once(':')
+                    .ok_or(format!("Malformed label entry '{}'", entry))?;
+                let r: usize = r.parse().or(Err(format!("Malformed rank '{}'", r)))?;
+                let d: usize = d.parse().or(Err(format!("Malformed distance '{}'", d)))?;
+                // `query` merges labels by rank, which needs them strictly increasing
+                if label.last().is_some_and(|&(last, _)| last >= r) {
+                    return Err(format!(
+                        "Label {} has rank {} out of increasing order",
+                        labels.len(),
+                        r
+                    ));
+                }
+                label.push((r, d));
+            }
+            labels.push(label);
+        }
+        Ok(Self { labels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance_oracle_bounds() {
+        let g = NormalUndiGraph::from_koch(3);
+        let oracle = DistanceOracle::build(&g, 8, 3);
+        assert_eq!(oracle.landmarks.len(), 8);
+        for u in [0, 5, 17, 40] {
+            let exact = g.distances_from(u);
+            for (v, &d) in exact.iter().enumerate() {
+                let upper = oracle.approx_distance(u, v).unwrap();
+                assert!(oracle.lower_bound(u, v) <= d);
+                assert!(d <= upper);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pruned_landmark_labeling() {
+        let g = NormalUndiGraph::from_pseudofractal(4);
+        let pll = PrunedLandmarkLabeling::build(&g);
+        let mut buf: Vec<u8> = Vec::new();
+        pll.save(&mut buf).unwrap();
+        let loaded = PrunedLandmarkLabeling::load(buf.as_slice()).unwrap();
+        assert_eq!(loaded.num_entries(), pll.num_entries());
+        for bad in ["0:0\t2:1\t1:1\n", "0:0\t3:1\t3:2\n"] {
+            assert!(PrunedLandmarkLabeling::load(bad.as_bytes()).is_err());
+        }
+        for u in 0..g.n {
+            let exact = g.distances_from(u);
+            for (v, &d) in exact.iter().enumerate() {
+                assert_eq!(pll.query(u, v), Some(d));
+                assert_eq!(loaded.query(u, v), Some(d));
+            }
+        }
+    }
+
+    #[test]
+    fn test_distance_oracle_landmark_exact() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let oracle = DistanceOracle::from_landmarks(&g, vec![0]);
+        let exact = g.distances_from(0);
+        for (v, &d) in exact.iter().enumerate() {
+            assert_eq!(oracle.approx_distance(0, v), Some(d));
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Shared flag asking a running operation to stop. Clones refer to the same
+/// flag, so one can be handed to the operation and the other kept to cancel.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+type Callback = dyn Fn(&str, u64, Option<u64>) + Send + Sync;
+
+/// Progress callback, called with the stage name, the units of work done
+/// and the total when it is known. It may be called from worker threads.
+#[derive(Clone)]
+pub struct ProgressFn(Arc<Callback>);
+
+impl ProgressFn {
+    pub fn new<F: Fn(&str, u64, Option<u64>) + Send + Sync + 'static>(f: F) -> Self {
+        Self(Arc::new(f))
+    }
+    pub fn report(&self, stage: &str, done: u64, total: Option<u64>) {
+        (self.0)(stage, done, total)
+    }
+}
+
+impl fmt::Debug for ProgressFn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressFn")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_token_and_callback() {
+        let token = CancellationToken::new();
+        let handle = token.clone();
+        assert!(!token.is_cancelled());
+        handle.cancel();
+        assert!(token.is_cancelled());
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let progress = ProgressFn::new(move |stage, done, total| {
+            sink.lock().unwrap().push((stage.to_string(), done, total))
+        });
+        progress.clone().report("parse", 3, None);
+        assert_eq!(*seen.lock().unwrap(), vec![("parse".to_string(), 3, None)]);
+    }
+}
@@ -0,0 +1,156 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::laplacian::{LaplacianSolver, DENSE_LIMIT};
+use super::linalg::dot;
+use super::normal_graph::NormalUndiGraph;
+use super::weighted_graph::WeightedUndiGraph;
+
+// probes of the trace estimate behind `kemeny_constant` on large graphs
+const KEMENY_PROBES: usize = 64;
+
+impl NormalUndiGraph {
+    fn require_connected(&self, what: &str) -> Result<(), String> {
+        if self.n == 0 || self.num_components() > 1 {
+            return Err(format!(
+                "{} of '{}' needs a connected non-empty graph",
+                what, self.name
+            ));
+        }
+        Ok(())
+    }
+    fn degree_vec(&self) -> Vec<f64> {
+        self.adjs.iter().map(|adj| adj.len() as f64).collect()
+    }
+    /// Expected steps of a simple random walk from every node to `target`,
+    /// from `H(u, t) = 2m (L+_tt - L+_ut) + (L+ d)_u - (L+ d)_t`. Uses
+    /// `laplacian_pinv` up to the dense limit of `kirchhoff_index` and two
+    /// Laplacian solves beyond.
+    pub fn mean_hitting_times(&self, target: usize) -> Result<Vec<f64>, String> {
+        self.require_connected("Hitting times")?;
+        let d = self.degree_vec();
+        let (x, y) = if self.n <= DENSE_LIMIT {
+            let pinv = self.laplacian_pinv();
+            let x: Vec<f64> = (0..self.n)
+                .map(|u| (0..self.n).map(|w| pinv[(u, w)] * d[w]).sum())
+                .collect();
+            let y: Vec<f64> = (0..self.n).map(|u| pinv[(u, target)]).collect();
+            (x, y)
+        } else {
+            let solver = self.walk_solver();
+            let mut e = vec![0.0; self.n];
+            e[target] = 1.0;
+            (solver.solve(&d).x, solver.solve(&e).x)
+        };
+        let two_m = 2.0 * self.m as f64;
+        Ok((0..self.n)
+            .map(|u| {
+                if u == target {
+                    0.0
+                } else {
+                    two_m * (y[target] - y[u]) + x[u] - x[target]
+                }
+            })
+            .collect())
+    }
+    /// Kemeny constant, the expected time to reach a node drawn from the
+    /// stationary distribution, the same from every start. Exact as
+    /// `tr(D L+) - d^T L+ d / 2m` up to the dense limit, estimated with
+    /// `kemeny_constant_approx` beyond.
+    pub fn kemeny_constant(&self) -> Result<f64, String> {
+        self.require_connected("Kemeny constant")?;
+        if self.n > DENSE_LIMIT {
+            return self.kemeny_constant_approx(KEMENY_PROBES, 0);
+        }
+        let d = self.degree_vec();
+        let pinv = self.laplacian_pinv();
+        let trace: f64 = (0..self.n).map(|u| d[u] * pinv[(u, u)]).sum();
+        let quad: f64 = (0..self.n)
+            .map(|u| d[u] * (0..self.n).map(|w| pinv[(u, w)] * d[w]).sum::<f64>())
+            .sum();
+        Ok(trace - quad / (2.0 * self.m as f64))
+    }
+    /// `kemeny_constant` with `tr(D L+)` estimated by Hutchinson's method
+    /// from `probes` Rademacher vectors, one Laplacian solve each.
+    pub fn kemeny_constant_approx(&self, probes: usize, seed: u64) -> Result<f64, String> {
+        self.require_connected("Kemeny constant")?;
+        let d = self.degree_vec();
+        let solver = self.walk_solver();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut trace = 0.0;
+        for _ in 0..probes {
+            let z: Vec<f64> = d
+                .iter()
+                .map(|du| if rng.gen() { du.sqrt() } else { -du.sqrt() })
+                .collect();
+            trace += dot(&z, &solver.solve(&z).x);
+        }
+        trace /= probes.max(1) as f64;
+        let quad = dot(&d, &solver.solve(&d).x);
+        Ok(trace - quad / (2.0 * self.m as f64))
+    }
+    fn walk_solver(&self) -> LaplacianSolver {
+        LaplacianSolver::new(WeightedUndiGraph::from_normal(self), Default::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hitting_times() {
+        let path = NormalUndiGraph::from_edges("P3".to_string(), 3, &[(0, 1), (1, 2)]);
+        let h = path.mean_hitting_times(2).unwrap();
+        for (a, b) in h.iter().zip([4.0, 3.0, 0.0]) {
+            assert!((a - b).abs() < 1e-9);
+        }
+        let g = NormalUndiGraph::from_apollo(2);
+        let solver = g.walk_solver();
+        let mut e = vec![0.0; g.n];
+        e[5] = 1.0;
+        // the dense and the solver formula agree
+        let d = g.degree_vec();
+        let (x, y) = (solver.solve(&d).x, solver.solve(&e).x);
+        let h = g.mean_hitting_times(5).unwrap();
+        for u in 0..g.n {
+            let expected = if u == 5 {
+                0.0
+            } else {
+                2.0 * g.m as f64 * (y[5] - y[u]) + x[u] - x[5]
+            };
+            assert!((h[u] - expected).abs() < 1e-6);
+        }
+        assert!(
+            NormalUndiGraph::from_edges("2K2".to_string(), 4, &[(0, 1), (2, 3)])
+                .mean_hitting_times(0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_kemeny_constant() {
+        // K_n has (n - 1)^2 / n
+        let k4 = NormalUndiGraph::from_apollo(0);
+        assert!((k4.kemeny_constant().unwrap() - 9.0 / 4.0).abs() < 1e-9);
+        // equals sum 1 / (1 - λ) over the non-unit eigenvalues of the walk,
+        // and the stationary average of hitting times from any start
+        let g = NormalUndiGraph::from_pseudofractal(3);
+        let kemeny = g.kemeny_constant().unwrap();
+        let spectrum = g
+            .spectrum_matrix(crate::spectral::SpectrumMatrix::NormalizedLaplacian)
+            .symmetric_eigenvalues();
+        let mut mu: Vec<f64> = spectrum.iter().copied().collect();
+        mu.sort_unstable_by(|a, b| a.total_cmp(b));
+        let spectral: f64 = mu[1..].iter().map(|m| 1.0 / m).sum();
+        assert!((kemeny - spectral).abs() < 1e-6);
+        let from_hitting: f64 = (0..g.n)
+            .map(|t| {
+                g.adjs[t].len() as f64 / (2 * g.m) as f64 * g.mean_hitting_times(t).unwrap()[0]
+            })
+            .sum();
+        assert!((kemeny - from_hitting).abs() < 1e-6);
+        let approx = g.kemeny_constant_approx(400, 1).unwrap();
+        assert!((approx - kemeny).abs() < 0.05 * kemeny);
+    }
+}
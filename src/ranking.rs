@@ -0,0 +1,71 @@
+use std::cmp::Ordering;
+
+use super::normal_graph::NormalUndiGraph;
+
+/// Indices of the `k` largest scores, largest first. Equal scores go to the
+/// smaller index, so the result is deterministic, and NaN ranks last.
+pub fn top_k_indices(scores: &[f64], k: usize) -> Vec<usize> {
+    let key = |i: usize| {
+        if scores[i].is_nan() {
+            f64::NEG_INFINITY
+        } else {
+            scores[i]
+        }
+    };
+    let cmp = |&a: &usize, &b: &usize| -> Ordering { key(b).total_cmp(&key(a)).then(a.cmp(&b)) };
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    let k = k.min(order.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    order.select_nth_unstable_by(k - 1, cmp);
+    order.truncate(k);
+    order.sort_unstable_by(cmp);
+    order
+}
+
+impl NormalUndiGraph {
+    /// The `k` nodes with the largest `scores`, ranked as in `top_k_indices`.
+    /// With `old_ids` from `canonicalize` or `relabel_by`, the original ids
+    /// are returned instead of the node numbers of this graph.
+    pub fn top_k_by(&self, scores: &[f64], k: usize, old_ids: Option<&[usize]>) -> Vec<usize> {
+        assert_eq!(scores.len(), self.n, "one score per node expected");
+        let top = top_k_indices(scores, k);
+        match old_ids {
+            Some(old_ids) => top.into_iter().map(|u| old_ids[u]).collect(),
+            None => top,
+        }
+    }
+    /// The `k` nodes of highest degree, see `top_k_by`.
+    pub fn hubs(&self, k: usize, old_ids: Option<&[usize]>) -> Vec<usize> {
+        let degrees: Vec<f64> = self.adjs.iter().map(|adj| adj.len() as f64).collect();
+        self.top_k_by(&degrees, k, old_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::general_graph::GeneralUndiGraph;
+
+    #[test]
+    fn test_top_k() {
+        assert_eq!(
+            top_k_indices(&[1.0, 3.0, f64::NAN, 3.0, 2.0], 3),
+            vec![1, 3, 4]
+        );
+        assert_eq!(top_k_indices(&[1.0, f64::NAN], 5), vec![0, 1]);
+        assert!(top_k_indices(&[1.0], 0).is_empty());
+
+        // a star centred at 40 plus an edge hanging off leaf 10
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [(40, 10), (40, 20), (40, 30), (10, 50)] {
+            g.add_edge(u, v);
+        }
+        let (normal, old_ids) = g.canonicalize();
+        assert_eq!(normal.hubs(2, Some(&old_ids)), vec![40, 10]);
+        assert_eq!(normal.hubs(2, None), vec![3, 0]);
+        let scores = normal.harmonic_centrality();
+        assert_eq!(normal.top_k_by(&scores, 1, Some(&old_ids)), vec![40]);
+    }
+}
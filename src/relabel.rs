@@ -0,0 +1,154 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::VecDeque;
+
+use super::general_graph::GeneralUndiGraph;
+use super::normal_graph::NormalUndiGraph;
+
+pub enum RelabelStrategy<'a> {
+    // keeps the relative order of ids and closes the gaps between them
+    Compact,
+    // decreasing degree, ties by id
+    Degree,
+    // BFS order, each component searched from its smallest id
+    Bfs,
+    // uniformly random permutation
+    Random(u64),
+    // increasing key of the original id, ties by id
+    Key(&'a dyn Fn(usize) -> usize),
+}
+
+/// Node ids of `g` in increasing order, i.e. the compact relabeling.
+pub(crate) fn sorted_ids(g: &GeneralUndiGraph) -> Vec<usize> {
+    let mut ids: Vec<usize> = g.nodes.iter().copied().collect();
+    ids.sort_unstable();
+    ids
+}
+
+/// Converts `g` to a `NormalUndiGraph` numbered by `strategy`. Also returns
+/// the original id of every new node.
+pub fn relabel_by(
+    g: &GeneralUndiGraph,
+    strategy: &RelabelStrategy,
+) -> (NormalUndiGraph, Vec<usize>) {
+    let compact = NormalUndiGraph::from_general(g);
+    let ids = sorted_ids(g);
+    let order = compact.relabel_order(&ids, strategy);
+    let old_ids = order.iter().map(|&u| ids[u]).collect();
+    (compact.permuted(&order), old_ids)
+}
+
+impl NormalUndiGraph {
+    // New-to-old node order under `strategy`, where node `u` is known to the
+    // caller as `ids[u]`; `ids` must be increasing.
+    fn relabel_order(&self, ids: &[usize], strategy: &RelabelStrategy) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.n).collect();
+        match strategy {
+            RelabelStrategy::Compact => {}
+            RelabelStrategy::Degree => {
+                order.sort_by_key(|&u| std::cmp::Reverse(self.adjs[u].len()));
+            }
+            RelabelStrategy::Bfs => {
+                let mut seen = vec![false; self.n];
+                order.clear();
+                for root in 0..self.n {
+                    if seen[root] {
+                        continue;
+                    }
+                    seen[root] = true;
+                    let mut queue = VecDeque::from([root]);
+                    while let Some(u) = queue.pop_front() {
+                        order.push(u);
+                        for &v in &self.adjs[u] {
+                            if !seen[v] {
+                                seen[v] = true;
+                                queue.push_back(v);
+                            }
+                        }
+                    }
+                }
+            }
+            RelabelStrategy::Random(seed) => {
+                order.shuffle(&mut StdRng::seed_from_u64(*seed));
+            }
+            RelabelStrategy::Key(key) => {
+                order.sort_by_key(|&u| key(ids[u]));
+            }
+        }
+        order
+    }
+    // Graph whose node `i` is node `order[i]` of `self`.
+    fn permuted(&self, order: &[usize]) -> Self {
+        let mut new_id = vec![0; self.n];
+        for (i, &u) in order.iter().enumerate() {
+            new_id[u] = i;
+        }
+        let adjs = order
+            .iter()
+            .map(|&u| {
+                let mut adj: Vec<usize> = self.adjs[u].iter().map(|&v| new_id[v]).collect();
+                adj.sort_unstable();
+                adj
+            })
+            .collect();
+        Self {
+            name: self.name.clone(),
+            n: self.n,
+            m: self.m,
+            adjs,
+        }
+    }
+    /// Renumbers the nodes by `strategy`. Also returns the old id of every
+    /// new node.
+    pub fn relabel_by(&self, strategy: &RelabelStrategy) -> (Self, Vec<usize>) {
+        let ids: Vec<usize> = (0..self.n).collect();
+        let order = self.relabel_order(&ids, strategy);
+        (self.permuted(&order), order)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star_and_edge() -> GeneralUndiGraph {
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [(40, 10), (40, 20), (40, 30), (7, 90)] {
+            g.add_edge(u, v);
+        }
+        g
+    }
+
+    #[test]
+    fn test_relabel_by() {
+        let g = star_and_edge();
+        let (compact, ids) = relabel_by(&g, &RelabelStrategy::Compact);
+        assert_eq!(ids, vec![7, 10, 20, 30, 40, 90]);
+        assert_eq!(compact.adjs[4], vec![1, 2, 3]);
+        let (by_degree, ids) = relabel_by(&g, &RelabelStrategy::Degree);
+        assert_eq!(ids[0], 40);
+        assert_eq!(by_degree.adjs[0].len(), 3);
+        let (_, ids) = relabel_by(&g, &RelabelStrategy::Bfs);
+        assert_eq!(ids, vec![7, 90, 10, 40, 20, 30]);
+        let key = |u: usize| usize::MAX - u;
+        let (_, ids) = relabel_by(&g, &RelabelStrategy::Key(&key));
+        assert_eq!(ids, vec![90, 40, 30, 20, 10, 7]);
+    }
+
+    #[test]
+    fn test_random_relabel() {
+        let g = NormalUndiGraph::from_apollo(2);
+        let (h, order) = g.relabel_by(&RelabelStrategy::Random(5));
+        assert_eq!((h.n, h.m), (g.n, g.m));
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..g.n).collect::<Vec<_>>());
+        for (i, &u) in order.iter().enumerate() {
+            let mut mapped: Vec<usize> = h.adjs[i].iter().map(|&v| order[v]).collect();
+            mapped.sort_unstable();
+            assert_eq!(mapped, g.adjs[u]);
+        }
+        assert_eq!(g.relabel_by(&RelabelStrategy::Random(5)).1, order);
+    }
+}
@@ -0,0 +1,542 @@
+use flate2::write::ZlibEncoder;
+use flate2::{Compression, Crc};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::general_graph::GeneralUndiGraph;
+use super::layout::ForceLayoutOptions;
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone, Copy)]
+pub enum NodeScore<'a> {
+    Uniform,
+    Degree,
+    Custom(&'a [f64]),
+}
+
+pub struct RenderOptions<'a> {
+    pub canvas_size: f64,
+    // computed with the default force-directed layout when absent
+    pub coords: Option<&'a [(f64, f64)]>,
+    pub size_by: NodeScore<'a>,
+    pub color_by: NodeScore<'a>,
+    pub min_radius: f64,
+    pub max_radius: f64,
+    pub labels: bool,
+}
+
+impl<'a> Default for RenderOptions<'a> {
+    fn default() -> Self {
+        Self {
+            canvas_size: 800.0,
+            coords: None,
+            size_by: NodeScore::Uniform,
+            color_by: NodeScore::Uniform,
+            min_radius: 3.0,
+            max_radius: 12.0,
+            labels: false,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum DotLabel<'a> {
+    // node ids as they appear in the graph
+    Id,
+    // no label, Graphviz then shows nothing inside the node
+    Empty,
+    // label from the node id
+    Custom(&'a dyn Fn(usize) -> String),
+}
+
+pub struct DotOptions<'a> {
+    pub labels: DotLabel<'a>,
+    // fill nodes with the palette by degree
+    pub color_by_degree: bool,
+    // edges written at most, in ascending order; None writes all of them
+    pub max_edges: Option<usize>,
+}
+
+impl<'a> Default for DotOptions<'a> {
+    fn default() -> Self {
+        Self {
+            labels: DotLabel::Id,
+            color_by_degree: false,
+            max_edges: None,
+        }
+    }
+}
+
+fn dot_quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// `nodes` ascending with their degrees, `edges` ascending with u < v.
+fn write_dot<W: Write, I: Iterator<Item = (usize, usize)>>(
+    w: &mut W,
+    name: &str,
+    nodes: &[(usize, usize)],
+    edges: I,
+    num_edges: usize,
+    options: &DotOptions,
+) -> std::io::Result<()> {
+    writeln!(w, "graph {} {{", dot_quote(name))?;
+    let lo = nodes.iter().map(|&(_, d)| d).min().unwrap_or(0);
+    let hi = nodes.iter().map(|&(_, d)| d).max().unwrap_or(0);
+    let span = if hi > lo { (hi - lo) as f64 } else { 1.0 };
+    for &(u, deg) in nodes {
+        let mut attrs = Vec::new();
+        match options.labels {
+            DotLabel::Id => {}
+            DotLabel::Empty => attrs.push("label=\"\"".to_string()),
+            DotLabel::Custom(label) => attrs.push(format!("label={}", dot_quote(&label(u)))),
+        }
+        if options.color_by_degree {
+            let color = palette_color((deg - lo) as f64 / span);
+            attrs.push(format!("style=filled, fillcolor=\"{}\"", color));
+        }
+        if attrs.is_empty() {
+            writeln!(w, "  {};", u)?;
+        } else {
+            writeln!(w, "  {} [{}];", u, attrs.join(", "))?;
+        }
+    }
+    let cap = options.max_edges.unwrap_or(usize::MAX);
+    for (u, v) in edges.take(cap) {
+        writeln!(w, "  {} -- {};", u, v)?;
+    }
+    if num_edges > cap {
+        writeln!(w, "  // {} more edges omitted", num_edges - cap)?;
+    }
+    writeln!(w, "}}")
+}
+
+// Viridis sampled at five stops, linearly interpolated.
+const PALETTE: [(f64, f64, f64); 5] = [
+    (68.0, 1.0, 84.0),
+    (59.0, 82.0, 139.0),
+    (33.0, 145.0, 140.0),
+    (94.0, 201.0, 98.0),
+    (253.0, 231.0, 37.0),
+];
+
+type Rgb = (u8, u8, u8);
+
+const DEFAULT_FILL: Rgb = (0x1f, 0x77, 0xb4);
+// edges are drawn #999 at half a pixel wide, i.e. half-covered in the PNG
+const EDGE_RGB: Rgb = (0x99, 0x99, 0x99);
+const EDGE_ALPHA: f64 = 0.5;
+
+fn palette_rgb(t: f64) -> Rgb {
+    let t = t.clamp(0.0, 1.0) * (PALETTE.len() - 1) as f64;
+    let i = (t.floor() as usize).min(PALETTE.len() - 2);
+    let f = t - i as f64;
+    let (a, b) = (PALETTE[i], PALETTE[i + 1]);
+    (
+        (a.0 + (b.0 - a.0) * f).round() as u8,
+        (a.1 + (b.1 - a.1) * f).round() as u8,
+        (a.2 + (b.2 - a.2) * f).round() as u8,
+    )
+}
+
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+fn palette_color(t: f64) -> String {
+    hex(palette_rgb(t))
+}
+
+// Projected drawing shared by the SVG and PNG writers.
+struct Scene {
+    size: f64,
+    lines: Vec<((f64, f64), (f64, f64))>,
+    // centre, radius and fill of every node
+    nodes: Vec<(f64, f64, f64, Rgb)>,
+}
+
+// White RGB raster with coverage-based antialiasing.
+struct Canvas {
+    side: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(side: usize) -> Self {
+        Self {
+            side,
+            pixels: vec![255; 3 * side * side],
+        }
+    }
+    fn blend(&mut self, x: i64, y: i64, (r, g, b): Rgb, alpha: f64) {
+        if x < 0 || y < 0 || x >= self.side as i64 || y >= self.side as i64 || alpha <= 0.0 {
+            return;
+        }
+        let i = 3 * (y as usize * self.side + x as usize);
+        let alpha = alpha.min(1.0);
+        for (p, c) in self.pixels[i..i + 3].iter_mut().zip([r, g, b]) {
+            *p = (*p as f64 * (1.0 - alpha) + c as f64 * alpha).round() as u8;
+        }
+    }
+    // Xiaolin Wu's line, spreading each step over the two nearest pixels.
+    fn line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), color: Rgb, alpha: f64) {
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (a0, b0, a1, b1) = if steep {
+            (y0, x0, y1, x1)
+        } else {
+            (x0, y0, x1, y1)
+        };
+        let (a0, b0, a1, b1) = if a0 > a1 {
+            (a1, b1, a0, b0)
+        } else {
+            (a0, b0, a1, b1)
+        };
+        let slope = if a1 > a0 { (b1 - b0) / (a1 - a0) } else { 0.0 };
+        let mut a = a0.round();
+        while a <= a1.round() {
+            let b = b0 + slope * (a - a0) - 0.5;
+            let (lo, frac) = (b.floor(), b - b.floor());
+            for (offset, cover) in [(0.0, 1.0 - frac), (1.0, frac)] {
+                let (x, y) = if steep {
+                    (lo + offset, a)
+                } else {
+                    (a, lo + offset)
+                };
+                self.blend(x as i64, y as i64, color, cover * alpha);
+            }
+            a += 1.0;
+        }
+    }
+    fn disc(&mut self, cx: f64, cy: f64, r: f64, color: Rgb) {
+        let (x_lo, x_hi) = ((cx - r - 1.0).floor() as i64, (cx + r + 1.0).ceil() as i64);
+        let (y_lo, y_hi) = ((cy - r - 1.0).floor() as i64, (cy + r + 1.0).ceil() as i64);
+        for y in y_lo..=y_hi {
+            for x in x_lo..=x_hi {
+                let d = (x as f64 + 0.5 - cx).hypot(y as f64 + 0.5 - cy);
+                self.blend(x, y, color, (r + 0.5 - d).clamp(0.0, 1.0));
+            }
+        }
+    }
+    fn write_png<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        fn chunk<W: Write>(w: &mut W, kind: &[u8; 4], data: &[u8]) -> std::io::Result<()> {
+            let mut crc = Crc::new();
+            crc.update(kind);
+            crc.update(data);
+            w.write_all(&(data.len() as u32).to_be_bytes())?;
+            w.write_all(kind)?;
+            w.write_all(data)?;
+            w.write_all(&crc.sum().to_be_bytes())
+        }
+        let side = self.side as u32;
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&side.to_be_bytes());
+        header.extend_from_slice(&side.to_be_bytes());
+        // 8-bit RGB, deflate, adaptive filtering, no interlace
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for row in self.pixels.chunks(3 * self.side) {
+            // filter type 0 before every scanline
+            encoder.write_all(&[0])?;
+            encoder.write_all(row)?;
+        }
+        w.write_all(b"\x89PNG\r\n\x1a\n")?;
+        chunk(w, b"IHDR", &header)?;
+        chunk(w, b"IDAT", &encoder.finish()?)?;
+        chunk(w, b"IEND", &[])
+    }
+}
+
+impl NormalUndiGraph {
+    // Scores rescaled into [0, 1]; constant scores map to 0.
+    fn normalized_scores(&self, score: NodeScore) -> Option<Vec<f64>> {
+        let raw: Vec<f64> = match score {
+            NodeScore::Uniform => return None,
+            NodeScore::Degree => self.adjs.iter().map(|adj| adj.len() as f64).collect(),
+            NodeScore::Custom(values) => values.to_vec(),
+        };
+        let lo = raw.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = raw.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let span = if hi > lo { hi - lo } else { 1.0 };
+        Some(raw.iter().map(|x| (x - lo) / span).collect())
+    }
+    // Projects the layout onto the canvas and sizes and colours the nodes.
+    fn scene(&self, options: &RenderOptions) -> std::io::Result<Scene> {
+        for score in [options.size_by, options.color_by] {
+            if let NodeScore::Custom(values) = score {
+                if values.len() != self.n {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidInput,
+                        format!("Expected {} node scores, got {}", self.n, values.len()),
+                    ));
+                }
+            }
+        }
+        let computed;
+        let coords = match options.coords {
+            Some(coords) if coords.len() != self.n => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("Expected {} node coordinates, got {}", self.n, coords.len()),
+                ));
+            }
+            Some(coords) => coords,
+            None => {
+                computed = self.fruchterman_reingold(&ForceLayoutOptions::default());
+                &computed
+            }
+        };
+        let sizes = self.normalized_scores(options.size_by);
+        let colors = self.normalized_scores(options.color_by);
+        let (size, margin) = (options.canvas_size, options.max_radius + 2.0);
+        let (mut x0, mut y0, mut x1, mut y1) = (f64::MAX, f64::MAX, f64::MIN, f64::MIN);
+        for &(x, y) in coords {
+            x0 = x0.min(x);
+            y0 = y0.min(y);
+            x1 = x1.max(x);
+            y1 = y1.max(y);
+        }
+        let scale = (size - 2.0 * margin) / (x1 - x0).max(y1 - y0).max(1e-12);
+        let project = |(x, y): (f64, f64)| (margin + (x - x0) * scale, margin + (y - y0) * scale);
+        let mut lines = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            let i = adj.partition_point(|v| v <= &u);
+            for &v in adj[i..].iter() {
+                lines.push((project(coords[u]), project(coords[v])));
+            }
+        }
+        let nodes = coords
+            .iter()
+            .enumerate()
+            .map(|(u, &c)| {
+                let (x, y) = project(c);
+                let r = match &sizes {
+                    Some(s) => {
+                        options.min_radius + s[u] * (options.max_radius - options.min_radius)
+                    }
+                    None => options.min_radius,
+                };
+                let fill = match &colors {
+                    Some(s) => palette_rgb(s[u]),
+                    None => DEFAULT_FILL,
+                };
+                (x, y, r, fill)
+            })
+            .collect();
+        Ok(Scene { size, lines, nodes })
+    }
+    pub fn render_svg_to<W: Write>(
+        &self,
+        w: &mut W,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        let scene = self.scene(options)?;
+        writeln!(
+            w,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\">",
+            scene.size
+        )?;
+        for &((ux, uy), (vx, vy)) in &scene.lines {
+            writeln!(
+                w,
+                "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#999\" stroke-width=\"0.5\"/>",
+                ux, uy, vx, vy
+            )?;
+        }
+        for (u, &(x, y, r, fill)) in scene.nodes.iter().enumerate() {
+            writeln!(
+                w,
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"{}\"/>",
+                x,
+                y,
+                r,
+                hex(fill)
+            )?;
+            if options.labels {
+                writeln!(
+                    w,
+                    "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"10\">{}</text>",
+                    x + r,
+                    y - r,
+                    u
+                )?;
+            }
+        }
+        writeln!(w, "</svg>")
+    }
+    pub fn render_svg<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.render_svg_to(&mut w, options)?;
+        w.flush()
+    }
+    /// Rasterises the same drawing as `render_svg_to` into an antialiased
+    /// RGB PNG of `canvas_size` pixels square. `labels` are not drawn, as
+    /// there is no font rasteriser; use the SVG output for labelled plots.
+    pub fn render_png_to<W: Write>(
+        &self,
+        w: &mut W,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        let scene = self.scene(options)?;
+        let side = scene.size.round().max(1.0) as usize;
+        let mut canvas = Canvas::new(side);
+        for &(a, b) in &scene.lines {
+            canvas.line(a, b, EDGE_RGB, EDGE_ALPHA);
+        }
+        for &(x, y, r, fill) in &scene.nodes {
+            canvas.disc(x, y, r, fill);
+        }
+        canvas.write_png(w)
+    }
+    pub fn render_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &RenderOptions,
+    ) -> std::io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.render_png_to(&mut w, options)?;
+        w.flush()
+    }
+    /// Writes the graph in Graphviz DOT, nodes by index.
+    pub fn to_dot<W: Write>(&self, w: &mut W, options: &DotOptions) -> std::io::Result<()> {
+        let nodes: Vec<(usize, usize)> =
+            self.adjs.iter().map(|adj| adj.len()).enumerate().collect();
+        let edges = self.adjs.iter().enumerate().flat_map(|(u, adj)| {
+            let i = adj.partition_point(|v| v <= &u);
+            adj[i..].iter().map(move |&v| (u, v))
+        });
+        write_dot(w, &self.name, &nodes, edges, self.m, options)
+    }
+}
+
+impl GeneralUndiGraph {
+    /// Writes the graph in Graphviz DOT, nodes and edges sorted by id.
+    pub fn to_dot<W: Write>(&self, w: &mut W, options: &DotOptions) -> std::io::Result<()> {
+        let mut degrees: std::collections::HashMap<usize, usize> =
+            self.nodes.iter().map(|&u| (u, 0)).collect();
+        let mut edges: Vec<(usize, usize)> = self
+            .edges
+            .iter()
+            .map(|&(u, v)| (u.min(v), u.max(v)))
+            .collect();
+        edges.sort_unstable();
+        for &(u, v) in &edges {
+            *degrees.entry(u).or_default() += 1;
+            *degrees.entry(v).or_default() += 1;
+        }
+        let mut nodes: Vec<(usize, usize)> = degrees.into_iter().collect();
+        nodes.sort_unstable();
+        write_dot(
+            w,
+            &self.name,
+            &nodes,
+            edges.into_iter(),
+            self.edges.len(),
+            options,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_svg_by_degree() {
+        let g = NormalUndiGraph::from_apollo(2);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("apollo.svg");
+        let options = RenderOptions {
+            size_by: NodeScore::Degree,
+            color_by: NodeScore::Degree,
+            labels: true,
+            ..Default::default()
+        };
+        g.render_svg(&path, &options).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(svg.matches("<circle").count(), g.n);
+        assert_eq!(svg.matches("<text").count(), g.n);
+        assert!(svg.contains(&palette_color(1.0)));
+        assert!(svg.contains("r=\"12.00\""));
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let g = NormalUndiGraph::from_koch(1);
+        let label = |u: usize| format!("v\"{}", u);
+        let options = DotOptions {
+            labels: DotLabel::Custom(&label),
+            color_by_degree: true,
+            max_edges: Some(3),
+        };
+        let mut buf = Vec::new();
+        g.to_dot(&mut buf, &options).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+        assert!(dot.starts_with("graph "));
+        assert_eq!(dot.matches(" -- ").count(), 3);
+        assert!(dot.contains(&format!("// {} more edges omitted", g.m - 3)));
+        assert!(dot.contains("label=\"v\\\"0\""));
+        assert!(dot.contains(&palette_color(1.0)));
+
+        let mut general = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [(40, 10), (40, 20), (7, 90)] {
+            general.add_edge(u, v);
+        }
+        let mut buf = Vec::new();
+        general.to_dot(&mut buf, &DotOptions::default()).unwrap();
+        let dot = String::from_utf8(buf).unwrap();
+        assert_eq!(dot.matches(" -- ").count(), general.num_edges());
+        assert!(dot.contains("  7 -- 90;\n  10 -- 40;"));
+        assert!(!dot.contains("omitted") && !dot.contains("fillcolor"));
+    }
+
+    #[test]
+    fn test_render_png() {
+        let g = NormalUndiGraph::from_koch(2);
+        let options = RenderOptions {
+            canvas_size: 64.0,
+            color_by: NodeScore::Degree,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        g.render_png_to(&mut buf, &options).unwrap();
+        assert!(buf.starts_with(b"\x89PNG\r\n\x1a\n"));
+        assert_eq!(&buf[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(buf[16..20].try_into().unwrap()), 64);
+        assert!(buf.ends_with(&[0xae, 0x42, 0x60, 0x82]));
+        // the scanlines decode to a non-blank 64 x 64 RGB image
+        let start = buf.windows(4).position(|w| w == b"IDAT").unwrap();
+        let len = u32::from_be_bytes(buf[start - 4..start].try_into().unwrap()) as usize;
+        let mut raw = Vec::new();
+        std::io::Read::read_to_end(
+            &mut flate2::read::ZlibDecoder::new(&buf[start + 4..start + 4 + len]),
+            &mut raw,
+        )
+        .unwrap();
+        assert_eq!(raw.len(), 64 * (1 + 3 * 64));
+        assert!(raw.iter().any(|&b| b != 0 && b != 255));
+    }
+
+    #[test]
+    fn test_render_svg_custom_scores() {
+        let g = NormalUndiGraph::from_koch(1);
+        let scores = vec![1.0; g.n - 1];
+        let options = RenderOptions {
+            color_by: NodeScore::Custom(&scores),
+            ..Default::default()
+        };
+        let mut buf: Vec<u8> = Vec::new();
+        assert!(g.render_svg_to(&mut buf, &options).is_err());
+        let coords = vec![(0.0, 0.0); g.n - 1];
+        let options = RenderOptions {
+            coords: Some(&coords),
+            ..Default::default()
+        };
+        let err = g.render_svg_to(&mut buf, &options).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+}
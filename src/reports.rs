@@ -0,0 +1,204 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphSummary {
+    pub name: String,
+    pub n: usize,
+    pub m: usize,
+    pub max_degree: usize,
+    pub mean_degree: f64,
+    pub degeneracy: usize,
+    pub average_clustering: f64,
+    pub num_components: usize,
+    pub largest_component: usize,
+}
+
+const SUMMARY_HEADER: &str = "name,n,m,max_degree,mean_degree,degeneracy,average_clustering,num_components,largest_component";
+
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl GraphSummary {
+    fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            self.name,
+            self.n,
+            self.m,
+            self.max_degree,
+            self.mean_degree,
+            self.degeneracy,
+            self.average_clustering,
+            self.num_components,
+            self.largest_component
+        )
+    }
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"name\": {}, \"n\": {}, \"m\": {}, \"max_degree\": {}, \"mean_degree\": {}, \"degeneracy\": {}, \"average_clustering\": {}, \"num_components\": {}, \"largest_component\": {}}}",
+            json_string(&self.name),
+            self.n,
+            self.m,
+            self.max_degree,
+            self.mean_degree,
+            self.degeneracy,
+            self.average_clustering,
+            self.num_components,
+            self.largest_component
+        )
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn summary(&self) -> GraphSummary {
+        GraphSummary {
+            name: self.name.clone(),
+            n: self.n,
+            m: self.m,
+            max_degree: self.max_degree(),
+            mean_degree: self.average_degree(),
+            degeneracy: self.degeneracy(),
+            average_clustering: self.average_clustering(),
+            num_components: self.num_components(),
+            largest_component: self.component_sizes().into_iter().max().unwrap_or(0),
+        }
+    }
+    /// Writes `<name>_nodes.csv` (degree, core number, clustering and component
+    /// of every node), `<name>_components.csv` and `<name>_summary.json` into
+    /// `dir`, creating it if needed.
+    pub fn write_report<P: AsRef<Path>>(&self, dir: P) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let cores = self.core_numbers();
+        let clustering = self.local_clustering();
+        let components = self.connected_components();
+        let mut wf = BufWriter::new(File::create(dir.join(format!("{}_nodes.csv", self.name)))?);
+        writeln!(wf, "node,degree,core,clustering,component")?;
+        for u in 0..self.n {
+            writeln!(
+                wf,
+                "{},{},{},{},{}",
+                u,
+                self.adjs[u].len(),
+                cores[u],
+                clustering[u],
+                components[u]
+            )?;
+        }
+        wf.flush()?;
+        let mut wf = BufWriter::new(File::create(
+            dir.join(format!("{}_components.csv", self.name)),
+        )?);
+        writeln!(wf, "component,size")?;
+        for (c, size) in self.component_sizes().into_iter().enumerate() {
+            writeln!(wf, "{},{}", c, size)?;
+        }
+        wf.flush()?;
+        let mut wf = File::create(dir.join(format!("{}_summary.json", self.name)))?;
+        writeln!(wf, "{}", self.summary().to_json())
+    }
+}
+
+/// Writes one CSV row per summary to `path`.
+pub fn write_summary_table<P: AsRef<Path>>(path: P, summaries: &[GraphSummary]) -> io::Result<()> {
+    let mut wf = BufWriter::new(File::create(path)?);
+    writeln!(wf, "{}", SUMMARY_HEADER)?;
+    for summary in summaries {
+        writeln!(wf, "{}", summary.csv_row())?;
+    }
+    wf.flush()
+}
+
+/// Builds generations `0..=generations` of an iterative generator and writes
+/// one summary row per generation to `<family>_generations.csv` in `dir`.
+pub fn write_generation_report<P, F>(
+    dir: P,
+    family: &str,
+    generations: usize,
+    generator: F,
+) -> io::Result<Vec<GraphSummary>>
+where
+    P: AsRef<Path>,
+    F: Fn(usize) -> NormalUndiGraph,
+{
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    let summaries: Vec<GraphSummary> = (0..=generations).map(|g| generator(g).summary()).collect();
+    let mut wf = BufWriter::new(File::create(
+        dir.join(format!("{}_generations.csv", family)),
+    )?);
+    writeln!(wf, "generation,{}", SUMMARY_HEADER)?;
+    for (g, summary) in summaries.iter().enumerate() {
+        writeln!(wf, "{},{}", g, summary.csv_row())?;
+    }
+    wf.flush()?;
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let g = NormalUndiGraph::from_koch(2);
+        g.write_report(dir.path()).unwrap();
+        let nodes = fs::read_to_string(dir.path().join("Koch_2_nodes.csv")).unwrap();
+        assert_eq!(nodes.lines().count(), g.n + 1);
+        let json = fs::read_to_string(dir.path().join("Koch_2_summary.json")).unwrap();
+        assert!(json.starts_with("{\"name\": \"Koch_2\", \"n\": 33, \"m\": 48,"));
+        assert_eq!(json_string("a\"b\\"), "\"a\\\"b\\\\\"");
+    }
+
+    #[test]
+    fn test_report_components() {
+        let dir = tempfile::tempdir().unwrap();
+        for g in [
+            NormalUndiGraph::from_pseudo_ext(2, 4),
+            NormalUndiGraph::from_koch(4),
+            NormalUndiGraph::from_apollo(4),
+        ] {
+            g.write_report(dir.path()).unwrap();
+            let path = dir.path().join(format!("{}_components.csv", g.name));
+            let components = fs::read_to_string(path).unwrap();
+            assert_eq!(components, format!("component,size\n0,{}\n", g.n));
+        }
+        let split = NormalUndiGraph::from_edges("2K2".to_string(), 5, &[(0, 1), (2, 3)]);
+        split.write_report(dir.path()).unwrap();
+        let components = fs::read_to_string(dir.path().join("2K2_components.csv")).unwrap();
+        assert_eq!(components.lines().count(), 4);
+        let nodes = fs::read_to_string(dir.path().join("2K2_nodes.csv")).unwrap();
+        assert_eq!(nodes.lines().nth(5), Some("4,0,0,0,2"));
+    }
+
+    #[test]
+    fn test_generation_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let summaries =
+            write_generation_report(dir.path(), "apollo", 3, NormalUndiGraph::from_apollo).unwrap();
+        for (g, s) in summaries.iter().enumerate() {
+            assert_eq!(s.m, 6 * 3usize.pow(g as u32));
+            assert_eq!(s.degeneracy, 3);
+            assert_eq!(s.num_components, 1);
+        }
+        let csv = fs::read_to_string(dir.path().join("apollo_generations.csv")).unwrap();
+        assert_eq!(csv.lines().count(), 5);
+    }
+}
@@ -0,0 +1,341 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::VecDeque;
+
+use super::config::ComputeConfig;
+use super::normal_graph::NormalUndiGraph;
+
+pub struct AliasSampler {
+    prob: Vec<f64>,
+    alias: Vec<usize>,
+}
+
+impl AliasSampler {
+    /// Vose's alias method. Returns `None` if `weights` is empty, contains a
+    /// negative or non-finite entry, or sums to zero.
+    pub fn new(weights: &[f64]) -> Option<Self> {
+        let k = weights.len();
+        if k == 0 || weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return None;
+        }
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        let mut prob: Vec<f64> = weights.iter().map(|w| w * k as f64 / total).collect();
+        let mut alias: Vec<usize> = (0..k).collect();
+        let (mut small, mut large): (Vec<usize>, Vec<usize>) = (0..k).partition(|&i| prob[i] < 1.0);
+        while let (Some(&s), Some(&l)) = (small.last(), large.last()) {
+            small.pop();
+            alias[s] = l;
+            prob[l] -= 1.0 - prob[s];
+            if prob[l] < 1.0 {
+                large.pop();
+                small.push(l);
+            }
+        }
+        for i in small.into_iter().chain(large) {
+            prob[i] = 1.0;
+        }
+        Some(Self { prob, alias })
+    }
+    pub fn len(&self) -> usize {
+        self.prob.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.prob.is_empty()
+    }
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let i = rng.gen_range(0..self.prob.len());
+        if rng.gen::<f64>() < self.prob[i] {
+            i
+        } else {
+            self.alias[i]
+        }
+    }
+}
+
+/// Draws `k` nodes independently with probability proportional to `weights`.
+pub fn sample_nodes_weighted(weights: &[f64], k: usize, seed: u64) -> Option<Vec<usize>> {
+    let sampler = AliasSampler::new(weights)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    Some((0..k).map(|_| sampler.sample(&mut rng)).collect())
+}
+
+/// Draws up to `k` distinct nodes by Efraimidis-Spirakis weighted reservoir
+/// keys, in sampling order. Zero-weight nodes are never drawn.
+pub fn sample_nodes_weighted_without_replacement(
+    weights: &[f64],
+    k: usize,
+    seed: u64,
+) -> Option<Vec<usize>> {
+    if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+        return None;
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut keys: Vec<(f64, usize)> = weights
+        .iter()
+        .enumerate()
+        .filter(|(_, &w)| w > 0.0)
+        .map(|(i, &w)| (rng.gen::<f64>().ln() / w, i))
+        .collect();
+    let k = k.min(keys.len());
+    if k == 0 {
+        return Some(Vec::new());
+    }
+    keys.select_nth_unstable_by(k - 1, |a, b| b.0.total_cmp(&a.0));
+    keys.truncate(k);
+    keys.sort_unstable_by(|a, b| b.0.total_cmp(&a.0));
+    Some(keys.into_iter().map(|(_, i)| i).collect())
+}
+
+pub struct TransitionTables<'a> {
+    graph: &'a NormalUndiGraph,
+    pub p: f64,
+    pub q: f64,
+    // edges[u][i] samples the next step after traversing u -> adjs[u][i]
+    edges: Vec<Vec<AliasSampler>>,
+}
+
+impl NormalUndiGraph {
+    /// Landmarks pairwise at least `min_distance` hops apart, chosen greedily
+    /// over the nodes in seeded random order: a node is taken unless it lies
+    /// within `min_distance - 1` hops of an earlier landmark. The set is
+    /// maximal, so every node is that close to some landmark, and spreads
+    /// over the graph better than uniform sampling.
+    pub fn mis_landmarks(&self, min_distance: usize, seed: u64) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.n).collect();
+        order.shuffle(&mut StdRng::seed_from_u64(seed));
+        // hops to the nearest landmark so far, kept exact up to min_distance
+        let mut dist = vec![usize::MAX; self.n];
+        let mut landmarks = Vec::new();
+        let mut queue = VecDeque::new();
+        for s in order {
+            if dist[s] < min_distance {
+                continue;
+            }
+            landmarks.push(s);
+            dist[s] = 0;
+            queue.push_back(s);
+            while let Some(u) = queue.pop_front() {
+                if dist[u] + 1 >= min_distance {
+                    continue;
+                }
+                for &v in &self.adjs[u] {
+                    if dist[v] > dist[u] + 1 {
+                        dist[v] = dist[u] + 1;
+                        queue.push_back(v);
+                    }
+                }
+            }
+        }
+        landmarks
+    }
+    /// Precomputes node2vec second-order transition tables with return
+    /// parameter `p` and in-out parameter `q`, both positive and finite.
+    pub fn transition_tables(&self, p: f64, q: f64) -> Result<TransitionTables<'_>, String> {
+        for (what, x) in [("p", p), ("q", q)] {
+            if !(x.is_finite() && x > 0.0) {
+                return Err(format!(
+                    "node2vec {} must be positive and finite, got {}",
+                    what, x
+                ));
+            }
+        }
+        let edges = self
+            .adjs
+            .iter()
+            .enumerate()
+            .map(|(prev, prev_adj)| {
+                prev_adj
+                    .iter()
+                    .map(|&cur| {
+                        let weights: Vec<f64> = self.adjs[cur]
+                            .iter()
+                            .map(|&x| {
+                                if x == prev {
+                                    1.0 / p
+                                } else if prev_adj.binary_search(&x).is_ok() {
+                                    1.0
+                                } else {
+                                    1.0 / q
+                                }
+                            })
+                            .collect();
+                        // positive weights, and `cur` has at least `prev` as neighbour
+                        AliasSampler::new(&weights).unwrap()
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(TransitionTables {
+            graph: self,
+            p,
+            q,
+            edges,
+        })
+    }
+}
+
+impl<'a> TransitionTables<'a> {
+    pub fn walk<R: Rng + ?Sized>(&self, start: usize, length: usize, rng: &mut R) -> Vec<usize> {
+        let adjs = &self.graph.adjs;
+        let mut walk = Vec::with_capacity(length);
+        if length == 0 {
+            return walk;
+        }
+        walk.push(start);
+        if length == 1 || adjs[start].is_empty() {
+            return walk;
+        }
+        let mut i = rng.gen_range(0..adjs[start].len());
+        let (mut prev, mut cur) = (start, adjs[start][i]);
+        walk.push(cur);
+        while walk.len() < length {
+            let j = self.edges[prev][i].sample(rng);
+            let next = adjs[cur][j];
+            i = j;
+            (prev, cur) = (cur, next);
+            walk.push(cur);
+        }
+        walk
+    }
+    pub fn walks(&self, walks_per_node: usize, length: usize, seed: u64) -> Vec<Vec<usize>> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut walks = Vec::with_capacity(walks_per_node * self.graph.n);
+        for _ in 0..walks_per_node {
+            for u in 0..self.graph.n {
+                walks.push(self.walk(u, length, &mut rng));
+            }
+        }
+        walks
+    }
+    /// `walks` generated on the threads of `config`, in the same order. Each
+    /// thread draws from its own stream of `seed`; in deterministic mode
+    /// every walk does, so the walks do not depend on the thread count.
+    pub fn walks_with(
+        &self,
+        walks_per_node: usize,
+        length: usize,
+        seed: u64,
+        config: &ComputeConfig,
+    ) -> Vec<Vec<usize>> {
+        let n = self.graph.n;
+        let total = walks_per_node * n;
+        if config.deterministic {
+            return config.par_map(total, |i| {
+                let mut rng = ComputeConfig::stream_rng(seed, i as u64);
+                self.walk(i % n, length, &mut rng)
+            });
+        }
+        let threads = config.num_threads.clamp(1, total.max(1));
+        let block = total.div_ceil(threads);
+        config
+            .par_map(threads, |t| {
+                let mut rng = ComputeConfig::stream_rng(seed, t as u64);
+                (t * block..total.min((t + 1) * block))
+                    .map(|i| self.walk(i % n, length, &mut rng))
+                    .collect::<Vec<_>>()
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_sampler() {
+        let sampler = AliasSampler::new(&[1.0, 0.0, 3.0]).unwrap();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut cnt = [0usize; 3];
+        for _ in 0..40000 {
+            cnt[sampler.sample(&mut rng)] += 1;
+        }
+        assert_eq!(cnt[1], 0);
+        assert!((cnt[2] as f64 / cnt[0] as f64 - 3.0).abs() < 0.2);
+        assert!(AliasSampler::new(&[]).is_none());
+        assert!(AliasSampler::new(&[0.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_sample_nodes_weighted() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let degs: Vec<f64> = g.degrees().iter().map(|&d| d as f64).collect();
+        let with = sample_nodes_weighted(&degs, 1000, 1).unwrap();
+        assert_eq!(with.len(), 1000);
+        assert_eq!(with, sample_nodes_weighted(&degs, 1000, 1).unwrap());
+        let hubs = with.iter().filter(|&&u| u < 4).count();
+        assert!(hubs > 1000 * 4 / g.n);
+
+        let without = sample_nodes_weighted_without_replacement(&degs, 10, 1).unwrap();
+        let mut dedup = without.clone();
+        dedup.sort_unstable();
+        dedup.dedup();
+        assert_eq!(dedup.len(), 10);
+        let sparse = [0.0, 2.0, 0.0, 1.0];
+        let all = sample_nodes_weighted_without_replacement(&sparse, 10, 3).unwrap();
+        assert_eq!(all.len(), 2);
+        assert!(!all.contains(&0) && !all.contains(&2));
+    }
+
+    #[test]
+    fn test_mis_landmarks() {
+        let g = NormalUndiGraph::from_koch(3);
+        for d in [1, 2, 4] {
+            let landmarks = g.mis_landmarks(d, 9);
+            assert_eq!(landmarks, g.mis_landmarks(d, 9));
+            let mut nearest = vec![usize::MAX; g.n];
+            for (i, &l) in landmarks.iter().enumerate() {
+                let dist = g.distances_from(l);
+                assert!(landmarks[i + 1..].iter().all(|&o| dist[o] >= d));
+                for (n, x) in nearest.iter_mut().zip(dist) {
+                    *n = (*n).min(x);
+                }
+            }
+            // maximal: no node could be added
+            assert!(nearest.iter().all(|&x| x < d.max(1)));
+        }
+        assert_eq!(g.mis_landmarks(1, 0).len(), g.n);
+    }
+
+    #[test]
+    fn test_node2vec_walks() {
+        let g = NormalUndiGraph::from_koch(2);
+        let tables = g.transition_tables(1.0, 0.5).unwrap();
+        for (p, q) in [
+            (0.0, 1.0),
+            (1.0, -2.0),
+            (f64::NAN, 1.0),
+            (1.0, f64::INFINITY),
+        ] {
+            assert!(g.transition_tables(p, q).is_err());
+        }
+        let walks = tables.walks(2, 10, 7);
+        assert_eq!(walks.len(), 2 * g.n);
+        for walk in &walks {
+            assert_eq!(walk.len(), 10);
+            for w in walk.windows(2) {
+                assert!(g.adjs[w[0]].binary_search(&w[1]).is_ok());
+            }
+        }
+        assert_eq!(walks, tables.walks(2, 10, 7));
+
+        let config = |num_threads, deterministic| ComputeConfig {
+            num_threads,
+            deterministic,
+            ..ComputeConfig::serial()
+        };
+        let parallel = tables.walks_with(2, 10, 7, &config(3, false));
+        assert_eq!(parallel.len(), 2 * g.n);
+        assert!(parallel.iter().enumerate().all(|(i, w)| w[0] == i % g.n));
+        assert_eq!(
+            tables.walks_with(2, 10, 7, &config(1, true)),
+            tables.walks_with(2, 10, 7, &config(4, true))
+        );
+    }
+}
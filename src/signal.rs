@@ -0,0 +1,184 @@
+use nalgebra as na;
+
+use super::linalg::{extreme_eigenpairs, EigenOptions};
+use super::normal_graph::NormalUndiGraph;
+use super::spectral::{SpectrumMatrix, DEFAULT_SPECTRUM_MAX_NODES};
+
+/// Laplacian eigenbasis kept for repeated graph Fourier transforms. The
+/// columns of `basis` are orthonormal eigenvectors by increasing frequency
+/// `eigenvalues`; a truncated basis holds only the lowest frequencies.
+pub struct GraphFourier {
+    pub eigenvalues: Vec<f64>,
+    pub basis: na::DMatrix<f64>,
+}
+
+impl GraphFourier {
+    pub fn num_frequencies(&self) -> usize {
+        self.eigenvalues.len()
+    }
+    /// Fourier coefficients `U^T x` of a node signal.
+    pub fn gft(&self, signal: &[f64]) -> Vec<f64> {
+        assert_eq!(signal.len(), self.basis.nrows(), "signal length");
+        let x = na::DVector::from_column_slice(signal);
+        self.basis.tr_mul(&x).iter().copied().collect()
+    }
+    /// Node signal `U c` of Fourier coefficients; on a truncated basis the
+    /// projection onto the kept frequencies.
+    pub fn igft(&self, coefficients: &[f64]) -> Vec<f64> {
+        assert_eq!(
+            coefficients.len(),
+            self.num_frequencies(),
+            "coefficient count"
+        );
+        let c = na::DVector::from_column_slice(coefficients);
+        (&self.basis * c).iter().copied().collect()
+    }
+    /// Signal with each frequency scaled by `response(λ)`.
+    pub fn filter<F: Fn(f64) -> f64>(&self, signal: &[f64], response: F) -> Vec<f64> {
+        let coefficients: Vec<f64> = self
+            .gft(signal)
+            .into_iter()
+            .zip(&self.eigenvalues)
+            .map(|(c, &lambda)| c * response(lambda))
+            .collect();
+        self.igft(&coefficients)
+    }
+}
+
+impl NormalUndiGraph {
+    /// Full Laplacian eigenbasis by dense decomposition, up to
+    /// `DEFAULT_SPECTRUM_MAX_NODES` nodes.
+    pub fn fourier_basis(&self) -> Result<GraphFourier, String> {
+        if self.n > DEFAULT_SPECTRUM_MAX_NODES {
+            return Err(format!(
+                "Graph '{}' has {} nodes, exceeding the dense spectrum cap of {}",
+                self.name, self.n, DEFAULT_SPECTRUM_MAX_NODES
+            ));
+        }
+        let eigen = self
+            .spectrum_matrix(SpectrumMatrix::Laplacian)
+            .symmetric_eigen();
+        let mut order: Vec<usize> = (0..self.n).collect();
+        order.sort_unstable_by(|&i, &j| eigen.eigenvalues[i].total_cmp(&eigen.eigenvalues[j]));
+        Ok(GraphFourier {
+            eigenvalues: order
+                .iter()
+                .map(|&i| eigen.eigenvalues[i].max(0.0))
+                .collect(),
+            basis: eigen.eigenvectors.select_columns(&order),
+        })
+    }
+    /// The `k` lowest frequencies of the Laplacian eigenbasis, found
+    /// matrix-free by restarted Lanczos; for graphs too large for
+    /// `fourier_basis`. Fails when Lanczos does not converge.
+    pub fn fourier_basis_truncated(&self, k: usize) -> Result<GraphFourier, String> {
+        let apply = |x: &[f64], y: &mut [f64]| self.laplacian_apply(x, y);
+        let pairs = extreme_eigenpairs(apply, self.n, k, false, &EigenOptions::default())?;
+        let mut basis = na::DMatrix::zeros(self.n, pairs.len());
+        for (j, (_, x)) in pairs.iter().enumerate() {
+            basis.set_column(j, &na::DVector::from_column_slice(x));
+        }
+        Ok(GraphFourier {
+            eigenvalues: pairs.iter().map(|&(lambda, _)| lambda.max(0.0)).collect(),
+            basis,
+        })
+    }
+    /// `h(L) x` for a spectral response `h` without any eigenvectors, by
+    /// the degree-`order` Chebyshev expansion of `h` over `[0, 2 d_max]`,
+    /// which holds the whole Laplacian spectrum. Costs `order` sparse
+    /// products; smooth responses converge fast in `order`.
+    pub fn chebyshev_filter<F: Fn(f64) -> f64>(
+        &self,
+        signal: &[f64],
+        response: F,
+        order: usize,
+    ) -> Vec<f64> {
+        assert_eq!(signal.len(), self.n, "signal length");
+        let lambda_max = 2.0 * self.adjs.iter().map(|adj| adj.len()).max().unwrap_or(0) as f64;
+        if lambda_max == 0.0 {
+            return signal.iter().map(|x| response(0.0) * x).collect();
+        }
+        // coefficients by Chebyshev-Gauss quadrature at order + 1 nodes
+        let nodes = order + 1;
+        let coefficients: Vec<f64> = (0..=order)
+            .map(|k| {
+                let sum: f64 = (0..nodes)
+                    .map(|j| {
+                        let theta = std::f64::consts::PI * (j as f64 + 0.5) / nodes as f64;
+                        response((theta.cos() + 1.0) * lambda_max / 2.0) * (k as f64 * theta).cos()
+                    })
+                    .sum();
+                2.0 * sum / nodes as f64
+            })
+            .collect();
+        // `L~ x = 2 L x / lambda_max - x` has its spectrum in [-1, 1]
+        let scaled = |x: &[f64], y: &mut [f64]| {
+            self.laplacian_apply(x, y);
+            for (y, x) in y.iter_mut().zip(x) {
+                *y = 2.0 * *y / lambda_max - x;
+            }
+        };
+        let mut prev = signal.to_vec();
+        let mut out: Vec<f64> = prev.iter().map(|x| coefficients[0] / 2.0 * x).collect();
+        if order == 0 {
+            return out;
+        }
+        let mut cur = vec![0.0; self.n];
+        scaled(&prev, &mut cur);
+        for (o, c) in out.iter_mut().zip(&cur) {
+            *o += coefficients[1] * c;
+        }
+        let mut next = vec![0.0; self.n];
+        for &ck in &coefficients[2..] {
+            scaled(&cur, &mut next);
+            for (n, p) in next.iter_mut().zip(&prev) {
+                *n = 2.0 * *n - p;
+            }
+            for (o, n) in out.iter_mut().zip(&next) {
+                *o += ck * n;
+            }
+            std::mem::swap(&mut prev, &mut cur);
+            std::mem::swap(&mut cur, &mut next);
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gft_round_trip() {
+        let g = NormalUndiGraph::from_apollo(3);
+        let fourier = g.fourier_basis().unwrap();
+        assert_eq!(fourier.num_frequencies(), g.n);
+        assert!(fourier.eigenvalues.windows(2).all(|w| w[0] <= w[1]));
+        let signal: Vec<f64> = (0..g.n).map(|u| (u as f64).sin()).collect();
+        let back = fourier.igft(&fourier.gft(&signal));
+        assert!(back.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 1e-9));
+        // a constant signal has only the zero frequency
+        let coefficients = fourier.gft(&vec![1.0; g.n]);
+        assert!((coefficients[0].abs() - (g.n as f64).sqrt()).abs() < 1e-9);
+        assert!(coefficients[1..].iter().all(|c| c.abs() < 1e-9));
+
+        let truncated = g.fourier_basis_truncated(4).unwrap();
+        for (a, b) in truncated.eigenvalues.iter().zip(&fourier.eigenvalues) {
+            assert!((a - b).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_chebyshev_filter() {
+        let g = NormalUndiGraph::from_koch(2);
+        let fourier = g.fourier_basis().unwrap();
+        let signal: Vec<f64> = (0..g.n).map(|u| (u % 5) as f64).collect();
+        let heat = |lambda: f64| (-0.5 * lambda).exp();
+        let exact = fourier.filter(&signal, heat);
+        let approx = g.chebyshev_filter(&signal, heat, 30);
+        assert!(exact.iter().zip(&approx).all(|(a, b)| (a - b).abs() < 1e-8));
+        // the identity response returns the signal
+        let same = g.chebyshev_filter(&signal, |_| 1.0, 3);
+        assert!(same.iter().zip(&signal).all(|(a, b)| (a - b).abs() < 1e-12));
+    }
+}
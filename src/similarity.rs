@@ -0,0 +1,325 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::normal_graph::NormalUndiGraph;
+
+pub struct GedOptions {
+    pub node_cost: f64,
+    pub edge_cost: f64,
+    // A* is used when both graphs have at most this many nodes, beam search otherwise
+    pub exact_max_nodes: usize,
+    pub beam_width: usize,
+}
+
+impl Default for GedOptions {
+    fn default() -> Self {
+        Self {
+            node_cost: 1.0,
+            edge_cost: 1.0,
+            exact_max_nodes: 10,
+            beam_width: 64,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GedResult {
+    pub cost: f64,
+    // mapping[u] is the image of node u of the first graph, None if deleted
+    pub mapping: Vec<Option<usize>>,
+    pub exact: bool,
+}
+
+#[derive(Clone)]
+struct GedState {
+    bound: f64,
+    cost: f64,
+    mapping: Vec<Option<usize>>,
+    used: Vec<bool>,
+    complete: bool,
+}
+
+impl PartialEq for GedState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for GedState {}
+
+impl PartialOrd for GedState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for GedState {
+    // min-heap on the bound, preferring deeper states on ties
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .bound
+            .total_cmp(&self.bound)
+            .then(self.mapping.len().cmp(&other.mapping.len()))
+    }
+}
+
+struct GedSearch<'a> {
+    g1: &'a NormalUndiGraph,
+    g2: &'a NormalUndiGraph,
+    order: Vec<usize>,
+    options: &'a GedOptions,
+}
+
+impl<'a> GedSearch<'a> {
+    fn has_edge(g: &NormalUndiGraph, u: usize, v: usize) -> bool {
+        g.adjs[u].binary_search(&v).is_ok()
+    }
+    fn heuristic(&self, depth: usize, used_cnt: usize) -> f64 {
+        let rest1 = self.order.len() - depth;
+        let rest2 = self.g2.n - used_cnt;
+        self.options.node_cost * rest1.abs_diff(rest2) as f64
+    }
+    fn root(&self) -> GedState {
+        GedState {
+            bound: self.heuristic(0, 0),
+            cost: 0.0,
+            mapping: Vec::with_capacity(self.g1.n),
+            used: vec![false; self.g2.n],
+            complete: false,
+        }
+    }
+    fn expand(&self, state: &GedState) -> Vec<GedState> {
+        let depth = state.mapping.len();
+        if depth == self.order.len() {
+            let mut cost = state.cost;
+            let unused: Vec<usize> = (0..self.g2.n).filter(|&v| !state.used[v]).collect();
+            cost += self.options.node_cost * unused.len() as f64;
+            for (u, adj) in self.g2.adjs.iter().enumerate() {
+                for &v in adj {
+                    if u < v && (!state.used[u] || !state.used[v]) {
+                        cost += self.options.edge_cost;
+                    }
+                }
+            }
+            let mut done = state.clone();
+            done.cost = cost;
+            done.bound = cost;
+            done.complete = true;
+            return vec![done];
+        }
+        let u = self.order[depth];
+        let used_cnt = state.used.iter().filter(|&&b| b).count();
+        let targets = (0..self.g2.n)
+            .filter(|&v| !state.used[v])
+            .map(Some)
+            .chain(std::iter::once(None));
+        targets
+            .map(|target| {
+                let mut cost = state.cost;
+                if target.is_none() {
+                    cost += self.options.node_cost;
+                }
+                for (j, &image) in state.mapping.iter().enumerate() {
+                    let e1 = Self::has_edge(self.g1, u, self.order[j]);
+                    let e2 = match (target, image) {
+                        (Some(v), Some(w)) => Self::has_edge(self.g2, v, w),
+                        _ => false,
+                    };
+                    if e1 != e2 {
+                        cost += self.options.edge_cost;
+                    }
+                }
+                let mut next = state.clone();
+                next.mapping.push(target);
+                let mut cnt = used_cnt;
+                if let Some(v) = target {
+                    next.used[v] = true;
+                    cnt += 1;
+                }
+                next.cost = cost;
+                next.bound = cost + self.heuristic(depth + 1, cnt);
+                next
+            })
+            .collect()
+    }
+    fn finish(&self, state: GedState, exact: bool) -> GedResult {
+        let mut mapping = vec![None; self.g1.n];
+        for (j, image) in state.mapping.into_iter().enumerate() {
+            mapping[self.order[j]] = image;
+        }
+        GedResult {
+            cost: state.cost,
+            mapping,
+            exact,
+        }
+    }
+    fn astar(&self) -> GedResult {
+        let mut heap = BinaryHeap::from([self.root()]);
+        while let Some(state) = heap.pop() {
+            if state.complete {
+                return self.finish(state, true);
+            }
+            heap.extend(self.expand(&state));
+        }
+        unreachable!("the search space always contains a complete mapping")
+    }
+    fn beam(&self) -> GedResult {
+        let mut beam = vec![self.root()];
+        while !beam[0].complete {
+            let mut next: Vec<GedState> = beam.iter().flat_map(|s| self.expand(s)).collect();
+            next.sort_by(|a, b| a.bound.total_cmp(&b.bound));
+            next.truncate(self.options.beam_width.max(1));
+            beam = next;
+        }
+        let best = beam
+            .into_iter()
+            .min_by(|a, b| a.cost.total_cmp(&b.cost))
+            .unwrap();
+        self.finish(best, false)
+    }
+}
+
+// Label histograms of both graphs over all WL rounds, with labels compressed
+// through a dictionary shared between the two graphs.
+fn wl_histograms(graphs: [&NormalUndiGraph; 2], iterations: usize) -> [HashMap<usize, usize>; 2] {
+    let mut dict: HashMap<(usize, Vec<usize>), usize> = HashMap::new();
+    let mut labels: Vec<Vec<usize>> = graphs
+        .iter()
+        .map(|g| g.adjs.iter().map(|adj| adj.len()).collect())
+        .collect();
+    let mut hists = [HashMap::new(), HashMap::new()];
+    let mut offset = 0;
+    for round in 0..=iterations {
+        let mut max_label = 0;
+        for (hist, ls) in hists.iter_mut().zip(&labels) {
+            for &l in ls {
+                *hist.entry(offset + l).or_insert(0) += 1;
+                max_label = max_label.max(l);
+            }
+        }
+        offset += max_label + 1;
+        if round == iterations {
+            break;
+        }
+        dict.clear();
+        labels = graphs
+            .iter()
+            .zip(&labels)
+            .map(|(g, ls)| {
+                (0..g.n)
+                    .map(|u| {
+                        let mut sig: Vec<usize> = g.adjs[u].iter().map(|&v| ls[v]).collect();
+                        sig.sort_unstable();
+                        let next = dict.len();
+                        *dict.entry((ls[u], sig)).or_insert(next)
+                    })
+                    .collect()
+            })
+            .collect();
+    }
+    hists
+}
+
+fn dot(a: &HashMap<usize, usize>, b: &HashMap<usize, usize>) -> f64 {
+    a.iter()
+        .filter_map(|(k, x)| b.get(k).map(|y| (x * y) as f64))
+        .sum()
+}
+
+impl NormalUndiGraph {
+    /// Weisfeiler-Lehman subtree kernel: the sum over rounds 0..=iterations of
+    /// the inner products of label histograms, starting from degree labels.
+    pub fn wl_kernel(&self, other: &NormalUndiGraph, iterations: usize) -> f64 {
+        let [h1, h2] = wl_histograms([self, other], iterations);
+        dot(&h1, &h2)
+    }
+    /// WL kernel scaled to `k(G, H) / sqrt(k(G, G) k(H, H))`, in [0, 1].
+    pub fn wl_kernel_normalized(&self, other: &NormalUndiGraph, iterations: usize) -> f64 {
+        let [h1, h2] = wl_histograms([self, other], iterations);
+        let norm = (dot(&h1, &h1) * dot(&h2, &h2)).sqrt();
+        if norm == 0.0 {
+            0.0
+        } else {
+            dot(&h1, &h2) / norm
+        }
+    }
+    /// Edit distance under unit-style node/edge insertion and deletion costs.
+    /// Exact via A* for small graphs, an upper bound via beam search otherwise.
+    pub fn graph_edit_distance(&self, other: &NormalUndiGraph, options: &GedOptions) -> GedResult {
+        let mut order: Vec<usize> = (0..self.n).collect();
+        order.sort_by_key(|&u| (std::cmp::Reverse(self.adjs[u].len()), u));
+        let search = GedSearch {
+            g1: self,
+            g2: other,
+            order,
+            options,
+        };
+        if self.n <= options.exact_max_nodes && other.n <= options.exact_max_nodes {
+            search.astar()
+        } else {
+            search.beam()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle(n: usize) -> NormalUndiGraph {
+        let edges: Vec<(usize, usize)> = (0..n).map(|i| (i, (i + 1) % n)).collect();
+        NormalUndiGraph::from_edges(format!("C{}", n), n, &edges)
+    }
+
+    #[test]
+    fn test_ged_exact() {
+        let options = GedOptions::default();
+        let c4 = cycle(4);
+        assert_eq!(c4.graph_edit_distance(&c4, &options).cost, 0.0);
+        // C5 -> C4: delete a node and its two edges, insert one edge
+        let res = cycle(5).graph_edit_distance(&c4, &options);
+        assert!(res.exact);
+        assert_eq!(res.cost, 4.0);
+        assert_eq!(res.mapping.iter().filter(|m| m.is_none()).count(), 1);
+        // K4 -> C4: delete the two diagonals
+        let k4 = NormalUndiGraph::from_apollo(0);
+        assert_eq!(k4.graph_edit_distance(&c4, &options).cost, 2.0);
+    }
+
+    #[test]
+    fn test_wl_kernel() {
+        let c6 = cycle(6);
+        // every node of C6 keeps the same label in every round
+        assert_eq!(c6.wl_kernel(&c6, 2), 3.0 * 36.0);
+        assert!((c6.wl_kernel_normalized(&c6, 3) - 1.0).abs() < 1e-12);
+        // two triangles vs a hexagon are 1-WL indistinguishable
+        let triangles = NormalUndiGraph::from_edges(
+            "2C3".to_string(),
+            6,
+            &[(0, 1), (1, 2), (2, 0), (3, 4), (4, 5), (5, 3)],
+        );
+        assert!((c6.wl_kernel_normalized(&triangles, 3) - 1.0).abs() < 1e-12);
+        let koch = NormalUndiGraph::from_koch(1);
+        let apollo = NormalUndiGraph::from_apollo(1);
+        let k = koch.wl_kernel_normalized(&apollo, 2);
+        assert!((0.0..1.0).contains(&k));
+        assert_eq!(koch.wl_kernel(&apollo, 2), apollo.wl_kernel(&koch, 2));
+    }
+
+    #[test]
+    fn test_ged_beam_upper_bound() {
+        let g1 = NormalUndiGraph::from_pseudofractal(1);
+        let g2 = NormalUndiGraph::from_apollo(1);
+        let exact = g1.graph_edit_distance(&g2, &GedOptions::default());
+        let beam = g1.graph_edit_distance(
+            &g2,
+            &GedOptions {
+                exact_max_nodes: 0,
+                beam_width: 16,
+                ..Default::default()
+            },
+        );
+        assert!(exact.exact && !beam.exact);
+        assert!(beam.cost >= exact.cost);
+    }
+}
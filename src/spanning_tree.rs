@@ -0,0 +1,282 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+use super::normal_graph::NormalUndiGraph;
+use super::spatial::HeapEntry;
+
+// rate of the exponential shifts in each round of MPX clustering
+const MPX_BETA: f64 = 0.2;
+
+pub struct SpanningForest {
+    // parent[u] is None for the root of each tree
+    pub parent: Vec<Option<usize>>,
+    pub depth: Vec<usize>,
+}
+
+pub struct LowStretchTree {
+    pub forest: SpanningForest,
+    pub average_stretch: f64,
+}
+
+impl SpanningForest {
+    /// Roots every tree of an acyclic edge set at its smallest node.
+    pub(crate) fn from_edges(n: usize, edges: &[(usize, usize)]) -> Self {
+        NormalUndiGraph::from_edges(String::new(), n, edges).bfs_forest()
+    }
+    pub fn num_edges(&self) -> usize {
+        self.parent.iter().filter(|p| p.is_some()).count()
+    }
+    pub fn to_graph(&self, name: String) -> NormalUndiGraph {
+        let edges: Vec<(usize, usize)> = self
+            .parent
+            .iter()
+            .enumerate()
+            .filter_map(|(u, p)| p.map(|p| (u, p)))
+            .collect();
+        NormalUndiGraph::from_edges(name, self.parent.len(), &edges)
+    }
+    /// Length of the tree path between `u` and `v`, None across trees.
+    pub fn tree_distance(&self, u: usize, v: usize) -> Option<usize> {
+        let (mut a, mut b) = (u, v);
+        let mut dist = 0;
+        while a != b {
+            if self.depth[a] < self.depth[b] {
+                std::mem::swap(&mut a, &mut b);
+            }
+            a = self.parent[a]?;
+            dist += 1;
+        }
+        Some(dist)
+    }
+    /// Mean tree distance between the endpoints of the edges of `g`.
+    pub fn average_stretch(&self, g: &NormalUndiGraph) -> f64 {
+        let mut total = 0;
+        for (u, adj) in g.adjs.iter().enumerate() {
+            for &v in adj.iter().filter(|&&v| u < v) {
+                total += self.tree_distance(u, v).unwrap_or(0);
+            }
+        }
+        if g.m == 0 {
+            0.0
+        } else {
+            total as f64 / g.m as f64
+        }
+    }
+}
+
+impl NormalUndiGraph {
+    // Miller-Peng-Xu clustering: every node starts a BFS at time `-delta_u`
+    // with `delta_u ~ Exp(beta)` and each node joins the first BFS to reach it.
+    // Returns the cluster centre and BFS parent of every node.
+    fn mpx_clusters<R: Rng>(&self, beta: f64, rng: &mut R) -> (Vec<usize>, Vec<usize>) {
+        let shifts: Vec<f64> = (0..self.n)
+            .map(|_| -(1.0 - rng.gen::<f64>()).ln() / beta)
+            .collect();
+        let mut arrival: Vec<f64> = shifts.iter().map(|d| -d).collect();
+        let mut center: Vec<usize> = (0..self.n).collect();
+        let mut parent: Vec<usize> = (0..self.n).collect();
+        let mut done = vec![false; self.n];
+        let mut heap: BinaryHeap<HeapEntry> = (0..self.n)
+            .map(|u| HeapEntry {
+                priority: arrival[u],
+                node: u,
+            })
+            .collect();
+        while let Some(HeapEntry { priority, node: u }) = heap.pop() {
+            if done[u] || priority > arrival[u] {
+                continue;
+            }
+            done[u] = true;
+            for &v in &self.adjs[u] {
+                if !done[v] && priority + 1.0 < arrival[v] {
+                    arrival[v] = priority + 1.0;
+                    center[v] = center[u];
+                    parent[v] = u;
+                    heap.push(HeapEntry {
+                        priority: arrival[v],
+                        node: v,
+                    });
+                }
+            }
+        }
+        (center, parent)
+    }
+    /// AKPW-style low-stretch spanning forest: repeatedly clusters the graph by
+    /// exponentially shifted BFS, keeps the BFS trees inside clusters and
+    /// contracts every cluster, until no edges remain.
+    pub fn low_stretch_spanning_tree(&self, seed: u64) -> LowStretchTree {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut tree_edges = Vec::with_capacity(self.n.saturating_sub(1));
+        let mut level = NormalUndiGraph::from_edges(self.name.clone(), self.n, &self.edge_list());
+        // original edge represented by each edge of the contracted graph
+        let mut rep: HashMap<(usize, usize), (usize, usize)> =
+            self.edge_list().into_iter().map(|e| (e, e)).collect();
+        while level.m > 0 {
+            let (center, parent) = level.mpx_clusters(MPX_BETA, &mut rng);
+            let mut id = vec![usize::MAX; level.n];
+            let mut k = 0;
+            for u in 0..level.n {
+                if center[u] == u {
+                    id[u] = k;
+                    k += 1;
+                }
+            }
+            if k == level.n {
+                continue;
+            }
+            for (u, &p) in parent.iter().enumerate() {
+                if p != u {
+                    tree_edges.push(rep[&(u.min(p), u.max(p))]);
+                }
+            }
+            let mut next_rep = HashMap::new();
+            for (a, adj) in level.adjs.iter().enumerate() {
+                for &b in adj.iter().filter(|&&b| a < b) {
+                    let (x, y) = (id[center[a]], id[center[b]]);
+                    if x != y {
+                        next_rep.entry((x.min(y), x.max(y))).or_insert(rep[&(a, b)]);
+                    }
+                }
+            }
+            let edges: Vec<(usize, usize)> = next_rep.keys().copied().collect();
+            level = NormalUndiGraph::from_edges(self.name.clone(), k, &edges);
+            rep = next_rep;
+        }
+        let forest = SpanningForest::from_edges(self.n, &tree_edges);
+        LowStretchTree {
+            average_stretch: forest.average_stretch(self),
+            forest,
+        }
+    }
+    // Search tree grown from each root in turn, skipping roots already reached.
+    fn search_forest(
+        &self,
+        roots: impl Iterator<Item = usize>,
+        breadth_first: bool,
+    ) -> SpanningForest {
+        let mut parent = vec![None; self.n];
+        let mut depth = vec![usize::MAX; self.n];
+        for root in roots {
+            if depth[root] != usize::MAX {
+                continue;
+            }
+            depth[root] = 0;
+            if breadth_first {
+                let mut queue = VecDeque::from([root]);
+                while let Some(u) = queue.pop_front() {
+                    for &v in &self.adjs[u] {
+                        if depth[v] == usize::MAX {
+                            depth[v] = depth[u] + 1;
+                            parent[v] = Some(u);
+                            queue.push_back(v);
+                        }
+                    }
+                }
+            } else {
+                // stack of nodes with the index of their next neighbour to try
+                let mut stack = vec![(root, 0)];
+                while let Some((u, i)) = stack.last_mut() {
+                    let u = *u;
+                    match self.adjs[u].get(*i) {
+                        Some(&v) => {
+                            *i += 1;
+                            if depth[v] == usize::MAX {
+                                depth[v] = depth[u] + 1;
+                                parent[v] = Some(u);
+                                stack.push((v, 0));
+                            }
+                        }
+                        None => {
+                            stack.pop();
+                        }
+                    }
+                }
+            }
+        }
+        SpanningForest { parent, depth }
+    }
+    /// BFS tree of the component of `source`; other nodes have no parent and
+    /// depth `usize::MAX`.
+    pub fn bfs_tree(&self, source: usize) -> SpanningForest {
+        self.search_forest(std::iter::once(source), true)
+    }
+    /// DFS tree of the component of `source`, visiting neighbours in
+    /// increasing order.
+    pub fn dfs_tree(&self, source: usize) -> SpanningForest {
+        self.search_forest(std::iter::once(source), false)
+    }
+    /// BFS trees of all components, each rooted at its smallest node.
+    pub fn bfs_forest(&self) -> SpanningForest {
+        self.search_forest(0..self.n, true)
+    }
+    pub fn dfs_forest(&self) -> SpanningForest {
+        self.search_forest(0..self.n, false)
+    }
+    pub(crate) fn edge_list(&self) -> Vec<(usize, usize)> {
+        let mut edges = Vec::with_capacity(self.m);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            edges.extend(adj.iter().filter(|&&v| u < v).map(|&v| (u, v)));
+        }
+        edges
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_stretch_spanning_tree() {
+        let g = NormalUndiGraph::from_apollo(4);
+        let lst = g.low_stretch_spanning_tree(3);
+        assert_eq!(lst.forest.num_edges(), g.n - 1);
+        for (u, p) in lst.forest.parent.iter().enumerate() {
+            if let Some(p) = *p {
+                assert!(g.adjs[u].binary_search(&p).is_ok());
+            }
+        }
+        assert!(lst.average_stretch >= 1.0);
+        let tree = lst.forest.to_graph("tree".to_string());
+        assert_eq!(tree.girth(), None);
+        assert_eq!(tree.num_components(), 1);
+    }
+
+    #[test]
+    fn test_stretch_of_forest() {
+        // a forest is its own spanning forest with stretch 1
+        let g = NormalUndiGraph::from_edges("g".to_string(), 5, &[(0, 1), (1, 2), (3, 4)]);
+        let lst = g.low_stretch_spanning_tree(0);
+        assert_eq!(lst.forest.num_edges(), 3);
+        assert_eq!(lst.average_stretch, 1.0);
+        assert_eq!(lst.forest.tree_distance(0, 2), Some(2));
+        assert_eq!(lst.forest.tree_distance(0, 4), None);
+    }
+
+    #[test]
+    fn test_bfs_dfs_tree() {
+        let g = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            6,
+            &[(0, 1), (0, 2), (1, 3), (2, 3), (4, 5)],
+        );
+        let bfs = g.bfs_tree(0);
+        assert_eq!(
+            bfs.parent,
+            vec![None, Some(0), Some(0), Some(1), None, None]
+        );
+        assert_eq!(&bfs.depth[..4], &g.distances_from(0)[..4]);
+        assert_eq!(bfs.depth[4], usize::MAX);
+        let dfs = g.dfs_tree(0);
+        assert_eq!(
+            dfs.parent,
+            vec![None, Some(0), Some(3), Some(1), None, None]
+        );
+        assert_eq!(dfs.depth[2], 3);
+        assert_eq!(dfs.tree_distance(0, 4), None);
+        let forest = g.dfs_forest();
+        assert_eq!(forest.num_edges(), g.n - g.num_components());
+        assert_eq!(forest.parent[5], Some(4));
+        assert_eq!(g.bfs_forest().to_graph("f".to_string()).m, 4);
+    }
+}
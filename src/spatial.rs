@@ -0,0 +1,235 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::normal_graph::NormalUndiGraph;
+
+const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Metric {
+    Euclidean,
+    // coordinates are (latitude, longitude) in degrees, distances in km
+    Haversine,
+}
+
+impl Metric {
+    pub fn distance(&self, a: (f64, f64), b: (f64, f64)) -> f64 {
+        match self {
+            Metric::Euclidean => ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt(),
+            Metric::Haversine => {
+                let (lat1, lat2) = (a.0.to_radians(), b.0.to_radians());
+                let (dlat, dlon) = ((b.0 - a.0).to_radians(), (b.1 - a.1).to_radians());
+                let h = (dlat / 2.0).sin().powi(2)
+                    + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+                2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()
+            }
+        }
+    }
+}
+
+pub struct SpatialGraph {
+    pub graph: NormalUndiGraph,
+    pub coords: Vec<(f64, f64)>,
+    pub metric: Metric,
+}
+
+// min-heap entry on an f64 priority, shared with other Dijkstra-style searches
+pub(crate) struct HeapEntry {
+    pub(crate) priority: f64,
+    pub(crate) node: usize,
+}
+
+impl PartialEq for HeapEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for HeapEntry {}
+
+impl PartialOrd for HeapEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for HeapEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .total_cmp(&self.priority)
+            .then(self.node.cmp(&other.node))
+    }
+}
+
+impl SpatialGraph {
+    pub fn new(
+        graph: NormalUndiGraph,
+        coords: Vec<(f64, f64)>,
+        metric: Metric,
+    ) -> Result<Self, String> {
+        if coords.len() != graph.n {
+            return Err(format!(
+                "Graph '{}' has {} nodes but {} coordinates were given",
+                graph.name,
+                graph.n,
+                coords.len()
+            ));
+        }
+        Ok(Self {
+            graph,
+            coords,
+            metric,
+        })
+    }
+    pub fn distance(&self, u: usize, v: usize) -> f64 {
+        self.metric.distance(self.coords[u], self.coords[v])
+    }
+    pub fn edge_lengths(&self) -> Vec<(usize, usize, f64)> {
+        let mut lengths = Vec::with_capacity(self.graph.m);
+        for (u, adj) in self.graph.adjs.iter().enumerate() {
+            let i = adj.partition_point(|v| v <= &u);
+            for &v in adj[i..].iter() {
+                lengths.push((u, v, self.distance(u, v)));
+            }
+        }
+        lengths
+    }
+    /// Histogram of edge lengths over `bins` equal-width bins, as
+    /// `(lower, upper, count)` triples.
+    pub fn edge_length_histogram(&self, bins: usize) -> Vec<(f64, f64, usize)> {
+        let lengths: Vec<f64> = self.edge_lengths().into_iter().map(|e| e.2).collect();
+        if lengths.is_empty() || bins == 0 {
+            return Vec::new();
+        }
+        let lo = lengths.iter().copied().fold(f64::INFINITY, f64::min);
+        let hi = lengths.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = if hi > lo {
+            (hi - lo) / bins as f64
+        } else {
+            1.0
+        };
+        let mut counts = vec![0usize; bins];
+        for x in lengths {
+            counts[(((x - lo) / width) as usize).min(bins - 1)] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| (lo + i as f64 * width, lo + (i + 1) as f64 * width, c))
+            .collect()
+    }
+    /// A* search over edge lengths with the straight-line distance as heuristic.
+    /// Returns the path length and the node sequence from `source` to `target`.
+    pub fn astar(&self, source: usize, target: usize) -> Option<(f64, Vec<usize>)> {
+        let n = self.graph.n;
+        let mut dist = vec![f64::INFINITY; n];
+        let mut parent = vec![usize::MAX; n];
+        let mut closed = vec![false; n];
+        let mut heap = BinaryHeap::new();
+        dist[source] = 0.0;
+        heap.push(HeapEntry {
+            priority: self.distance(source, target),
+            node: source,
+        });
+        while let Some(HeapEntry { node: u, .. }) = heap.pop() {
+            if u == target {
+                let mut path = vec![target];
+                while *path.last().unwrap() != source {
+                    path.push(parent[*path.last().unwrap()]);
+                }
+                path.reverse();
+                return Some((dist[target], path));
+            }
+            if closed[u] {
+                continue;
+            }
+            closed[u] = true;
+            for &v in &self.graph.adjs[u] {
+                let d = dist[u] + self.distance(u, v);
+                if d < dist[v] {
+                    dist[v] = d;
+                    parent[v] = u;
+                    heap.push(HeapEntry {
+                        priority: d + self.distance(v, target),
+                        node: v,
+                    });
+                }
+            }
+        }
+        None
+    }
+    /// Random geometric graph on the unit square: `n` uniform points joined
+    /// whenever their Euclidean distance is at most `radius`.
+    pub fn from_random_geometric(n: usize, radius: f64, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let coords: Vec<(f64, f64)> = (0..n).map(|_| (rng.gen(), rng.gen())).collect();
+        let cell = |(x, y): (f64, f64)| ((x / radius) as i64, (y / radius) as i64);
+        let mut grid: HashMap<(i64, i64), Vec<usize>> = HashMap::new();
+        for (u, &c) in coords.iter().enumerate() {
+            grid.entry(cell(c)).or_default().push(u);
+        }
+        let mut edges = Vec::new();
+        for (u, &c) in coords.iter().enumerate() {
+            let (cx, cy) = cell(c);
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    for &v in grid.get(&(cx + dx, cy + dy)).into_iter().flatten() {
+                        if u < v && Metric::Euclidean.distance(c, coords[v]) <= radius {
+                            edges.push((u, v));
+                        }
+                    }
+                }
+            }
+        }
+        let graph = NormalUndiGraph::from_edges(format!("RGG_{}_{}", n, radius), n, &edges);
+        Self {
+            graph,
+            coords,
+            metric: Metric::Euclidean,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_haversine() {
+        let paris = (48.8566, 2.3522);
+        let london = (51.5074, -0.1278);
+        let d = Metric::Haversine.distance(paris, london);
+        assert!((d - 343.5).abs() < 1.0);
+    }
+
+    #[test]
+    fn test_random_geometric_edges() {
+        let g = SpatialGraph::from_random_geometric(300, 0.1, 5);
+        assert!(g.edge_lengths().iter().all(|e| e.2 <= 0.1));
+        let mut brute = 0;
+        for u in 0..g.graph.n {
+            for v in u + 1..g.graph.n {
+                if g.distance(u, v) <= 0.1 {
+                    brute += 1;
+                }
+            }
+        }
+        assert_eq!(brute, g.graph.m);
+        let hist = g.edge_length_histogram(5);
+        assert_eq!(hist.iter().map(|h| h.2).sum::<usize>(), g.graph.m);
+    }
+
+    #[test]
+    fn test_astar_path() {
+        let graph =
+            NormalUndiGraph::from_edges("square".to_string(), 4, &[(0, 1), (1, 2), (0, 3), (3, 2)]);
+        let coords = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 3.0)];
+        let g = SpatialGraph::new(graph, coords, Metric::Euclidean).unwrap();
+        let (d, path) = g.astar(0, 2).unwrap();
+        assert!((d - 2.0).abs() < 1e-12);
+        assert_eq!(path, vec![0, 1, 2]);
+    }
+}
@@ -0,0 +1,230 @@
+use nalgebra as na;
+
+use super::linalg::{extreme_eigenpairs, EigenOptions};
+use super::normal_graph::NormalUndiGraph;
+
+pub const DEFAULT_SPECTRUM_MAX_NODES: usize = 5000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectrumMatrix {
+    Adjacency,
+    Laplacian,
+    NormalizedLaplacian,
+}
+
+impl NormalUndiGraph {
+    pub fn spectrum_matrix(&self, kind: SpectrumMatrix) -> na::DMatrix<f64> {
+        let (diag, adj) = self.diag_adj();
+        match kind {
+            SpectrumMatrix::Adjacency => adj,
+            SpectrumMatrix::Laplacian => na::DMatrix::from_diagonal(&diag) - adj,
+            SpectrumMatrix::NormalizedLaplacian => {
+                let inv_sqrt = diag.map(|d| if d > 0.0 { 1.0 / d.sqrt() } else { 0.0 });
+                let mut mat = -adj;
+                for i in 0..self.n {
+                    for j in 0..self.n {
+                        mat[(i, j)] *= inv_sqrt[i] * inv_sqrt[j];
+                    }
+                    if diag[i] > 0.0 {
+                        mat[(i, i)] += 1.0;
+                    }
+                }
+                mat
+            }
+        }
+    }
+    /// Sorted eigenvalues of the chosen matrix, paired with their multiplicities.
+    /// Eigenvalues closer than `1e-8 * max(1, |λ|)` are merged into one entry.
+    pub fn full_spectrum(
+        &self,
+        kind: SpectrumMatrix,
+        max_nodes: usize,
+    ) -> Result<Vec<(f64, usize)>, String> {
+        if self.n > max_nodes {
+            return Err(format!(
+                "Graph '{}' has {} nodes, exceeding the dense spectrum cap of {}",
+                self.name, self.n, max_nodes
+            ));
+        }
+        let mut eigenvalues: Vec<f64> = self
+            .spectrum_matrix(kind)
+            .symmetric_eigenvalues()
+            .iter()
+            .copied()
+            .collect();
+        eigenvalues.sort_unstable_by(|a, b| a.total_cmp(b));
+        Ok(group_eigenvalues(&eigenvalues, 1e-8))
+    }
+    pub(crate) fn laplacian_apply(&self, x: &[f64], y: &mut [f64]) {
+        for (u, adj) in self.adjs.iter().enumerate() {
+            y[u] = adj.len() as f64 * x[u] - adj.iter().map(|&v| x[v]).sum::<f64>();
+        }
+    }
+    /// The `k` largest adjacency eigenvalues in decreasing order, by sparse
+    /// restarted Lanczos; see `extreme_eigenpairs`.
+    pub fn adjacency_eigenvalues(&self, k: usize) -> Result<Vec<f64>, String> {
+        let apply = |x: &[f64], y: &mut [f64]| self.adjacency_apply(x, y);
+        let pairs = extreme_eigenpairs(apply, self.n, k, true, &EigenOptions::default())?;
+        Ok(pairs.into_iter().map(|(x, _)| x).collect())
+    }
+    /// The `k` smallest Laplacian eigenvalues in increasing order, by sparse
+    /// restarted Lanczos; the first is 0.
+    pub fn laplacian_eigenvalues(&self, k: usize) -> Result<Vec<f64>, String> {
+        let apply = |x: &[f64], y: &mut [f64]| self.laplacian_apply(x, y);
+        let pairs = extreme_eigenpairs(apply, self.n, k, false, &EigenOptions::default())?;
+        Ok(pairs.into_iter().map(|(x, _)| x.max(0.0)).collect())
+    }
+    /// `n x k` matrix whose columns are the Laplacian eigenvectors of the
+    /// second to `k + 1`-th smallest eigenvalues, found matrix-free like
+    /// `laplacian_eigenvalues`. Only the first null vector is dropped, so on
+    /// disconnected graphs the leading columns separate the components.
+    /// Columns are unit vectors with their largest entry positive.
+    pub fn spectral_embedding(&self, k: usize) -> Result<na::DMatrix<f64>, String> {
+        let apply = |x: &[f64], y: &mut [f64]| self.laplacian_apply(x, y);
+        let pairs = extreme_eigenpairs(apply, self.n, k + 1, false, &EigenOptions::default())?;
+        let cols = pairs.len().saturating_sub(1);
+        let mut coords = na::DMatrix::zeros(self.n, cols);
+        for (j, (_, x)) in pairs.into_iter().skip(1).enumerate() {
+            let peak = x
+                .iter()
+                .copied()
+                .fold(0.0, |a: f64, b| if b.abs() > a.abs() { b } else { a });
+            let sign = if peak < 0.0 { -1.0 } else { 1.0 };
+            for (u, xu) in x.into_iter().enumerate() {
+                coords[(u, j)] = sign * xu;
+            }
+        }
+        Ok(coords)
+    }
+    /// Second smallest Laplacian eigenvalue, 0 exactly when disconnected.
+    pub fn algebraic_connectivity(&self) -> Result<f64, String> {
+        if self.n < 2 || self.num_components() > 1 {
+            return Ok(0.0);
+        }
+        Ok(self.laplacian_eigenvalues(2)?[1])
+    }
+    /// Gap between the two largest adjacency eigenvalues.
+    pub fn spectral_gap(&self) -> Result<f64, String> {
+        Ok(match self.adjacency_eigenvalues(2)?[..] {
+            [a, b] => a - b,
+            _ => 0.0,
+        })
+    }
+}
+
+pub fn group_eigenvalues(sorted: &[f64], tol: f64) -> Vec<(f64, usize)> {
+    let mut groups: Vec<(f64, usize, f64)> = Vec::new();
+    for &x in sorted {
+        match groups.last_mut() {
+            Some((mean, cnt, last)) if (x - *last).abs() <= tol * x.abs().max(1.0) => {
+                *mean += (x - *mean) / (*cnt + 1) as f64;
+                *cnt += 1;
+                *last = x;
+            }
+            _ => groups.push((x, 1, x)),
+        }
+    }
+    groups
+        .into_iter()
+        .map(|(mean, cnt, _)| (mean, cnt))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_spectrum_complete() {
+        let g = NormalUndiGraph::from_apollo(0);
+        let spec = g
+            .full_spectrum(SpectrumMatrix::Adjacency, DEFAULT_SPECTRUM_MAX_NODES)
+            .unwrap();
+        assert_eq!(spec.len(), 2);
+        assert!((spec[0].0 + 1.0).abs() < 1e-9 && spec[0].1 == 3);
+        assert!((spec[1].0 - 3.0).abs() < 1e-9 && spec[1].1 == 1);
+    }
+
+    #[test]
+    fn test_full_spectrum_laplacian() {
+        let g = NormalUndiGraph::from_pseudofractal(3);
+        let spec = g
+            .full_spectrum(SpectrumMatrix::Laplacian, DEFAULT_SPECTRUM_MAX_NODES)
+            .unwrap();
+        assert!(spec[0].0.abs() < 1e-9);
+        assert_eq!(spec[0].1, 1);
+        assert_eq!(spec.iter().map(|(_, k)| k).sum::<usize>(), g.n);
+        let norm = g
+            .full_spectrum(
+                SpectrumMatrix::NormalizedLaplacian,
+                DEFAULT_SPECTRUM_MAX_NODES,
+            )
+            .unwrap();
+        assert!(norm.last().unwrap().0 <= 2.0 + 1e-9);
+    }
+
+    #[test]
+    fn test_sparse_eigenvalues() {
+        for g in [
+            NormalUndiGraph::from_pseudofractal(4),
+            NormalUndiGraph::from_koch(2),
+        ] {
+            let dense = |kind| -> Vec<f64> {
+                let mut eig: Vec<f64> = g
+                    .spectrum_matrix(kind)
+                    .symmetric_eigenvalues()
+                    .iter()
+                    .copied()
+                    .collect();
+                eig.sort_unstable_by(|a, b| a.total_cmp(b));
+                eig
+            };
+            let lap = dense(SpectrumMatrix::Laplacian);
+            for (a, b) in g.laplacian_eigenvalues(4).unwrap().iter().zip(&lap) {
+                assert!((a - b).abs() < 1e-6);
+            }
+            let adj = dense(SpectrumMatrix::Adjacency);
+            for (a, b) in g
+                .adjacency_eigenvalues(4)
+                .unwrap()
+                .iter()
+                .zip(adj.iter().rev())
+            {
+                assert!((a - b).abs() < 1e-6);
+            }
+            assert!((g.algebraic_connectivity().unwrap() - lap[1]).abs() < 1e-6);
+            assert!((g.spectral_gap().unwrap() - (adj[g.n - 1] - adj[g.n - 2])).abs() < 1e-6);
+        }
+        let two = NormalUndiGraph::from_edges("2K2".to_string(), 4, &[(0, 1), (2, 3)]);
+        assert_eq!(two.algebraic_connectivity(), Ok(0.0));
+    }
+
+    #[test]
+    fn test_spectral_embedding() {
+        let g = NormalUndiGraph::from_koch(2);
+        let coords = g.spectral_embedding(3).unwrap();
+        assert_eq!(coords.shape(), (g.n, 3));
+        let gram = coords.transpose() * &coords;
+        assert!((gram - na::DMatrix::identity(3, 3)).norm() < 1e-6);
+        let lap = g.spectrum_matrix(SpectrumMatrix::Laplacian);
+        let eig = g.laplacian_eigenvalues(4).unwrap();
+        for j in 0..3 {
+            let x = coords.column(j);
+            // nontrivial: orthogonal to the constant vector
+            assert!(x.sum().abs() < 1e-6);
+            assert!((&lap * x - eig[j + 1] * x).norm() < 1e-5);
+        }
+        // the Fiedler vector of a path is monotone along it
+        let edges: Vec<(usize, usize)> = (1..10).map(|u| (u - 1, u)).collect();
+        let path = NormalUndiGraph::from_edges("P10".to_string(), 10, &edges);
+        let fiedler = path.spectral_embedding(1).unwrap();
+        let x: Vec<f64> = fiedler.column(0).iter().copied().collect();
+        assert!(x.windows(2).all(|w| w[0] < w[1]) || x.windows(2).all(|w| w[0] > w[1]));
+    }
+
+    #[test]
+    fn test_full_spectrum_cap() {
+        let g = NormalUndiGraph::from_koch(2);
+        assert!(g.full_spectrum(SpectrumMatrix::Adjacency, 10).is_err());
+    }
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fs::{read_dir, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use super::general_graph::GeneralUndiGraph;
+use super::io::GraphFormat;
+use super::normal_graph::NormalUndiGraph;
+use super::reports::{write_summary_table, GraphSummary};
+
+/// Loads every file in `dir` matching `format`, in file-name order, and
+/// returns the summary of each graph named after its file stem.
+pub fn scan_dir<P: AsRef<Path>>(dir: P, format: GraphFormat) -> Result<Vec<GraphSummary>, String> {
+    let dir = dir.as_ref();
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for entry in read_dir(dir).or(Err(format!("Failed to read dir '{}'", dir.display())))? {
+        let path = entry
+            .or(Err(format!(
+                "Failed to traverse entry of '{}'",
+                dir.display()
+            )))?
+            .path();
+        if path.is_file() && format.matches(&path) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let g = GeneralUndiGraph::read_path(path, format).map_err(|e| e.to_string())?;
+            Ok(NormalUndiGraph::from_general(&g).summary())
+        })
+        .collect()
+}
+
+/// `scan_dir` followed by writing the combined table as CSV to `out`.
+pub fn scan_dir_to_csv<P: AsRef<Path>, Q: AsRef<Path>>(
+    dir: P,
+    format: GraphFormat,
+    out: Q,
+) -> Result<Vec<GraphSummary>, String> {
+    let summaries = scan_dir(dir, format)?;
+    write_summary_table(&out, &summaries).or(Err(format!(
+        "Failed to write summary table to '{}'",
+        out.as_ref().display()
+    )))?;
+    Ok(summaries)
+}
+
+/// Degree statistics of a graph, cheap enough to compute right after loading.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GraphStats {
+    pub n: usize,
+    pub m: usize,
+    pub min_degree: usize,
+    pub max_degree: usize,
+    pub average_degree: f64,
+    // fraction of the n(n - 1)/2 possible edges present, 0 below two nodes
+    pub density: f64,
+}
+
+// `dist[d]` is the number of nodes of degree `d`
+fn distribution<I: Iterator<Item = usize>>(degrees: I) -> Vec<usize> {
+    let mut dist = Vec::new();
+    for d in degrees {
+        if d >= dist.len() {
+            dist.resize(d + 1, 0);
+        }
+        dist[d] += 1;
+    }
+    dist
+}
+
+fn average_degree(n: usize, m: usize) -> f64 {
+    if n == 0 {
+        0.0
+    } else {
+        2.0 * m as f64 / n as f64
+    }
+}
+
+fn density(n: usize, m: usize) -> f64 {
+    if n < 2 {
+        0.0
+    } else {
+        2.0 * m as f64 / (n as f64 * (n - 1) as f64)
+    }
+}
+
+fn stats_from(n: usize, m: usize, dist: &[usize]) -> GraphStats {
+    GraphStats {
+        n,
+        m,
+        min_degree: dist.iter().position(|&c| c > 0).unwrap_or(0),
+        max_degree: dist.len().saturating_sub(1),
+        average_degree: average_degree(n, m),
+        density: density(n, m),
+    }
+}
+
+/// Writes the non-empty bins of `dist` as `degree<TAB>count` lines.
+pub fn write_degree_distribution<P: AsRef<Path>>(path: P, dist: &[usize]) -> io::Result<()> {
+    let mut wf = BufWriter::new(File::create(path)?);
+    writeln!(wf, "degree\tcount")?;
+    for (d, &count) in dist.iter().enumerate().filter(|(_, &c)| c > 0) {
+        writeln!(wf, "{}\t{}", d, count)?;
+    }
+    wf.flush()
+}
+
+impl NormalUndiGraph {
+    /// Number of nodes of every degree, indexed by degree.
+    pub fn degree_distribution(&self) -> Vec<usize> {
+        distribution(self.adjs.iter().map(|adj| adj.len()))
+    }
+    pub fn average_degree(&self) -> f64 {
+        average_degree(self.n, self.m)
+    }
+    pub fn max_degree(&self) -> usize {
+        self.adjs.iter().map(|adj| adj.len()).max().unwrap_or(0)
+    }
+    pub fn density(&self) -> f64 {
+        density(self.n, self.m)
+    }
+    pub fn stats(&self) -> GraphStats {
+        stats_from(self.n, self.m, &self.degree_distribution())
+    }
+}
+
+impl GeneralUndiGraph {
+    fn degree_map(&self) -> HashMap<usize, usize> {
+        let mut degrees: HashMap<usize, usize> = self.nodes.iter().map(|&u| (u, 0)).collect();
+        for &(u, v) in &self.edges {
+            *degrees.entry(u).or_default() += 1;
+            *degrees.entry(v).or_default() += 1;
+        }
+        degrees
+    }
+    /// Number of nodes of every degree, indexed by degree.
+    pub fn degree_distribution(&self) -> Vec<usize> {
+        distribution(self.degree_map().into_values())
+    }
+    pub fn average_degree(&self) -> f64 {
+        average_degree(self.num_nodes(), self.num_edges())
+    }
+    pub fn max_degree(&self) -> usize {
+        self.degree_map().into_values().max().unwrap_or(0)
+    }
+    pub fn density(&self) -> f64 {
+        density(self.num_nodes(), self.num_edges())
+    }
+    pub fn stats(&self) -> GraphStats {
+        stats_from(
+            self.num_nodes(),
+            self.num_edges(),
+            &self.degree_distribution(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_scan_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("b.txt"), "0 1\n1 2\n2 0\n").unwrap();
+        fs::write(dir.path().join("a.txt"), "# path\n0 1\n1 2\n5 6\n").unwrap();
+        fs::write(dir.path().join("notes.md"), "not a graph").unwrap();
+        let out = dir.path().join("table.csv");
+        let summaries = scan_dir_to_csv(dir.path(), GraphFormat::EdgeList, &out).unwrap();
+        let names: Vec<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!((summaries[0].n, summaries[0].num_components), (5, 2));
+        assert_eq!(summaries[1].average_clustering, 1.0);
+        let table = fs::read_to_string(out).unwrap();
+        assert_eq!(table.lines().count(), 3);
+        fs::write(dir.path().join("c.txt"), "0 1\noops\n").unwrap();
+        assert!(scan_dir(dir.path(), GraphFormat::EdgeList).is_err());
+    }
+
+    #[test]
+    fn test_degree_stats() {
+        // a star on 4 nodes plus an isolated node
+        let mut general = GeneralUndiGraph::new("g".to_string());
+        for v in 1..4 {
+            general.add_edge(0, v);
+        }
+        general.nodes.insert(9);
+        assert_eq!(general.degree_distribution(), vec![1, 3, 0, 1]);
+        let stats = general.stats();
+        assert_eq!(
+            (stats.n, stats.m, stats.min_degree, stats.max_degree),
+            (5, 3, 0, 3)
+        );
+        assert_eq!(stats.average_degree, 1.2);
+        assert_eq!(stats.density, 0.3);
+        let normal = NormalUndiGraph::from_general(&general);
+        assert_eq!(normal.stats(), stats);
+        assert_eq!(normal.max_degree(), general.max_degree());
+
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("dist.tsv");
+        write_degree_distribution(&out, &normal.degree_distribution()).unwrap();
+        assert_eq!(
+            fs::read_to_string(out).unwrap(),
+            "degree\tcount\n0\t1\n1\t3\n3\t1\n"
+        );
+    }
+}
@@ -0,0 +1,141 @@
+use super::normal_graph::NormalUndiGraph;
+
+// at most this many offending nodes are listed in a failure message
+const MAX_LISTED: usize = 10;
+
+fn listing<T: std::fmt::Debug>(items: &[T]) -> String {
+    let shown: Vec<String> = items
+        .iter()
+        .take(MAX_LISTED)
+        .map(|x| format!("{:?}", x))
+        .collect();
+    let more = items.len().saturating_sub(MAX_LISTED);
+    if more > 0 {
+        format!("[{}, ... {} more]", shown.join(", "), more)
+    } else {
+        format!("[{}]", shown.join(", "))
+    }
+}
+
+/// Panics unless `g` has a single connected component, listing the
+/// component sizes otherwise.
+#[track_caller]
+pub fn assert_connected(g: &NormalUndiGraph) {
+    let mut sizes = g.component_sizes();
+    if sizes.len() > 1 {
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        panic!(
+            "graph '{}' is not connected: {} components of sizes {}",
+            g.name,
+            sizes.len(),
+            listing(&sizes)
+        );
+    }
+}
+
+/// Panics unless every degree lies in `min..=max`, listing the offending
+/// `(node, degree)` pairs otherwise.
+#[track_caller]
+pub fn assert_degree_bounds(g: &NormalUndiGraph, min: usize, max: usize) {
+    let bad: Vec<(usize, usize)> = g
+        .adjs
+        .iter()
+        .enumerate()
+        .map(|(u, adj)| (u, adj.len()))
+        .filter(|&(_, d)| d < min || d > max)
+        .collect();
+    if !bad.is_empty() {
+        panic!(
+            "graph '{}' has {} nodes with degree outside {}..={}: {}",
+            g.name,
+            bad.len(),
+            min,
+            max,
+            listing(&bad)
+        );
+    }
+}
+
+/// Panics if any node lists itself as a neighbour.
+#[track_caller]
+pub fn assert_no_self_loops(g: &NormalUndiGraph) {
+    let bad: Vec<usize> = (0..g.n)
+        .filter(|&u| g.adjs[u].binary_search(&u).is_ok())
+        .collect();
+    if !bad.is_empty() {
+        panic!(
+            "graph '{}' has self-loops at {} nodes: {}",
+            g.name,
+            bad.len(),
+            listing(&bad)
+        );
+    }
+}
+
+/// Panics unless `g.m == m` and the adjacency lists hold `2m` entries.
+#[track_caller]
+pub fn assert_edge_count(g: &NormalUndiGraph, m: usize) {
+    let entries: usize = g.adjs.iter().map(|adj| adj.len()).sum();
+    if g.m != m || entries != 2 * m {
+        panic!(
+            "graph '{}' expected {} edges, found m = {} with {} adjacency entries",
+            g.name, m, g.m, entries
+        );
+    }
+}
+
+/// Checks graph properties with diagnostics on failure:
+/// `assert_graph!(g, connected)`, `assert_graph!(g, no_self_loops)`,
+/// `assert_graph!(g, edges == m)` and `assert_graph!(g, degree in lo..=hi)`.
+#[macro_export]
+macro_rules! assert_graph {
+    ($g:expr, connected) => {
+        $crate::testing::assert_connected(&$g)
+    };
+    ($g:expr, no_self_loops) => {
+        $crate::testing::assert_no_self_loops(&$g)
+    };
+    ($g:expr, edges == $m:expr) => {
+        $crate::testing::assert_edge_count(&$g, $m)
+    };
+    ($g:expr, degree in $lo:tt..=$hi:tt) => {
+        $crate::testing::assert_degree_bounds(&$g, $lo, $hi)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assertions_pass() {
+        let g = NormalUndiGraph::from_koch(2);
+        crate::assert_graph!(g, connected);
+        crate::assert_graph!(g, no_self_loops);
+        crate::assert_graph!(g, edges == 48);
+        crate::assert_graph!(g, degree in 2..=8);
+    }
+
+    #[test]
+    fn test_assertion_messages() {
+        let g = NormalUndiGraph::from_edges("g".to_string(), 14, &[(0, 1), (2, 3)]);
+        let message = |f: &dyn Fn()| {
+            let err = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_err();
+            err.downcast_ref::<String>().unwrap().clone()
+        };
+        assert_eq!(
+            message(&|| assert_connected(&g)),
+            "graph 'g' is not connected: 12 components of sizes \
+             [2, 2, 1, 1, 1, 1, 1, 1, 1, 1, ... 2 more]"
+        );
+        assert_eq!(
+            message(&|| assert_degree_bounds(&g, 1, 1)),
+            "graph 'g' has 10 nodes with degree outside 1..=1: \
+             [(4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0), (11, 0), (12, 0), (13, 0)]"
+        );
+        assert!(message(&|| assert_edge_count(&g, 3)).contains("found m = 2"));
+        let mut looped = g;
+        looped.adjs[5].push(5);
+        assert!(message(&|| assert_no_self_loops(&looped)).contains("[5]"));
+    }
+}
@@ -0,0 +1,194 @@
+use super::normal_graph::NormalUndiGraph;
+
+pub const DEFAULT_HAMILTONIAN_MAX_NODES: usize = 64;
+
+impl NormalUndiGraph {
+    fn odd_degree_nodes(&self) -> Vec<usize> {
+        (0..self.n)
+            .filter(|&u| self.adjs[u].len() % 2 == 1)
+            .collect()
+    }
+    // All nodes with at least one edge lie in a single component.
+    fn edges_connected(&self) -> bool {
+        let Some(start) = (0..self.n).find(|&u| !self.adjs[u].is_empty()) else {
+            return true;
+        };
+        let dist = self.distances_from(start);
+        (0..self.n).all(|u| self.adjs[u].is_empty() || dist[u] != usize::MAX)
+    }
+    pub fn has_eulerian_circuit(&self) -> bool {
+        self.odd_degree_nodes().is_empty() && self.edges_connected()
+    }
+    pub fn has_eulerian_path(&self) -> bool {
+        self.odd_degree_nodes().len() <= 2 && self.edges_connected()
+    }
+    // Hierholzer's algorithm from `start`, assuming an Eulerian trail exists.
+    fn hierholzer(&self, start: usize) -> Vec<usize> {
+        let mut edge_id: Vec<Vec<usize>> = self.adjs.iter().map(|adj| vec![0; adj.len()]).collect();
+        let mut cnt = 0;
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for (i, &v) in adj.iter().enumerate() {
+                if u < v {
+                    let j = self.adjs[v].binary_search(&u).unwrap();
+                    edge_id[u][i] = cnt;
+                    edge_id[v][j] = cnt;
+                    cnt += 1;
+                }
+            }
+        }
+        let mut used = vec![false; cnt];
+        let mut next = vec![0usize; self.n];
+        let mut stack = vec![start];
+        let mut trail = Vec::with_capacity(cnt + 1);
+        while let Some(&u) = stack.last() {
+            while next[u] < self.adjs[u].len() && used[edge_id[u][next[u]]] {
+                next[u] += 1;
+            }
+            if next[u] == self.adjs[u].len() {
+                trail.push(u);
+                stack.pop();
+            } else {
+                used[edge_id[u][next[u]]] = true;
+                stack.push(self.adjs[u][next[u]]);
+            }
+        }
+        trail.reverse();
+        trail
+    }
+    /// Closed walk using every edge exactly once, starting at the smallest
+    /// non-isolated node.
+    pub fn eulerian_circuit(&self) -> Option<Vec<usize>> {
+        if !self.has_eulerian_circuit() {
+            return None;
+        }
+        let start = (0..self.n).find(|&u| !self.adjs[u].is_empty())?;
+        Some(self.hierholzer(start))
+    }
+    pub fn eulerian_path(&self) -> Option<Vec<usize>> {
+        if !self.has_eulerian_path() {
+            return None;
+        }
+        let start = match self.odd_degree_nodes().first() {
+            Some(&u) => u,
+            None => (0..self.n).find(|&u| !self.adjs[u].is_empty())?,
+        };
+        Some(self.hierholzer(start))
+    }
+    /// Backtracking search with Warnsdorff ordering (fewest unvisited
+    /// neighbours first). Exponential in the worst case, hence the node cap.
+    pub fn hamiltonian_path(&self, max_nodes: usize) -> Result<Option<Vec<usize>>, String> {
+        if self.n > max_nodes {
+            return Err(format!(
+                "Graph '{}' has {} nodes, exceeding the Hamiltonian search cap of {}",
+                self.name, self.n, max_nodes
+            ));
+        }
+        if self.n == 0 {
+            return Ok(Some(Vec::new()));
+        }
+        let mut starts: Vec<usize> = (0..self.n).collect();
+        starts.sort_by_key(|&u| (self.adjs[u].len(), u));
+        let mut visited = vec![false; self.n];
+        let mut path = Vec::with_capacity(self.n);
+        for s in starts {
+            visited[s] = true;
+            path.push(s);
+            if self.extend_hamiltonian(&mut path, &mut visited) {
+                return Ok(Some(path));
+            }
+            path.pop();
+            visited[s] = false;
+        }
+        Ok(None)
+    }
+    fn extend_hamiltonian(&self, path: &mut Vec<usize>, visited: &mut [bool]) -> bool {
+        if path.len() == self.n {
+            return true;
+        }
+        let u = *path.last().unwrap();
+        let free =
+            |v: usize, visited: &[bool]| self.adjs[v].iter().filter(|&&w| !visited[w]).count();
+        let mut candidates: Vec<usize> = self.adjs[u]
+            .iter()
+            .copied()
+            .filter(|&v| !visited[v])
+            .collect();
+        candidates.sort_by_key(|&v| (free(v, visited), v));
+        for v in candidates {
+            visited[v] = true;
+            path.push(v);
+            if self.extend_hamiltonian(path, visited) {
+                return true;
+            }
+            path.pop();
+            visited[v] = false;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_trail(g: &NormalUndiGraph, walk: &[usize]) -> bool {
+        let mut edges: Vec<(usize, usize)> = walk
+            .windows(2)
+            .map(|w| (w[0].min(w[1]), w[0].max(w[1])))
+            .collect();
+        let len = edges.len();
+        edges.sort_unstable();
+        edges.dedup();
+        edges.len() == len
+            && edges
+                .iter()
+                .all(|&(u, v)| g.adjs[u].binary_search(&v).is_ok())
+    }
+
+    #[test]
+    fn test_eulerian_circuit() {
+        // every node of a Koch network has even degree
+        let g = NormalUndiGraph::from_koch(2);
+        assert!(g.has_eulerian_circuit());
+        let circuit = g.eulerian_circuit().unwrap();
+        assert_eq!(circuit.len(), g.m + 1);
+        assert_eq!(circuit.first(), circuit.last());
+        assert!(is_trail(&g, &circuit));
+        assert!(!NormalUndiGraph::from_apollo(1).has_eulerian_circuit());
+    }
+
+    #[test]
+    fn test_eulerian_path() {
+        let g = NormalUndiGraph::from_edges(
+            "house".to_string(),
+            5,
+            &[(0, 1), (1, 2), (2, 3), (3, 0), (2, 4), (3, 4), (0, 2)],
+        );
+        assert!(!g.has_eulerian_circuit());
+        let path = g.eulerian_path().unwrap();
+        assert_eq!(path.len(), g.m + 1);
+        assert!(is_trail(&g, &path));
+    }
+
+    #[test]
+    fn test_hamiltonian_path() {
+        let g = NormalUndiGraph::from_pseudofractal(1);
+        let path = g
+            .hamiltonian_path(DEFAULT_HAMILTONIAN_MAX_NODES)
+            .unwrap()
+            .unwrap();
+        let mut sorted = path.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..g.n).collect::<Vec<_>>());
+        assert!(path
+            .windows(2)
+            .all(|w| g.adjs[w[0]].binary_search(&w[1]).is_ok()));
+        // a star with three leaves has no Hamiltonian path
+        let star = NormalUndiGraph::from_edges("star".to_string(), 4, &[(0, 1), (0, 2), (0, 3)]);
+        assert_eq!(star.hamiltonian_path(10).unwrap(), None);
+        assert!(g.hamiltonian_path(3).is_err());
+        // three pendant triangles hang off cut vertices, but a path has two ends
+        let koch = NormalUndiGraph::from_koch(1);
+        assert_eq!(koch.hamiltonian_path(20).unwrap(), None);
+    }
+}
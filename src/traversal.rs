@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Visit {
+    pub node: usize,
+    // None for the start node
+    pub parent: Option<usize>,
+    pub depth: usize,
+}
+
+/// Breadth-first iterator over the component of the start node, neighbours
+/// visited in increasing order.
+pub struct Bfs<'a> {
+    g: &'a NormalUndiGraph,
+    queue: VecDeque<Visit>,
+    seen: Vec<bool>,
+}
+
+/// Depth-first iterator yielding nodes in preorder, neighbours tried in
+/// increasing order.
+pub struct Dfs<'a> {
+    g: &'a NormalUndiGraph,
+    // visited nodes on the current path with the index of their next neighbour
+    stack: Vec<(Visit, usize)>,
+    seen: Vec<bool>,
+    start: Option<usize>,
+}
+
+impl<'a> Iterator for Bfs<'a> {
+    type Item = Visit;
+    fn next(&mut self) -> Option<Visit> {
+        let visit = self.queue.pop_front()?;
+        for &v in &self.g.adjs[visit.node] {
+            if !self.seen[v] {
+                self.seen[v] = true;
+                self.queue.push_back(Visit {
+                    node: v,
+                    parent: Some(visit.node),
+                    depth: visit.depth + 1,
+                });
+            }
+        }
+        Some(visit)
+    }
+}
+
+impl<'a> Iterator for Dfs<'a> {
+    type Item = Visit;
+    fn next(&mut self) -> Option<Visit> {
+        if let Some(start) = self.start.take() {
+            let visit = Visit {
+                node: start,
+                parent: None,
+                depth: 0,
+            };
+            self.seen[start] = true;
+            self.stack.push((visit, 0));
+            return Some(visit);
+        }
+        while let Some((top, i)) = self.stack.last_mut() {
+            let u = top.node;
+            let depth = top.depth;
+            match self.g.adjs[u].get(*i) {
+                Some(&v) => {
+                    *i += 1;
+                    if !self.seen[v] {
+                        self.seen[v] = true;
+                        let visit = Visit {
+                            node: v,
+                            parent: Some(u),
+                            depth: depth + 1,
+                        };
+                        self.stack.push((visit, 0));
+                        return Some(visit);
+                    }
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+        None
+    }
+}
+
+impl NormalUndiGraph {
+    pub fn bfs(&self, start: usize) -> Bfs<'_> {
+        let mut seen = vec![false; self.n];
+        seen[start] = true;
+        Bfs {
+            g: self,
+            queue: VecDeque::from([Visit {
+                node: start,
+                parent: None,
+                depth: 0,
+            }]),
+            seen,
+        }
+    }
+    pub fn dfs(&self, start: usize) -> Dfs<'_> {
+        Dfs {
+            g: self,
+            stack: Vec::new(),
+            seen: vec![false; self.n],
+            start: Some(start),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bfs_dfs() {
+        let g = NormalUndiGraph::from_edges(
+            "g".to_string(),
+            6,
+            &[(0, 1), (0, 2), (1, 3), (2, 3), (4, 5)],
+        );
+        let order: Vec<usize> = g.bfs(0).map(|visit| visit.node).collect();
+        assert_eq!(order, vec![0, 1, 2, 3]);
+        let dist = g.distances_from(0);
+        assert!(g.bfs(0).all(|visit| visit.depth == dist[visit.node]));
+        let order: Vec<usize> = g.dfs(0).map(|visit| visit.node).collect();
+        assert_eq!(order, vec![0, 1, 3, 2]);
+        let tree = g.dfs_tree(0);
+        for visit in g.dfs(0) {
+            assert_eq!(visit.parent, tree.parent[visit.node]);
+            assert_eq!(visit.depth, tree.depth[visit.node]);
+        }
+        assert_eq!(g.bfs(4).count(), 2);
+    }
+}
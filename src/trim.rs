@@ -0,0 +1,113 @@
+use std::collections::VecDeque;
+
+use super::general_graph::GeneralUndiGraph;
+use super::normal_graph::NormalUndiGraph;
+
+impl NormalUndiGraph {
+    /// Removes nodes of degree above `max_deg` once, then nodes of degree
+    /// below `min_deg` until none is left, as removals lower the degrees of
+    /// their neighbours. Pass `usize::MAX` for no upper bound. Also returns
+    /// the node of this graph behind every kept node.
+    pub fn trim_by_degree(&self, min_deg: usize, max_deg: usize) -> (Self, Vec<usize>) {
+        let mut removed: Vec<bool> = self.adjs.iter().map(|adj| adj.len() > max_deg).collect();
+        let mut deg: Vec<usize> = (0..self.n)
+            .map(|u| self.adjs[u].iter().filter(|&&v| !removed[v]).count())
+            .collect();
+        let mut queue: VecDeque<usize> = (0..self.n)
+            .filter(|&u| !removed[u] && deg[u] < min_deg)
+            .collect();
+        for &u in &queue {
+            removed[u] = true;
+        }
+        while let Some(u) = queue.pop_front() {
+            for &v in &self.adjs[u] {
+                if removed[v] {
+                    continue;
+                }
+                deg[v] -= 1;
+                if deg[v] < min_deg {
+                    removed[v] = true;
+                    queue.push_back(v);
+                }
+            }
+        }
+        let kept: Vec<usize> = (0..self.n).filter(|&u| !removed[u]).collect();
+        let mut new_id = vec![usize::MAX; self.n];
+        for (i, &u) in kept.iter().enumerate() {
+            new_id[u] = i;
+        }
+        let adjs: Vec<Vec<usize>> = kept
+            .iter()
+            .map(|&u| {
+                self.adjs[u]
+                    .iter()
+                    .filter(|&&v| !removed[v])
+                    .map(|&v| new_id[v])
+                    .collect()
+            })
+            .collect();
+        let m = adjs.iter().map(|adj| adj.len()).sum::<usize>() / 2;
+        let g = Self {
+            name: self.name.clone(),
+            n: kept.len(),
+            m,
+            adjs,
+        };
+        (g, kept)
+    }
+}
+
+impl GeneralUndiGraph {
+    /// `NormalUndiGraph::trim_by_degree` keeping the node ids of this graph.
+    pub fn trim_by_degree(&self, min_deg: usize, max_deg: usize) -> Self {
+        let (normal, ids) = self.canonicalize();
+        let (trimmed, kept) = normal.trim_by_degree(min_deg, max_deg);
+        let mut g = Self::new(self.name.clone());
+        for (u, adj) in trimmed.adjs.iter().enumerate() {
+            let id = ids[kept[u]];
+            g.nodes.insert(id);
+            for &v in adj {
+                g.add_edge(id, ids[kept[v]]);
+            }
+        }
+        g
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_by_degree() {
+        // triangle 0-1-2 with a tail 2-3-4 and a star of leaves around 5
+        let mut g = GeneralUndiGraph::new("g".to_string());
+        for (u, v) in [
+            (0, 1),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 4),
+            (5, 6),
+            (5, 7),
+            (5, 8),
+            (5, 0),
+        ] {
+            g.add_edge(u, v);
+        }
+        // peeling leaves eats the whole tail, not just node 4
+        let core = g.trim_by_degree(2, usize::MAX);
+        assert_eq!(core.num_edges(), 3);
+        assert!(core.nodes.iter().all(|u| [0, 1, 2].contains(u)));
+        // dropping the hub 5 strands its leaves, which are then peeled
+        let no_hub = g.trim_by_degree(1, 3);
+        assert_eq!(no_hub.num_nodes(), 5);
+        assert_eq!(no_hub.num_edges(), 5);
+
+        let apollo = NormalUndiGraph::from_apollo(2);
+        let (same, kept) = apollo.trim_by_degree(3, usize::MAX);
+        assert_eq!((same.n, same.m), (apollo.n, apollo.m));
+        assert_eq!(kept, (0..apollo.n).collect::<Vec<_>>());
+        assert_eq!(apollo.trim_by_degree(4, usize::MAX).0.n, 0);
+    }
+}
@@ -0,0 +1,252 @@
+use nalgebra as na;
+use std::fmt;
+
+use super::normal_graph::NormalUndiGraph;
+
+#[derive(Clone)]
+pub struct WeightedUndiGraph {
+    pub name: String,
+    pub n: usize,
+    pub m: usize,
+    // sorted by neighbour
+    pub adjs: Vec<Vec<(usize, f64)>>,
+}
+
+impl fmt::Display for WeightedUndiGraph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "# WeightedUndiGraph: {}\n# Nodes: {} Edges: {}\n",
+            self.name, self.n, self.m
+        )?;
+        for (u, adj) in self.adjs.iter().enumerate() {
+            let i = adj.partition_point(|&(v, _)| v <= u);
+            for (v, w) in adj[i..].iter() {
+                writeln!(f, "{}\t{}\t{}", u, v, w)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl WeightedUndiGraph {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            n: 0,
+            m: 0,
+            adjs: Vec::new(),
+        }
+    }
+    /// Adds `w` to the weight of edge `(u, v)`, creating it and any missing
+    /// nodes up to `max(u, v)` if needed. Self-loops are ignored.
+    pub fn add_edge(&mut self, u: usize, v: usize, w: f64) {
+        if u == v {
+            return;
+        }
+        if u.max(v) >= self.n {
+            self.n = u.max(v) + 1;
+            self.adjs.resize_with(self.n, Vec::new);
+        }
+        let mut inserted = false;
+        for (a, b) in [(u, v), (v, u)] {
+            let adj = &mut self.adjs[a];
+            match adj.binary_search_by_key(&b, |&(x, _)| x) {
+                Ok(i) => adj[i].1 += w,
+                Err(i) => {
+                    adj.insert(i, (b, w));
+                    inserted = true;
+                }
+            }
+        }
+        if inserted {
+            self.m += 1;
+        }
+    }
+    /// Builds a graph from weighted edges, summing the weights of parallel
+    /// edges and dropping self-loops.
+    pub fn from_edges(name: String, n: usize, edges: &[(usize, usize, f64)]) -> Self {
+        let mut adjs: Vec<Vec<(usize, f64)>> = vec![Vec::new(); n];
+        for &(u, v, w) in edges {
+            if u != v {
+                adjs[u].push((v, w));
+                adjs[v].push((u, w));
+            }
+        }
+        let mut m = 0;
+        for adj in adjs.iter_mut() {
+            adj.sort_by_key(|&(v, _)| v);
+            let mut merged: Vec<(usize, f64)> = Vec::with_capacity(adj.len());
+            for &(v, w) in adj.iter() {
+                match merged.last_mut() {
+                    Some(last) if last.0 == v => last.1 += w,
+                    _ => merged.push((v, w)),
+                }
+            }
+            m += merged.len();
+            *adj = merged;
+        }
+        Self {
+            name,
+            n,
+            m: m / 2,
+            adjs,
+        }
+    }
+    /// Unit weight on every edge.
+    pub fn from_normal(g: &NormalUndiGraph) -> Self {
+        Self {
+            name: g.name.clone(),
+            n: g.n,
+            m: g.m,
+            adjs: g
+                .adjs
+                .iter()
+                .map(|adj| adj.iter().map(|&v| (v, 1.0)).collect())
+                .collect(),
+        }
+    }
+    pub fn weight(&self, u: usize, v: usize) -> Option<f64> {
+        let adj = &self.adjs[u];
+        adj.binary_search_by_key(&v, |&(x, _)| x)
+            .ok()
+            .map(|i| adj[i].1)
+    }
+    /// Weighted degree of every node.
+    pub fn strengths(&self) -> Vec<f64> {
+        self.adjs
+            .iter()
+            .map(|adj| adj.iter().map(|&(_, w)| w).sum())
+            .collect()
+    }
+    /// Barrat's weighted clustering: every triangle `(i, j, h)` through `i`
+    /// counts with weight `(w_ij + w_ih) / 2`, normalized by `s_i (k_i - 1)`.
+    /// Reduces to the local clustering coefficient for unit weights.
+    pub fn barrat_clustering(&self) -> Vec<f64> {
+        let strengths = self.strengths();
+        (0..self.n)
+            .map(|i| {
+                let adj = &self.adjs[i];
+                let k = adj.len();
+                if k < 2 || strengths[i] == 0.0 {
+                    return 0.0;
+                }
+                let mut total = 0.0;
+                for (a, &(j, wij)) in adj.iter().enumerate() {
+                    for &(h, wih) in &adj[a + 1..] {
+                        if self.weight(j, h).is_some() {
+                            total += wij + wih;
+                        }
+                    }
+                }
+                total / (strengths[i] * (k - 1) as f64)
+            })
+            .collect()
+    }
+    /// Degree assortativity with every edge weighted by its weight in the
+    /// Pearson correlation (Leung and Chau). NaN when undefined.
+    pub fn weighted_assortativity(&self) -> f64 {
+        let (mut total, mut sum, mut sum_sq, mut cross) = (0.0, 0.0, 0.0, 0.0);
+        for adj in &self.adjs {
+            let ku = adj.len() as f64;
+            for &(v, w) in adj {
+                let kv = self.adjs[v].len() as f64;
+                total += w;
+                sum += w * ku;
+                sum_sq += w * ku * ku;
+                cross += w * ku * kv;
+            }
+        }
+        let mean = sum / total;
+        (cross / total - mean * mean) / (sum_sq / total - mean * mean)
+    }
+    pub fn adjacency_matrix(&self) -> na::DMatrix<f64> {
+        let mut mat = na::DMatrix::zeros(self.n, self.n);
+        for (u, adj) in self.adjs.iter().enumerate() {
+            for &(v, w) in adj {
+                mat[(u, v)] = w;
+            }
+        }
+        mat
+    }
+    /// Weighted Laplacian `diag(strengths) - A`.
+    pub fn laplacian_matrix(&self) -> na::DMatrix<f64> {
+        na::DMatrix::from_diagonal(&na::DVector::from_vec(self.strengths()))
+            - self.adjacency_matrix()
+    }
+    pub fn total_weight(&self) -> f64 {
+        self.adjs
+            .iter()
+            .flat_map(|adj| adj.iter().map(|&(_, w)| w))
+            .sum::<f64>()
+            / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_metrics() {
+        let g = NormalUndiGraph::from_apollo(2);
+        let w = WeightedUndiGraph::from_normal(&g);
+        let degrees: Vec<f64> = g.adjs.iter().map(|adj| adj.len() as f64).collect();
+        assert_eq!(w.strengths(), degrees);
+        for (a, b) in w.barrat_clustering().iter().zip(g.local_clustering()) {
+            assert!((a - b).abs() < 1e-12);
+        }
+        assert!((w.weighted_assortativity() - g.degree_assortativity()).abs() < 1e-12);
+
+        // triangle 0-1-2 plus pendant 3 on node 0; heavy edge 0-3 lowers C_0
+        let g = WeightedUndiGraph::from_edges(
+            "w".to_string(),
+            4,
+            &[(0, 1, 1.0), (1, 2, 1.0), (2, 0, 1.0), (0, 3, 4.0)],
+        );
+        assert_eq!(g.strengths(), vec![6.0, 2.0, 2.0, 4.0]);
+        let c = g.barrat_clustering();
+        assert!((c[0] - 2.0 / 12.0).abs() < 1e-12);
+        assert_eq!((c[1], c[3]), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_add_edge() {
+        let mut g = WeightedUndiGraph::new("w".to_string());
+        g.add_edge(2, 0, 1.5);
+        g.add_edge(0, 2, 0.5);
+        g.add_edge(1, 2, 3.0);
+        g.add_edge(1, 1, 9.0);
+        assert_eq!((g.n, g.m), (3, 2));
+        assert_eq!(g.weight(0, 2), Some(2.0));
+        assert_eq!(g.adjs[2], vec![(0, 2.0), (1, 3.0)]);
+        let lap = g.laplacian_matrix();
+        assert_eq!(lap[(2, 2)], 5.0);
+        assert_eq!(lap[(0, 2)], -2.0);
+        assert!(lap.row_sum().iter().all(|&x| x == 0.0));
+        let unit = WeightedUndiGraph::from_normal(&NormalUndiGraph::from_koch(1));
+        assert_eq!(
+            unit.laplacian_matrix(),
+            NormalUndiGraph::from_koch(1)
+                .spectrum_matrix(crate::spectral::SpectrumMatrix::Laplacian)
+        );
+    }
+
+    #[test]
+    fn test_from_edges() {
+        let g = WeightedUndiGraph::from_edges(
+            "w".to_string(),
+            3,
+            &[(0, 1, 1.5), (1, 0, 0.5), (1, 2, 3.0), (2, 2, 9.0)],
+        );
+        assert_eq!(g.m, 2);
+        assert_eq!(g.weight(0, 1), Some(2.0));
+        assert_eq!(g.weight(2, 1), Some(3.0));
+        assert_eq!(g.weight(0, 2), None);
+        assert_eq!(g.total_weight(), 5.0);
+        assert_eq!(
+            format!("{}", g),
+            "# WeightedUndiGraph: w\n# Nodes: 3 Edges: 2\n0\t1\t2\n1\t2\t3\n"
+        );
+    }
+}